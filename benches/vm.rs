@@ -0,0 +1,171 @@
+// Reference rust implementation of AluVM (arithmetic logic unit virtual machine).
+// To find more on AluVM please check <https://aluvm.org>
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2021-2024 by
+//     Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2021-2022 LNP/BP Standards Association. All rights reserved.
+// Copyright (C) 2023-2024 UBIDECO Labs,
+//     Institute for Distributed and Cognitive Computing, Switzerland.
+//     All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Benchmarks of program execution by the VM and of library preparation before the execution.
+//!
+//! Use criterion baselines to measure the effect of a change:
+//! `cargo bench --bench vm -- --save-baseline before` on the original code, followed by
+//! `cargo bench --bench vm -- --baseline before` on the changed one.
+
+use std::collections::BTreeMap;
+
+use aluvm::data::MaybeNumber;
+use aluvm::isa::asm::AsmItem;
+use aluvm::isa::{HaltReason, Instr};
+use aluvm::library::{ExecLib, Lib, LibId, LibResolver, LibSite, LoadedLib};
+use aluvm::reg::{Reg32, RegA};
+use aluvm::{aluasm, aluasm_labels, Vm};
+use criterion::{criterion_group, criterion_main, BatchSize, Bencher, BenchmarkId, Criterion};
+
+/// Number of loop iterations performed by each of the benchmarked programs.
+const ITERATIONS: u16 = 1000;
+
+/// Appends to the `prefix` code a loop running `body` [`ITERATIONS`] times and counting the
+/// iterations in `a16[0]` register.
+fn looped(prefix: Vec<AsmItem<Instr>>, body: Vec<AsmItem<Instr>>) -> Vec<AsmItem<Instr>> {
+    let iterations = ITERATIONS;
+    let mut code = prefix;
+    code.extend(aluasm_labels! {
+        put     a16[0],0                        ;
+        put     a16[1],iterations               ;
+    start:
+    });
+    code.extend(body);
+    code.extend(aluasm_labels! {
+        inc     a16[0]                          ;
+        lt.u    a16[0],a16[1]                   ;
+        jif     :start                          ;
+        ret                                     ;
+    });
+    code
+}
+
+fn counter() -> Vec<Lib> {
+    let code = looped(vec![], vec![]);
+    vec![Lib::assemble(&code).unwrap()]
+}
+
+// Assembler macros check operand compatibility of arithmetic instructions with panicking match arms
+#[allow(clippy::diverging_sub_expression)]
+fn arithmetics() -> Vec<Lib> {
+    let prefix = aluasm_labels! {
+        put     a64[0],1                        ;
+        put     a64[1],1                        ;
+    };
+    let body = aluasm_labels! {
+        add.uw  a64[0],a64[1]                   ;
+        swp     a64[0],a64[1]                   ;
+        mul.uw  a64[0],a64[2]                   ;
+    };
+    vec![Lib::assemble(&looped(prefix, body)).unwrap()]
+}
+
+fn hashing() -> Vec<Lib> {
+    let prefix = aluasm_labels! {
+        put     s16[0],"AluVM benchmark data"   ;
+    };
+    let body = aluasm_labels! {
+        sha2    s16[0],r256[0]                  ;
+        sha2    s16[0],r512[0]                  ;
+    };
+    vec![Lib::assemble(&looped(prefix, body)).unwrap()]
+}
+
+fn calls() -> Vec<Lib> {
+    let callee = Lib::assemble(&aluasm! {
+        inc     a16[2]                          ;
+        ret                                     ;
+    })
+    .unwrap();
+    let callee_id = callee.id();
+    let body = aluasm_labels! {
+        call    0 @ callee_id                   ;
+    };
+    vec![Lib::assemble(&looped(vec![], body)).unwrap(), callee]
+}
+
+fn programs() -> [(&'static str, Vec<Lib>); 4] {
+    [
+        ("counter", counter()),
+        ("arithmetics", arithmetics()),
+        ("hashing", hashing()),
+        ("calls", calls()),
+    ]
+}
+
+/// Measures program execution, excluding the time required for the virtual machine allocation.
+fn bench_vm<R>(b: &mut Bencher, entry: LibSite, libs: R)
+where
+    R: LibResolver + Copy,
+    R::Lib: ExecLib<Instr>,
+{
+    b.iter_batched_ref(Vm::<Instr>::new, |vm| vm.run(entry, libs, &()), BatchSize::LargeInput)
+}
+
+fn run(c: &mut Criterion) {
+    let mut group = c.benchmark_group("run");
+    for (name, libs) in programs() {
+        let entry = LibSite::with(0, libs[0].id());
+        let plain = libs.iter().map(|lib| (lib.id(), lib)).collect::<BTreeMap<LibId, _>>();
+        let loaded = libs
+            .iter()
+            .map(|lib| (lib.id(), LoadedLib::<Instr>::new(lib.clone())))
+            .collect::<BTreeMap<_, _>>();
+
+        let mut vm = Vm::<Instr>::new();
+        let report = vm.run(entry, &plain, &());
+        assert_eq!(report.halt, HaltReason::Stop, "benchmarked program `{name}` has failed");
+        let counter = vm.registers.get_n(RegA::A16, Reg32::Reg0);
+        assert_eq!(counter, MaybeNumber::from(ITERATIONS), "program `{name}` must loop");
+
+        group.bench_with_input(BenchmarkId::new("lib", name), &plain, |b, libs| {
+            bench_vm(b, entry, libs)
+        });
+        group.bench_with_input(BenchmarkId::new("loaded", name), &loaded, |b, libs| {
+            bench_vm(b, entry, libs)
+        });
+    }
+    group.finish();
+}
+
+fn prepare(c: &mut Criterion) {
+    let mut group = c.benchmark_group("prepare");
+    for (name, libs) in programs() {
+        let lib = &libs[0];
+        group.bench_with_input(BenchmarkId::new("id", name), lib, |b, lib| b.iter(|| lib.id()));
+        group.bench_with_input(BenchmarkId::new("disassemble", name), lib, |b, lib| {
+            b.iter(|| lib.disassemble::<Instr>().unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("load", name), lib, |b, lib| {
+            b.iter(|| LoadedLib::<Instr>::new(lib.clone()))
+        });
+    }
+    group.finish();
+}
+
+fn new(c: &mut Criterion) { c.bench_function("new", |b| b.iter(Vm::<Instr>::new)); }
+
+criterion_group!(benches, new, run, prepare);
+criterion_main!(benches);
@@ -0,0 +1,470 @@
+// Reference rust implementation of AluVM (arithmetic logic unit virtual machine).
+// To find more on AluVM please check <https://aluvm.org>
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2021-2024 by
+//     Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2021-2022 LNP/BP Standards Association. All rights reserved.
+// Copyright (C) 2023-2024 UBIDECO Labs,
+//     Institute for Distributed and Cognitive Computing, Switzerland.
+//     All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Typed interface for passing program inputs and reading program outputs via registers
+//!
+//! A program [`Signature`] declares registers which receive the program inputs and registers
+//! holding the program outputs. Rust values are marshalled in and out of these registers with
+//! [`AbiValue`] trait, and tuples of the values - with [`AbiValues`] trait.
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::any::type_name;
+
+use amplify::num::apfloat::{ieee, Float};
+use amplify::num::{i1024, i256, i512, u1024, u256, u512};
+use half::bf16;
+
+use crate::data::{ByteStr, MaybeNumber, Number, RegValue};
+use crate::reg::{CoreRegs, Reg, RegA, RegF, RegR};
+
+/// Errors marshalling values in and out of registers
+#[derive(Clone, Eq, PartialEq, Hash, Debug, Display)]
+#[cfg_attr(feature = "std", derive(Error))]
+#[display(doc_comments)]
+pub enum AbiError {
+    /// signature declares {expected} registers, while {actual} values are used.
+    Arity {
+        /// Number of registers in the signature
+        expected: usize,
+        /// Number of values
+        actual: usize,
+    },
+
+    /// value of type `{ty}` can't be stored in register {reg}.
+    TypeMismatch {
+        /// Register declared by the signature
+        reg: Reg,
+        /// Name of the value type
+        ty: &'static str,
+    },
+
+    /// output register {0} has no value.
+    Undefined(Reg),
+
+    /// value for register {0} can't be represented in a register (like a NaN float value).
+    Unrepresentable(Reg),
+}
+
+/// Rust value which can be stored in a register.
+pub trait AbiValue: Sized {
+    /// Detects whether the value of this type can be stored in the register.
+    fn fits(reg: Reg) -> bool;
+
+    /// Converts value into a register value, returning `None` if the value can't be represented
+    /// in a register (like a NaN float value).
+    fn into_value(self) -> Option<RegValue>;
+
+    /// Converts register value back, returning `None` if the register has no value.
+    fn from_value(value: RegValue) -> Option<Self>;
+}
+
+fn number(value: RegValue) -> Option<Number> {
+    match value {
+        RegValue::Number(val) => val.into(),
+        RegValue::String(_) => None,
+    }
+}
+
+macro_rules! impl_abi_value {
+    ($block:ident($family:ident::$reg:ident) => $($ty:ty),+) => {
+        $(
+            impl AbiValue for $ty {
+                #[inline]
+                fn fits(reg: Reg) -> bool { matches!(reg, Reg::$block($family::$reg, _)) }
+
+                #[inline]
+                fn into_value(self) -> Option<RegValue> { Some(MaybeNumber::from(self).into()) }
+
+                #[inline]
+                fn from_value(value: RegValue) -> Option<Self> { number(value).map(<$ty>::from) }
+            }
+        )+
+    };
+}
+
+// NaN float values can't be stored in a register, so they are not converted into register values
+macro_rules! impl_abi_float {
+    ($($reg:ident => $ty:ty),+) => {
+        $(
+            impl AbiValue for $ty {
+                #[inline]
+                fn fits(reg: Reg) -> bool { matches!(reg, Reg::F(RegF::$reg, _)) }
+
+                #[inline]
+                fn into_value(self) -> Option<RegValue> {
+                    (!self.is_nan()).then(|| MaybeNumber::from(self).into())
+                }
+
+                #[inline]
+                fn from_value(value: RegValue) -> Option<Self> { number(value).map(<$ty>::from) }
+            }
+        )+
+    };
+}
+
+// Integers are passed bit-wise, such that negative values keep their two's complement encoding
+macro_rules! impl_abi_int {
+    ($reg:ident => $($ty:ty),+) => {
+        $(
+            impl AbiValue for $ty {
+                #[inline]
+                fn fits(reg: Reg) -> bool { matches!(reg, Reg::A(RegA::$reg, _)) }
+
+                #[inline]
+                fn into_value(self) -> Option<RegValue> {
+                    Some(Number::from(self.to_le_bytes()).into())
+                }
+
+                #[inline]
+                fn from_value(value: RegValue) -> Option<Self> {
+                    number(value).map(|val| <$ty>::from_le_bytes(val.into()))
+                }
+            }
+        )+
+    };
+}
+
+impl_abi_int!(A8 => u8, i8);
+impl_abi_int!(A16 => u16, i16);
+impl_abi_int!(A32 => u32, i32);
+impl_abi_int!(A64 => u64, i64);
+impl_abi_int!(A128 => u128, i128);
+impl_abi_int!(A256 => u256, i256);
+impl_abi_int!(A512 => u512, i512);
+impl_abi_int!(A1024 => u1024, i1024);
+
+impl_abi_float!(
+    F16B => bf16,
+    F16 => ieee::Half,
+    F32 => ieee::Single,
+    F64 => ieee::Double,
+    F80 => ieee::X87DoubleExtended,
+    F128 => ieee::Quad,
+    F256 => ieee::Oct
+);
+
+impl_abi_value!(R(RegR::R128) => [u8; 16]);
+impl_abi_value!(R(RegR::R160) => [u8; 20]);
+impl_abi_value!(R(RegR::R256) => [u8; 32]);
+impl_abi_value!(R(RegR::R512) => [u8; 64]);
+impl_abi_value!(R(RegR::R1024) => [u8; 128]);
+impl_abi_value!(R(RegR::R2048) => [u8; 256]);
+impl_abi_value!(R(RegR::R4096) => [u8; 512]);
+impl_abi_value!(R(RegR::R8192) => [u8; 1024]);
+
+impl AbiValue for f32 {
+    #[inline]
+    fn fits(reg: Reg) -> bool { ieee::Single::fits(reg) }
+
+    #[inline]
+    fn into_value(self) -> Option<RegValue> {
+        ieee::Single::from_bits(self.to_bits().into()).into_value()
+    }
+
+    #[inline]
+    fn from_value(value: RegValue) -> Option<Self> {
+        ieee::Single::from_value(value).map(|val| f32::from_bits(val.to_bits().low_u32()))
+    }
+}
+
+impl AbiValue for f64 {
+    #[inline]
+    fn fits(reg: Reg) -> bool { ieee::Double::fits(reg) }
+
+    #[inline]
+    fn into_value(self) -> Option<RegValue> {
+        ieee::Double::from_bits(self.to_bits().into()).into_value()
+    }
+
+    #[inline]
+    fn from_value(value: RegValue) -> Option<Self> {
+        ieee::Double::from_value(value).map(|val| f64::from_bits(val.to_bits().low_u64()))
+    }
+}
+
+impl AbiValue for ByteStr {
+    #[inline]
+    fn fits(reg: Reg) -> bool { matches!(reg, Reg::S(_)) }
+
+    #[inline]
+    fn into_value(self) -> Option<RegValue> { Some(RegValue::String(Some(self))) }
+
+    #[inline]
+    fn from_value(value: RegValue) -> Option<Self> {
+        match value {
+            RegValue::String(val) => val,
+            RegValue::Number(_) => None,
+        }
+    }
+}
+
+/// Optional values, which clear the register when written as `None` and which are read as `None`
+/// from registers having no value instead of producing [`AbiError::Undefined`] error.
+impl<T: AbiValue> AbiValue for Option<T> {
+    #[inline]
+    fn fits(reg: Reg) -> bool { T::fits(reg) }
+
+    #[inline]
+    fn into_value(self) -> Option<RegValue> {
+        match self {
+            Some(val) => val.into_value(),
+            None => Some(RegValue::Number(MaybeNumber::none())),
+        }
+    }
+
+    #[inline]
+    fn from_value(value: RegValue) -> Option<Self> { Some(T::from_value(value)) }
+}
+
+/// List of values passed via registers, implemented for tuples of up to 8 [`AbiValue`]s.
+pub trait AbiValues: Sized {
+    /// Checks that the values can be stored in the provided registers.
+    fn check(regs: &[Reg]) -> Result<(), AbiError>;
+
+    /// Converts the values into register values, in order. Values which can't be represented in
+    /// a register are returned as `None`.
+    fn into_values(self) -> Vec<Option<RegValue>>;
+
+    /// Reads the values from the provided registers, which must pass [`AbiValues::check`].
+    fn read(regs: &[Reg], registers: &CoreRegs) -> Result<Self, AbiError>;
+}
+
+macro_rules! impl_abi_values {
+    ($($ty:ident $idx:tt),*) => {
+        impl<$($ty: AbiValue),*> AbiValues for ($($ty,)*) {
+            fn check(regs: &[Reg]) -> Result<(), AbiError> {
+                let count = <[&str]>::len(&[$(stringify!($ty)),*]);
+                if regs.len() != count {
+                    return Err(AbiError::Arity {
+                        expected: regs.len(),
+                        actual: count,
+                    });
+                }
+                $(
+                    if !$ty::fits(regs[$idx]) {
+                        return Err(AbiError::TypeMismatch {
+                            reg: regs[$idx],
+                            ty: type_name::<$ty>(),
+                        });
+                    }
+                )*
+                Ok(())
+            }
+
+            #[allow(unused_variables)]
+            fn into_values(self) -> Vec<Option<RegValue>> { vec![$(self.$idx.into_value()),*] }
+
+            #[allow(unused_variables)]
+            fn read(regs: &[Reg], registers: &CoreRegs) -> Result<Self, AbiError> {
+                Ok(($(
+                    $ty::from_value(registers.get(regs[$idx]))
+                        .ok_or(AbiError::Undefined(regs[$idx]))?,
+                )*))
+            }
+        }
+    };
+}
+
+impl_abi_values!();
+impl_abi_values!(A 0);
+impl_abi_values!(A 0, B 1);
+impl_abi_values!(A 0, B 1, C 2);
+impl_abi_values!(A 0, B 1, C 2, D 3);
+impl_abi_values!(A 0, B 1, C 2, D 3, E 4);
+impl_abi_values!(A 0, B 1, C 2, D 3, E 4, F 5);
+impl_abi_values!(A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+impl_abi_values!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+
+/// Signature of a program, declaring registers which receive the program inputs and registers
+/// holding the program outputs once the program completes.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub struct Signature {
+    /// Registers receiving the program inputs
+    pub inputs: Vec<Reg>,
+
+    /// Registers holding the program outputs
+    pub outputs: Vec<Reg>,
+}
+
+impl Signature {
+    /// Constructs signature from lists of input and output registers.
+    pub fn new(
+        inputs: impl IntoIterator<Item = impl Into<Reg>>,
+        outputs: impl IntoIterator<Item = impl Into<Reg>>,
+    ) -> Self {
+        Signature {
+            inputs: inputs.into_iter().map(Into::into).collect(),
+            outputs: outputs.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Writes input values into the input registers. If the values do not match the signature, or
+    /// some of them can't be represented in a register, errors without modifying the registers.
+    pub fn write_inputs<T: AbiValues>(
+        &self,
+        registers: &mut CoreRegs,
+        inputs: T,
+    ) -> Result<(), AbiError> {
+        T::check(&self.inputs)?;
+        let values = inputs.into_values();
+        if let Some(pos) = values.iter().position(Option::is_none) {
+            return Err(AbiError::Unrepresentable(self.inputs[pos]));
+        }
+        for (reg, value) in self.inputs.iter().zip(values.into_iter().flatten()) {
+            match (*reg, value) {
+                (Reg::A(reg, idx), value) => registers.set_n(reg, idx, number(value)),
+                (Reg::F(reg, idx), value) => registers.set_n(reg, idx, number(value)),
+                (Reg::R(reg, idx), value) => registers.set_n(reg, idx, number(value)),
+                (Reg::S(idx), RegValue::String(Some(val))) => registers.set_s16(idx, val).is_some(),
+                (Reg::S(idx), _) => registers.clr_s16(idx).is_some(),
+            };
+        }
+        Ok(())
+    }
+
+    /// Constructs registers with the default state having the input values written into the
+    /// input registers.
+    pub fn input_registers(&self, inputs: impl AbiValues) -> Result<CoreRegs, AbiError> {
+        let mut registers = CoreRegs::new();
+        self.write_inputs(&mut registers, inputs)?;
+        Ok(registers)
+    }
+
+    /// Reads output values from the output registers.
+    ///
+    /// # Errors
+    ///
+    /// If the types of the values do not match the signature, or if some of the output registers
+    /// has no value and the value type is not an [`Option`].
+    pub fn read_outputs<T: AbiValues>(&self, registers: &CoreRegs) -> Result<T, AbiError> {
+        T::check(&self.outputs)?;
+        T::read(&self.outputs, registers)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use amplify::num::u4;
+
+    use super::*;
+    use crate::isa::{ArithmeticOp, Instr, IntFlags};
+    use crate::library::{Lib, LibId, LibSite};
+    use crate::reg::{Reg32, RegS};
+    use crate::Vm;
+
+    #[test]
+    fn roundtrip() {
+        let sig = Signature::new(
+            [
+                Reg::A(RegA::A16, Reg32::Reg0),
+                Reg::A(RegA::A256, Reg32::Reg1),
+                Reg::A(RegA::A64, Reg32::Reg2),
+                Reg::F(RegF::F32, Reg32::Reg3),
+                Reg::F(RegF::F128, Reg32::Reg4),
+                Reg::R(RegR::R160, Reg32::Reg5),
+                Reg::S(RegS::from(u4::with(6))),
+                Reg::S(RegS::from(u4::with(7))),
+            ],
+            [Reg::A(RegA::A16, Reg32::Reg0)],
+        );
+        let inputs = (
+            7u16,
+            u256::from(1u64) << 200,
+            -5i64,
+            1.5f32,
+            ieee::Quad::from_bits(u256::from(12345u64)),
+            [0xAB; 20],
+            ByteStr::with(b"AluVM"),
+            None::<ByteStr>,
+        );
+        let regs = sig.input_registers(inputs.clone()).unwrap();
+        let sig = Signature {
+            inputs: vec![],
+            outputs: sig.inputs,
+        };
+        assert_eq!(sig.read_outputs(&regs), Ok(inputs));
+        assert_eq!(regs.a16(Reg32::Reg0), Some(7));
+        assert_eq!(regs.a64(Reg32::Reg2), Some(-5i64 as u64));
+    }
+
+    #[test]
+    fn errors() {
+        let a8 = Reg::A(RegA::A8, Reg32::Reg0);
+        let r256 = Reg::R(RegR::R256, Reg32::Reg0);
+        let sig = Signature::new([a8], [a8, r256]);
+
+        let mut regs = CoreRegs::new();
+        assert_eq!(
+            sig.write_inputs(&mut regs, (1u8, 2u8)),
+            Err(AbiError::Arity {
+                expected: 1,
+                actual: 2
+            })
+        );
+        assert_eq!(
+            sig.write_inputs(&mut regs, (1u16,)),
+            Err(AbiError::TypeMismatch { reg: a8, ty: "u16" })
+        );
+        assert_eq!(regs.a8(Reg32::Reg0), None);
+
+        sig.write_inputs(&mut regs, (1u8,)).unwrap();
+        assert_eq!(sig.read_outputs::<(u8, [u8; 32])>(&regs), Err(AbiError::Undefined(r256)));
+        assert_eq!(sig.read_outputs::<(u8, Option<[u8; 32]>)>(&regs), Ok((1, None)));
+
+        let f32_reg = Reg::F(RegF::F32, Reg32::Reg0);
+        let sig = Signature::new([a8, f32_reg], [a8]);
+        assert_eq!(
+            sig.write_inputs(&mut regs, (2u8, f32::NAN)),
+            Err(AbiError::Unrepresentable(f32_reg))
+        );
+        assert_eq!(
+            sig.write_inputs(&mut regs, (2u8, Some(f32::NAN))),
+            Err(AbiError::Unrepresentable(f32_reg))
+        );
+        assert_eq!(regs.a8(Reg32::Reg0), Some(1));
+        sig.write_inputs(&mut regs, (2u8, None::<f32>)).unwrap();
+        assert_eq!(regs.a8(Reg32::Reg0), Some(2));
+    }
+
+    #[test]
+    fn program() {
+        let sig =
+            Signature::new([Reg::A(RegA::A32, Reg32::Reg0), Reg::A(RegA::A32, Reg32::Reg1)], [
+                Reg::A(RegA::A32, Reg32::Reg1),
+            ]);
+        let code: [Instr; 1] = [Instr::Arithmetic(ArithmeticOp::AddA(
+            IntFlags::unsigned_checked(),
+            RegA::A32,
+            Reg32::Reg0,
+            Reg32::Reg1,
+        ))];
+        let lib = Lib::assemble(&code).unwrap();
+        let mut vm = Vm::<Instr>::new();
+        sig.write_inputs(&mut vm.registers, (40u32, 2u32)).unwrap();
+        assert!(vm.exec(LibSite::with(0, lib.id()), |_: LibId| Some(&lib), &()));
+        assert_eq!(sig.read_outputs(&vm.registers), Ok((42u32,)));
+    }
+}
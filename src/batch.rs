@@ -0,0 +1,295 @@
+// Reference rust implementation of AluVM (arithmetic logic unit virtual machine).
+// To find more on AluVM please check <https://aluvm.org>
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2021-2024 by
+//     Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2021-2022 LNP/BP Standards Association. All rights reserved.
+// Copyright (C) 2023-2024 UBIDECO Labs,
+//     Institute for Distributed and Cognitive Computing, Switzerland.
+//     All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Batch execution of many independent programs over a shared set of libraries
+
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
+#[cfg(feature = "alloc")]
+use alloc::collections::BTreeSet;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+use crate::isa::{CostModel, DefaultCost, Instr, InstructionSet, ReservedOp};
+use crate::library::{ExecLib, LibId, LibResolver, LibSite, ResolveError};
+use crate::reg::CoreRegs;
+use crate::vm::{ExecReport, LibPolicy, Vm};
+
+/// Program to be executed by [`Batch`] executor.
+#[derive(Clone, Debug)]
+pub struct Job<Ctx> {
+    /// Entry point of the program
+    pub entry_point: LibSite,
+
+    /// Context provided to the instructions of the program
+    pub context: Ctx,
+
+    /// Initial state of the registers
+    pub registers: Box<CoreRegs>,
+}
+
+impl<Ctx> Job<Ctx> {
+    /// Constructs job starting the execution with all registers in their default state.
+    pub fn new(entry_point: LibSite, context: Ctx) -> Self {
+        Job {
+            entry_point,
+            context,
+            registers: Box::default(),
+        }
+    }
+
+    /// Constructs job starting the execution with the provided register state.
+    pub fn with_registers(entry_point: LibSite, context: Ctx, registers: CoreRegs) -> Self {
+        Job {
+            entry_point,
+            context,
+            registers: Box::new(registers),
+        }
+    }
+}
+
+/// Outcome of a job executed by [`Batch`] executor.
+#[derive(Clone, Debug)]
+pub struct JobOutcome {
+    /// Report on the program execution
+    pub report: ExecReport,
+
+    /// Final state of the registers
+    pub registers: Box<CoreRegs>,
+}
+
+/// Executor running many independent programs over the same set of libraries, like
+/// [`crate::library::LibCache`].
+///
+/// Each job runs on its own virtual machine, so the outcomes do not depend on the order in which
+/// the jobs are executed, nor on the number of threads used by [`Batch::run_parallel`].
+#[derive(Clone, Debug, Default)]
+pub struct Batch<Isa = Instr<ReservedOp>, Cost = DefaultCost>
+where
+    Isa: InstructionSet,
+    Cost: CostModel<Isa>,
+{
+    /// Cost schedule used to account complexity of the executed instructions
+    pub cost: Cost,
+
+    /// Policy for libraries which can't be resolved
+    pub lib_policy: LibPolicy,
+
+    /// Refuse to execute libraries which do not pass verification, see [`Vm::verify_libs`]
+    pub verify_libs: bool,
+
+    phantom: PhantomData<Isa>,
+}
+
+impl<Isa> Batch<Isa>
+where Isa: InstructionSet
+{
+    /// Constructs new batch executor.
+    pub fn new() -> Self { Self::with_cost(DefaultCost) }
+}
+
+impl<Isa, Cost> Batch<Isa, Cost>
+where
+    Isa: InstructionSet,
+    Cost: CostModel<Isa> + Clone,
+{
+    /// Constructs new batch executor using a custom cost schedule.
+    pub fn with_cost(cost: Cost) -> Self {
+        Self {
+            cost,
+            lib_policy: LibPolicy::default(),
+            verify_libs: false,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Executes a single job.
+    pub fn run_job<Resolver>(
+        &self,
+        lib_resolver: &Resolver,
+        job: &Job<Isa::Context<'_>>,
+    ) -> JobOutcome
+    where
+        Resolver: LibResolver,
+        Resolver::Lib: ExecLib<Isa>,
+    {
+        let mut vm = Vm::<Isa, Cost>::with_cost(self.cost.clone());
+        vm.lib_policy = self.lib_policy;
+        vm.verify_libs = self.verify_libs;
+        vm.registers = job.registers.clone();
+        let report = vm.run(job.entry_point, Shared(lib_resolver), &job.context);
+        JobOutcome {
+            report,
+            registers: vm.registers,
+        }
+    }
+
+    /// Executes all jobs one by one, returning their outcomes in the order of the jobs.
+    pub fn run<Resolver>(
+        &self,
+        lib_resolver: &Resolver,
+        jobs: &[Job<Isa::Context<'_>>],
+    ) -> Vec<JobOutcome>
+    where
+        Resolver: LibResolver,
+        Resolver::Lib: ExecLib<Isa>,
+    {
+        jobs.iter().map(|job| self.run_job(lib_resolver, job)).collect()
+    }
+
+    /// Executes jobs on a pool of `threads` threads, returning their outcomes in the order of the
+    /// jobs. If `threads` is zero, uses the number of threads reported by
+    /// [`std::thread::available_parallelism`].
+    ///
+    /// The outcomes are exactly the same as the ones returned by [`Batch::run`].
+    #[cfg(feature = "std")]
+    pub fn run_parallel<'ctx, Resolver>(
+        &self,
+        lib_resolver: &Resolver,
+        jobs: &[Job<Isa::Context<'ctx>>],
+        threads: usize,
+    ) -> Vec<JobOutcome>
+    where
+        Self: Sync,
+        Resolver: LibResolver + Sync,
+        Resolver::Lib: ExecLib<Isa>,
+        Isa::Context<'ctx>: Sync,
+    {
+        use std::num::NonZeroUsize;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::{panic, thread};
+
+        let threads = match threads {
+            0 => thread::available_parallelism().map(NonZeroUsize::get).unwrap_or(1),
+            threads => threads,
+        };
+        if threads <= 1 || jobs.len() <= 1 {
+            return self.run(lib_resolver, jobs);
+        }
+
+        let next = AtomicUsize::new(0);
+        let worker = || {
+            let mut outcomes = vec![];
+            loop {
+                let idx = next.fetch_add(1, Ordering::Relaxed);
+                let Some(job) = jobs.get(idx) else {
+                    break outcomes;
+                };
+                outcomes.push((idx, self.run_job(lib_resolver, job)));
+            }
+        };
+        let mut outcomes = thread::scope(|scope| {
+            let workers =
+                (0..threads.min(jobs.len())).map(|_| scope.spawn(worker)).collect::<Vec<_>>();
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().unwrap_or_else(|err| panic::resume_unwind(err)))
+                .collect::<Vec<_>>()
+        });
+        outcomes.sort_by_key(|(idx, _)| *idx);
+        outcomes.into_iter().map(|(_, outcome)| outcome).collect()
+    }
+}
+
+/// Resolver sharing a reference to another resolver between virtual machines.
+struct Shared<'r, R>(&'r R);
+
+impl<'r, R> LibResolver for Shared<'r, R>
+where R: LibResolver
+{
+    type Lib = R::Lib;
+    type Handle<'a>
+        = R::Handle<'a>
+    where Self: 'a;
+
+    #[inline]
+    fn resolve(&self, id: LibId) -> Result<Self::Handle<'_>, ResolveError> { self.0.resolve(id) }
+
+    #[inline]
+    fn check_deps(&self, root: LibId) -> Result<BTreeSet<LibId>, ResolveError> {
+        self.0.check_deps(root)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use super::*;
+    use crate::data::Step;
+    use crate::isa::{ArithmeticOp, ControlFlowOp, HaltReason};
+    use crate::library::{Lib, LibCache};
+    use crate::reg::{Reg32, RegA};
+
+    fn libs() -> (LibSite, LibCache<Instr>) {
+        let inc: Instr =
+            Instr::Arithmetic(ArithmeticOp::Stp(RegA::A16, Reg32::Reg0, Step::with(1)));
+        let ret = Instr::ControlFlow(ControlFlowOp::Ret);
+        let callee = Lib::assemble(&[inc.clone(), ret.clone()]).unwrap();
+        let call = Instr::ControlFlow(ControlFlowOp::Call(LibSite::with(0, callee.id())));
+        let main = Lib::assemble(&[call, inc, ret]).unwrap();
+        let entry = LibSite::with(0, main.id());
+        (entry, [main, callee].into_iter().collect())
+    }
+
+    fn jobs(entry: LibSite) -> Vec<Job<()>> {
+        let mut jobs = (0..100u16)
+            .map(|val| {
+                let mut regs = CoreRegs::new();
+                regs.set_n(RegA::A16, Reg32::Reg0, val);
+                Job::with_registers(entry, (), regs)
+            })
+            .collect::<Vec<_>>();
+        jobs.insert(10, Job::new(entry, ()));
+        jobs.insert(20, Job::new(LibSite::with(0, LibId::from([0u8; 32])), ()));
+        jobs
+    }
+
+    #[test]
+    fn sequential() {
+        let (entry, libs) = libs();
+        let jobs = jobs(entry);
+        let outcomes = Batch::<Instr>::new().run(&libs, &jobs);
+        assert_eq!(outcomes.len(), jobs.len());
+
+        assert_eq!(outcomes[0].report.halt, HaltReason::Stop);
+        assert_eq!(outcomes[0].registers.a16(Reg32::Reg0), Some(2));
+        assert_eq!(outcomes[99].registers.a16(Reg32::Reg0), Some(99));
+        assert_eq!(outcomes[101].registers.a16(Reg32::Reg0), Some(101));
+        assert!(outcomes[0].report.status);
+        assert!(!outcomes[10].report.status);
+        assert_eq!(outcomes[20].report.halt, HaltReason::LibNotFound(LibId::from([0u8; 32])));
+    }
+
+    #[test]
+    fn parallel() {
+        let (entry, libs) = libs();
+        let jobs = jobs(entry);
+        let batch = Batch::<Instr>::new();
+        let expected = format!("{:?}", batch.run(&libs, &jobs));
+        for threads in [0, 1, 2, 3, 8, 200] {
+            assert_eq!(format!("{:?}", batch.run_parallel(&libs, &jobs, threads)), expected);
+        }
+    }
+}
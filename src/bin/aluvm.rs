@@ -0,0 +1,300 @@
+// Reference rust implementation of AluVM (arithmetic logic unit virtual machine).
+// To find more on AluVM please check <https://aluvm.org>
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2021-2024 by
+//     Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2021-2022 LNP/BP Standards Association. All rights reserved.
+// Copyright (C) 2023-2024 UBIDECO Labs,
+//     Institute for Distributed and Cognitive Computing, Switzerland.
+//     All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Command-line tool for assembling, inspecting and running AluVM libraries.
+
+use std::error::Error;
+use std::fs;
+use std::io::{self, Read as _, Write as _};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::str::FromStr;
+
+use aluvm::data::{ByteStr, MaybeNumber};
+use aluvm::isa::disasm::{self, DisasmError};
+use aluvm::isa::{asm, Instr, ReservedOp};
+use aluvm::library::{Lib, LibDir, LibSite};
+use aluvm::reg::{CoreRegs, NumericRegister, Reg, RegAFR};
+use aluvm::Vm;
+use amplify::confinement::{Confined, U24};
+use armor::AsciiArmor;
+use clap::{Parser, Subcommand};
+use strict_encoding::{StrictDeserialize, StrictSerialize};
+
+/// Prefix which starts ASCII-armored data
+const ARMOR_PREFIX: &[u8] = b"-----BEGIN ";
+
+#[derive(Parser)]
+#[command(author, version, about = "Command-line tool for AluVM libraries")]
+struct Args {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Assembles AluVM assembly text into a library
+    Asm {
+        /// File with the assembly text, or `-` for the standard input
+        src: PathBuf,
+
+        /// Output file, or a directory to put the library into under the name used by `run`.
+        /// Defaults to the standard output
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Produce ASCII-armored library instead of the strict-encoded one
+        #[arg(short, long)]
+        armor: bool,
+    },
+
+    /// Disassembles library into AluVM assembly text
+    Disasm {
+        /// Strict-encoded or ASCII-armored library file, or `-` for the standard input
+        lib: PathBuf,
+
+        /// Output file. Defaults to the standard output
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Prints library id
+    Id {
+        /// Strict-encoded or ASCII-armored library file, or `-` for the standard input
+        lib: PathBuf,
+    },
+
+    /// Converts strict-encoded library into ASCII armor
+    Armor {
+        /// Strict-encoded library file, or `-` for the standard input
+        lib: PathBuf,
+
+        /// Output file. Defaults to the standard output
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Converts ASCII-armored library into strict encoding
+    Dearmor {
+        /// ASCII-armored library file, or `-` for the standard input
+        lib: PathBuf,
+
+        /// Output file, or a directory to put the library into under the name used by `run`.
+        /// Defaults to the standard output
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Runs library code and prints the final register state
+    Run {
+        /// Entry point in `<offset> @ <library id>` form
+        entry: LibSite,
+
+        /// Directory with strict-encoded libraries named by their hex-encoded ids
+        #[arg(short = 'd', long, default_value = ".")]
+        lib_dir: PathBuf,
+
+        /// Initial register value in `<register>=<value>` form, like `a16[1]=378`
+        #[arg(short, long = "reg", value_name = "ASSIGNMENT")]
+        regs: Vec<String>,
+
+        /// File with initial register values, one `<register>=<value>` assignment per line.
+        /// Lines starting with `;` are comments
+        #[arg(short = 'f', long, value_name = "FILE")]
+        regs_file: Option<PathBuf>,
+
+        /// Refuse to execute libraries which do not pass bytecode verification
+        #[arg(long)]
+        verify: bool,
+    },
+}
+
+fn main() -> ExitCode {
+    match exec(Args::parse().command) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+/// Executes command, returning whether it has succeeded
+fn exec(command: Command) -> Result<bool, Box<dyn Error>> {
+    match command {
+        Command::Asm { src, output, armor } => {
+            let source = String::from_utf8(read(&src)?)?;
+            let lib = asm::assemble::<ReservedOp>(&source)?;
+            write_lib(&lib, output.as_deref(), armor)?;
+        }
+        Command::Disasm { lib, output } => {
+            let lib = read_lib(&lib)?;
+            let text = match disasm::disassemble::<ReservedOp>(&lib) {
+                Ok(text) => text.into_bytes(),
+                Err(DisasmError::Irreproducible) => {
+                    eprintln!("warning: library can't be reproduced from its assembly text");
+                    let mut listing = vec![];
+                    lib.print_disassemble::<ReservedOp>(&mut listing)?;
+                    listing
+                }
+                Err(err) => return Err(err.into()),
+            };
+            write(output.as_deref(), &text)?;
+        }
+        Command::Id { lib } => println!("{}", read_lib(&lib)?.id()),
+        Command::Armor { lib, output } => {
+            let lib = Lib::from_strict_serialized::<U24>(Confined::try_from(read(&lib)?)?)?;
+            write_lib(&lib, output.as_deref(), true)?;
+        }
+        Command::Dearmor { lib, output } => {
+            let lib = Lib::from_ascii_armored_str(&String::from_utf8(read(&lib)?)?)?;
+            write_lib(&lib, output.as_deref(), false)?;
+        }
+        Command::Run {
+            entry,
+            lib_dir,
+            regs,
+            regs_file,
+            verify,
+        } => {
+            let mut vm = Vm::<Instr>::new();
+            vm.verify_libs = verify;
+            if let Some(path) = regs_file {
+                let text = String::from_utf8(read(&path)?)?;
+                for line in text.lines().map(str::trim) {
+                    if !line.is_empty() && !line.starts_with(';') {
+                        assign(&mut vm.registers, line)?;
+                    }
+                }
+            }
+            for assignment in regs {
+                assign(&mut vm.registers, &assignment)?;
+            }
+            let report = vm.run(entry, LibDir::new(lib_dir), &());
+            println!("halt:       {}", report.halt);
+            println!("site:       {}", report.site);
+            println!("status:     {}", report.status);
+            println!("cycles:     {}", report.cycles);
+            println!("complexity: {}", report.complexity);
+            println!("{:?}", vm.registers);
+            return Ok(report.status);
+        }
+    }
+    Ok(true)
+}
+
+/// Reads file, or the standard input if the path is `-`
+fn read(path: &Path) -> io::Result<Vec<u8>> {
+    if path.as_os_str() == "-" {
+        let mut data = vec![];
+        io::stdin().read_to_end(&mut data)?;
+        return Ok(data);
+    }
+    fs::read(path)
+}
+
+/// Writes data to the file, or to the standard output if no path is given
+fn write(path: Option<&Path>, data: &[u8]) -> io::Result<()> {
+    match path {
+        Some(path) => fs::write(path, data),
+        None => io::stdout().write_all(data),
+    }
+}
+
+/// Reads library either in strict encoding or in ASCII armor
+fn read_lib(path: &Path) -> Result<Lib, Box<dyn Error>> {
+    let data = read(path)?;
+    let start = data.iter().position(|byte| !byte.is_ascii_whitespace()).unwrap_or(data.len());
+    if data[start..].starts_with(ARMOR_PREFIX) {
+        return Ok(Lib::from_ascii_armored_str(&String::from_utf8(data)?)?);
+    }
+    Ok(Lib::from_strict_serialized::<U24>(Confined::try_from(data)?)?)
+}
+
+/// Writes library either in strict encoding or in ASCII armor. If the path is a directory, the
+/// library is written into it under the name expected by [`LibDir`].
+fn write_lib(lib: &Lib, path: Option<&Path>, armor: bool) -> Result<(), Box<dyn Error>> {
+    let data = match armor {
+        true => lib.to_ascii_armored_string().into_bytes(),
+        false => lib.to_strict_serialized::<U24>()?.release(),
+    };
+    match path {
+        Some(dir) if dir.is_dir() => write(Some(&LibDir::new(dir).lib_path(lib.id())), &data)?,
+        path => write(path, &data)?,
+    }
+    Ok(())
+}
+
+/// Assigns value to a register from `<register>=<value>` string
+fn assign(regs: &mut CoreRegs, assignment: &str) -> Result<(), String> {
+    let (reg, value) = assignment.split_once('=').ok_or_else(|| {
+        format!("register assignment `{assignment}` must have `<register>=<value>` form")
+    })?;
+    let (reg, value) = (reg.trim(), value.trim());
+    let (reg, index) = match Reg::from_str(reg).map_err(|err| err.to_string())? {
+        Reg::S(index) => {
+            let value = ByteStr::from_str(value)
+                .map_err(|err| format!("invalid value `{value}` for `{index}`: {err}"))?;
+            regs.set_s16(index, value);
+            return Ok(());
+        }
+        Reg::A(reg, index) => (RegAFR::A(reg), index),
+        Reg::F(reg, index) => (RegAFR::F(reg), index),
+        Reg::R(reg, index) => (RegAFR::R(reg), index),
+    };
+    let mut num = MaybeNumber::from_str(value)
+        .map_err(|err| format!("invalid value `{value}` for `{reg}{index}`: {err}"))?;
+    let layout = reg.layout();
+    let defined = num.is_some();
+    // float conversions may round the value, which is fine for an initial value
+    if !num.reshape(layout) && (!layout.is_float() || defined && num.is_none()) {
+        return Err(format!("value `{value}` does not fit into `{reg}{index}`"));
+    }
+    regs.set_n(reg, index, num);
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use aluvm::reg::{Reg32, RegA, RegF, RegS};
+
+    use super::*;
+
+    #[test]
+    fn assignments() {
+        let mut regs = CoreRegs::new();
+        assign(&mut regs, "a16[1]=378").unwrap();
+        assign(&mut regs, "f32[2] = 0.5").unwrap();
+        assign(&mut regs, "s16[3]=\"a=b\"").unwrap();
+        assert_eq!(regs.get_n(RegA::A16, Reg32::Reg1), MaybeNumber::from(378u16));
+        assert!(regs.get_n(RegF::F32, Reg32::Reg2).is_some());
+        assert_eq!(regs.s16(RegS::from(3)), Some(&ByteStr::with("a=b")));
+
+        assert!(assign(&mut regs, "a8[1]").is_err());
+        assert!(assign(&mut regs, "a8[1]=256").is_err());
+        assert!(assign(&mut regs, "b8[1]=1").is_err());
+    }
+}
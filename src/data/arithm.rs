@@ -26,7 +26,8 @@
 use core::cmp::Ordering;
 use core::ops::{Neg, Rem};
 
-use amplify::num::apfloat::{ieee, Float};
+use amplify::num::apfloat::{ieee, Float, FloatConvert, Round, StatusAnd};
+use amplify::num::{i1024, u1024};
 use half::bf16;
 
 use super::{FloatLayout, IntLayout, Layout, Number, NumberLayout};
@@ -58,7 +59,7 @@ impl Ord for Number {
             Layout::Integer(_) => match (self.is_positive(), other.is_positive()) {
                 (true, false) => Ordering::Greater,
                 (false, true) => Ordering::Less,
-                _ => self.to_u1024_bytes().cmp(&other.to_u1024_bytes()),
+                _ => self[..].iter().rev().cmp(other[..].iter().rev()),
             },
             Layout::Float(FloatLayout::BFloat16) => {
                 bf16::from(self).partial_cmp(&bf16::from(other)).expect("number value contains NaN")
@@ -78,9 +79,9 @@ impl Ord for Number {
             Layout::Float(FloatLayout::IeeeQuad) => ieee::Quad::from(self)
                 .partial_cmp(&ieee::Quad::from(other))
                 .expect("number value contains NaN"),
-            Layout::Float(FloatLayout::IeeeOct) => {
-                unimplemented!("IEEE-754 256-bit floats are not yet supported")
-            }
+            Layout::Float(FloatLayout::IeeeOct) => ieee::Oct::from(self)
+                .partial_cmp(&ieee::Oct::from(other))
+                .expect("number value contains NaN"),
             Layout::Float(FloatLayout::FloatTapered) => {
                 unimplemented!("512-bit tapered floats are not yet supported")
             }
@@ -99,14 +100,14 @@ impl Number {
                 unimplemented!("512-bit tapered floats are not yet supported")
             }
             Layout::Float(float_layout) => {
-                let last_bit = Number::masked_bit(
+                let mask = !Number::masked_bit(
                     float_layout
                         .significand_pos()
                         .expect("non-tapered float layout does not provides significand position")
-                        .end,
+                        .start,
                     self.layout(),
                 );
-                (*self ^ last_bit).cmp(&(*other ^ last_bit))
+                (*self & mask).cmp(&(*other & mask))
             }
         }
     }
@@ -224,10 +225,14 @@ impl Number {
 
         match (layout, flags.signed) {
             (Layout::Integer(IntLayout { bytes, .. }), true) => {
-                let res = match flags.wrap {
-                    true => self.to_i1024_bytes().checked_div_euclid(rhs.to_i1024_bytes()),
-                    false => self.to_i1024_bytes().checked_div(rhs.to_i1024_bytes()),
-                };
+                let rhs = rhs.to_i1024_bytes();
+                let res = i1024_div_rem(self.to_i1024_bytes(), rhs).map(|(q, r)| {
+                    match (flags.wrap, r.is_negative(), rhs.is_positive()) {
+                        (true, true, true) => q.wrapping_sub(i1024::ONE),
+                        (true, true, false) => q.wrapping_add(i1024::ONE),
+                        _ => q,
+                    }
+                });
                 res.map(Number::from)
                     .and_then(|n| n.reshaped(Layout::signed(n.layout().bytes()), true))
                     .and_then(|n| n.reshaped(Layout::signed(bytes), false))
@@ -241,11 +246,12 @@ impl Number {
         }
     }
 
-    /// Addition of two floats with configuration flags for rounding.
+    /// Addition of two floats with configuration flags for rounding. Tapered float layouts are not
+    /// supported yet and always produce `None`.
     ///
     /// # Panics
     ///
-    /// - if applied to float number layouts
+    /// - if applied to integer number layouts
     /// - if numbers in arguments has different layout.
     pub fn float_add(self, rhs: Self, flag: RoundingFlag) -> MaybeNumber {
         let layout = self.layout();
@@ -265,21 +271,22 @@ impl Number {
                 ieee::Quad::from(self).add_r(rhs.into(), flag.into()).into()
             }
             Layout::Float(FloatLayout::X87DoubleExt) => {
-                ieee::X87DoubleExtended::from(self).add_r(rhs.into(), flag.into()).into()
+                x87_op(self, rhs, flag.into(), |a, b| a.add_r(b, flag.into()))
             }
             Layout::Float(FloatLayout::IeeeOct) => {
                 ieee::Oct::from(self).add_r(rhs.into(), flag.into()).into()
             }
-            Layout::Float(FloatLayout::FloatTapered) => todo!("(#5) tapered float addition"),
+            Layout::Float(FloatLayout::FloatTapered) => MaybeNumber::none(),
             Layout::Integer(_) => panic!("float addition of integer numbers"),
         }
     }
 
-    /// Subtraction of two floats with configuration flags for rounding.
+    /// Subtraction of two floats with configuration flags for rounding. Tapered float layouts are
+    /// not supported yet and always produce `None`.
     ///
     /// # Panics
     ///
-    /// - if applied to float number layouts
+    /// - if applied to integer number layouts
     /// - if numbers in arguments has different layout.
     pub fn float_sub(self, rhs: Self, flag: RoundingFlag) -> MaybeNumber {
         let layout = self.layout();
@@ -299,21 +306,22 @@ impl Number {
                 ieee::Quad::from(self).sub_r(rhs.into(), flag.into()).into()
             }
             Layout::Float(FloatLayout::X87DoubleExt) => {
-                ieee::X87DoubleExtended::from(self).sub_r(rhs.into(), flag.into()).into()
+                x87_op(self, rhs, flag.into(), |a, b| a.sub_r(b, flag.into()))
             }
             Layout::Float(FloatLayout::IeeeOct) => {
                 ieee::Oct::from(self).sub_r(rhs.into(), flag.into()).into()
             }
-            Layout::Float(FloatLayout::FloatTapered) => todo!("(#5) tapered float subtraction"),
+            Layout::Float(FloatLayout::FloatTapered) => MaybeNumber::none(),
             Layout::Integer(_) => panic!("float subtraction of integer numbers"),
         }
     }
 
-    /// Multiplication of two floats with configuration flags for rounding.
+    /// Multiplication of two floats with configuration flags for rounding. Tapered float layouts
+    /// are not supported yet and always produce `None`.
     ///
     /// # Panics
     ///
-    /// - if applied to float number layouts
+    /// - if applied to integer number layouts
     /// - if numbers in arguments has different layout.
     pub fn float_mul(self, rhs: Self, flag: RoundingFlag) -> MaybeNumber {
         let layout = self.layout();
@@ -333,21 +341,22 @@ impl Number {
                 ieee::Quad::from(self).mul_r(rhs.into(), flag.into()).into()
             }
             Layout::Float(FloatLayout::X87DoubleExt) => {
-                ieee::X87DoubleExtended::from(self).mul_r(rhs.into(), flag.into()).into()
+                x87_op(self, rhs, flag.into(), |a, b| a.mul_r(b, flag.into()))
             }
             Layout::Float(FloatLayout::IeeeOct) => {
                 ieee::Oct::from(self).mul_r(rhs.into(), flag.into()).into()
             }
-            Layout::Float(FloatLayout::FloatTapered) => todo!("(#5) tapered float multiplication"),
+            Layout::Float(FloatLayout::FloatTapered) => MaybeNumber::none(),
             Layout::Integer(_) => panic!("float multiplication of integer numbers"),
         }
     }
 
-    /// Division of two floats with configuration flags for rounding.
+    /// Division of two floats with configuration flags for rounding. Tapered float layouts are not
+    /// supported yet and always produce `None`.
     ///
     /// # Panics
     ///
-    /// - if applied to float number layouts
+    /// - if applied to integer number layouts
     /// - if numbers in arguments has different layout.
     pub fn float_div(self, rhs: Self, flag: RoundingFlag) -> MaybeNumber {
         let layout = self.layout();
@@ -367,12 +376,12 @@ impl Number {
                 ieee::Quad::from(self).div_r(rhs.into(), flag.into()).into()
             }
             Layout::Float(FloatLayout::X87DoubleExt) => {
-                ieee::X87DoubleExtended::from(self).div_r(rhs.into(), flag.into()).into()
+                x87_op(self, rhs, flag.into(), |a, b| a.div_r(b, flag.into()))
             }
             Layout::Float(FloatLayout::IeeeOct) => {
                 ieee::Oct::from(self).div_r(rhs.into(), flag.into()).into()
             }
-            Layout::Float(FloatLayout::FloatTapered) => todo!("(#5) tapered float division"),
+            Layout::Float(FloatLayout::FloatTapered) => MaybeNumber::none(),
             Layout::Integer(_) => panic!("float division of integer numbers"),
         }
     }
@@ -425,6 +434,42 @@ impl Number {
     pub fn without_sign(self) -> Option<Number> { self.applying_sign(false) }
 }
 
+/// Signed division with remainder, where the quotient is rounded towards zero. Returns `None` on
+/// division by zero or overflow.
+///
+/// `i1024` division may panic on operands of different signs, so we divide absolute values.
+fn i1024_div_rem(lhs: i1024, rhs: i1024) -> Option<(i1024, i1024)> {
+    let magnitude = |val: i1024| u1024::from_le_bytes(val.abs().to_le_bytes());
+    let (a, b) = (magnitude(lhs), magnitude(rhs));
+    let q = i1024::from_le_bytes(a.checked_div(b)?.to_le_bytes());
+    let r = i1024::from_le_bytes(a.checked_rem(b)?.to_le_bytes());
+    let negative = lhs.is_negative() != rhs.is_negative();
+    if q.is_negative() && !negative {
+        // MIN / -1
+        return None;
+    }
+    let q = if negative { q.wrapping_neg() } else { q };
+    let r = if lhs.is_negative() { r.wrapping_neg() } else { r };
+    Some((q, r))
+}
+
+/// Performs x87 extended precision float operation using IEEE quad precision and rounds the
+/// result back. x87 arithmetic in `apfloat` may panic on some operand bit patterns, while the
+/// conversion into quad precision is always exact.
+fn x87_op(
+    lhs: Number,
+    rhs: Number,
+    round: Round,
+    op: impl FnOnce(ieee::Quad, ieee::Quad) -> StatusAnd<ieee::Quad>,
+) -> MaybeNumber {
+    let mut loses_info = false;
+    let lhs: ieee::Quad = ieee::X87DoubleExtended::from(lhs).convert(&mut loses_info).value;
+    let rhs: ieee::Quad = ieee::X87DoubleExtended::from(rhs).convert(&mut loses_info).value;
+    let res = op(lhs, rhs);
+    let x87: StatusAnd<ieee::X87DoubleExtended> = res.value.convert_r(round, &mut loses_info);
+    (res.status | x87.status).and(x87.value).into()
+}
+
 impl Rem for Number {
     type Output = Option<Number>;
 
@@ -432,23 +477,25 @@ impl Rem for Number {
         if rhs.is_zero() {
             return None;
         }
-        let layout = self.layout();
-        Some(match layout {
-            Layout::Integer(IntLayout { signed: true, .. }) => {
+        // Remainder is never greater by its absolute value than the dividend, so it always fits
+        // into the dividend layout
+        match self.layout() {
+            Layout::Integer(IntLayout {
+                signed: false,
+                bytes,
+            }) => {
                 let val1 = self.to_u1024_bytes();
                 let val2 = rhs.to_u1024_bytes();
-                val1.rem(val2).into()
-            }
-            Layout::Integer(IntLayout { signed: false, .. }) if layout.bits() <= 128 => {
-                let val1 = i128::from(self);
-                let val2 = i128::from(rhs);
-                val1.rem(val2).into()
-            }
-            Layout::Integer(IntLayout { .. }) => {
-                todo!("(#11) implement large signed number modulo division algorithm")
+                Number::from(val1.rem(val2)).reshaped(Layout::unsigned(bytes), false)
             }
+            Layout::Integer(IntLayout {
+                signed: true,
+                bytes,
+            }) => i1024_div_rem(self.to_i1024_bytes(), rhs.to_i1024_bytes())
+                .map(|(_, r)| Number::from(r))
+                .and_then(|n| n.reshaped(Layout::signed(bytes), false)),
             Layout::Float(_) => panic!("modulo division of float number"),
-        })
+        }
     }
 }
 
@@ -983,4 +1030,41 @@ mod tests {
         let z = MaybeNumber::from(bf16::INFINITY);
         assert_eq!(x.float_div(y, RoundingFlag::Ceil), z);
     }
+
+    #[test]
+    fn rem() {
+        // Unsigned values are not reinterpreted as signed
+        assert_eq!(Number::from(200u8) % Number::from(7u8), Some(Number::from(4u8)));
+        let x = Number::from((1u128 << 127) + 5) % Number::from(10u128);
+        assert_eq!(x, Some(Number::from(3u128)));
+        let x = Number::from(u1024::MAX) % Number::from(u1024::from(10u8));
+        assert_eq!(x, Some(Number::from(u1024::from(5u8))));
+        assert_eq!(Number::from(200u8) % Number::from(0u8), None);
+
+        // Signed remainder takes the sign of the dividend
+        assert_eq!(Number::from(-7i8) % Number::from(3i8), Some(Number::from(-1i8)));
+        assert_eq!(Number::from(7i8) % Number::from(-3i8), Some(Number::from(1i8)));
+        assert_eq!(Number::from(-7i8) % Number::from(-3i8), Some(Number::from(-1i8)));
+        assert_eq!(Number::from(-6i8) % Number::from(3i8), Some(Number::from(0i8)));
+        assert_eq!(Number::from(-128i8) % Number::from(-1i8), Some(Number::from(0i8)));
+        assert_eq!(Number::from(-128i8) % Number::from(127i8), Some(Number::from(-1i8)));
+        let x = Number::from(i64::MIN + 1) % Number::from(-10i64);
+        assert_eq!(x, Some(Number::from(-7i64)));
+        assert_eq!(x.unwrap().layout(), Layout::signed(8));
+    }
+
+    #[test]
+    fn rounding_cmp() {
+        let f32 = |val: f32| Number::with(val.to_le_bytes(), FloatLayout::IeeeSingle).unwrap();
+        let one = f32(1.0);
+        let one_ulp = f32(f32::from_bits(1.0f32.to_bits() + 1));
+        let two_ulp = f32(f32::from_bits(1.0f32.to_bits() + 2));
+        // The last bit of significand is ignored
+        assert!(one.rounding_eq(&one_ulp));
+        assert_eq!(one.rounding_cmp(&two_ulp), Ordering::Less);
+        assert_eq!(two_ulp.rounding_cmp(&one_ulp), Ordering::Greater);
+        // Exponent is not modified, so the values can't turn into NaN
+        assert!(f32(f32::MAX).rounding_eq(&f32(f32::MAX)));
+        assert_eq!(f32(-f32::MAX).rounding_cmp(&f32(f32::MAX)), Ordering::Less);
+    }
 }
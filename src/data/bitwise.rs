@@ -52,27 +52,21 @@ impl BitAnd for Number {
     type Output = Number;
 
     #[inline]
-    fn bitand(self, rhs: Self) -> Self::Output {
-        self.to_u1024_bytes().bitand(rhs.to_u1024_bytes()).into()
-    }
+    fn bitand(self, rhs: Self) -> Self::Output { self.bytewise(rhs, |a, b| a & b) }
 }
 
 impl BitOr for Number {
     type Output = Number;
 
     #[inline]
-    fn bitor(self, rhs: Self) -> Self::Output {
-        self.to_u1024_bytes().bitor(rhs.to_u1024_bytes()).into()
-    }
+    fn bitor(self, rhs: Self) -> Self::Output { self.bytewise(rhs, |a, b| a | b) }
 }
 
 impl BitXor for Number {
     type Output = Number;
 
     #[inline]
-    fn bitxor(self, rhs: Self) -> Self::Output {
-        self.to_u1024_bytes().bitxor(rhs.to_u1024_bytes()).into()
-    }
+    fn bitxor(self, rhs: Self) -> Self::Output { self.bytewise(rhs, |a, b| a ^ b) }
 }
 
 impl Shl for Number {
@@ -118,6 +112,17 @@ impl Shr for Number {
 }
 
 impl Number {
+    /// Applies bitwise operation to each byte of the numbers, zero-extending the shorter one. The
+    /// result takes the layout of the longer number.
+    fn bytewise(self, rhs: Number, op: impl Fn(u8, u8) -> u8) -> Number {
+        let (mut res, other) = if self.len() >= rhs.len() { (self, rhs) } else { (rhs, self) };
+        for i in 0..res.len() {
+            let b = if i < other.len() { other[i] } else { 0 };
+            res[i] = op(res[i], b);
+        }
+        res
+    }
+
     /// Cyclic bit shift left. Panics if the number is not an integer.
     pub fn scl(self, shift: Number) -> Number {
         let layout = self.layout();
@@ -23,91 +23,106 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-#[cfg(feature = "alloc")]
-use alloc::boxed::Box;
 #[cfg(feature = "alloc")]
 use alloc::vec::Vec;
 use core::borrow::{Borrow, BorrowMut};
+use core::cmp::Ordering;
 use core::convert::TryFrom;
 use core::fmt::{self, Debug, Display, Formatter};
-use core::ops::Range;
+use core::ops::{Bound, Range, RangeBounds};
+use core::str::FromStr;
 
 use amplify::confinement::{SmallBlob, TinyBlob};
 use amplify::num::error::OverflowError;
 
+/// Errors happening when modifying or slicing [`ByteStr`].
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Display)]
+#[display(doc_comments)]
+#[cfg_attr(feature = "std", derive(Error))]
+pub enum ByteStrError {
+    /// byte string length would become {0} bytes, exceeding the maximum of 65535 bytes.
+    Overflow(usize),
+
+    /// range {start}..{end} is out of bounds of the byte string of {len} bytes.
+    OutOfBounds {
+        /// Start of the requested range
+        start: usize,
+        /// End of the requested range (exclusive)
+        end: usize,
+        /// Length of the byte string
+        len: u16,
+    },
+
+    /// invalid byte string literal; it must be either a quoted string or a sequence of hex digits.
+    InvalidLiteral,
+}
+
 /// Large binary bytestring object.
-#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+///
+/// The string is a growable buffer which may be up to [`ByteStr::MAX_LEN`] bytes long; its memory
+/// allocation is sized to the actual length of the string. Operations which may exceed the
+/// maximal length or access bytes outside the string return [`ByteStrError`] and leave the string
+/// unmodified.
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
 pub struct ByteStr {
-    /// Adjusted slice length.
-    len: u16,
-
     /// Slice bytes
-    #[doc(hidden)]
-    pub bytes: Box<[u8; u16::MAX as usize]>,
+    bytes: Vec<u8>,
 }
 
-impl Default for ByteStr {
-    fn default() -> ByteStr {
-        ByteStr {
-            len: 0,
-            bytes: Box::new([0u8; u16::MAX as usize]),
-        }
+impl PartialOrd for ByteStr {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+
+impl Ord for ByteStr {
+    /// Strings are ordered by their length first, and then lexicographically.
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.len().cmp(&other.len()).then_with(|| self.bytes.cmp(&other.bytes))
     }
 }
 
 impl AsRef<[u8]> for ByteStr {
     #[inline]
-    fn as_ref(&self) -> &[u8] { &self.bytes[..self.len as usize] }
+    fn as_ref(&self) -> &[u8] { &self.bytes }
 }
 
 impl AsMut<[u8]> for ByteStr {
     #[inline]
-    fn as_mut(&mut self) -> &mut [u8] { &mut self.bytes[..self.len as usize] }
+    fn as_mut(&mut self) -> &mut [u8] { &mut self.bytes }
 }
 
 impl Borrow<[u8]> for ByteStr {
     #[inline]
-    fn borrow(&self) -> &[u8] { &self.bytes[..self.len as usize] }
+    fn borrow(&self) -> &[u8] { &self.bytes }
 }
 
 impl BorrowMut<[u8]> for ByteStr {
     #[inline]
-    fn borrow_mut(&mut self) -> &mut [u8] { &mut self.bytes[..self.len as usize] }
+    fn borrow_mut(&mut self) -> &mut [u8] { &mut self.bytes }
 }
 
 impl Extend<u8> for ByteStr {
+    /// Panics if the resulting length of the string exceeds [`ByteStr::MAX_LEN`]. Use
+    /// [`ByteStr::try_extend`] for a non-panicking version.
     fn extend<T: IntoIterator<Item = u8>>(&mut self, iter: T) {
-        let mut pos = self.len();
-        let iter = iter.into_iter();
         for byte in iter {
-            assert!(pos < u16::MAX);
-            self.bytes[pos as usize] = byte;
-            pos += 1;
+            self.push(byte).expect("ByteStr length exceeds u16::MAX");
         }
-        self.len = pos;
     }
 }
 
 impl From<&TinyBlob> for ByteStr {
     fn from(blob: &TinyBlob) -> Self {
-        let len = blob.len_u8() as u16;
-        let mut bytes = [0u8; u16::MAX as usize];
-        bytes[0..(len as usize)].copy_from_slice(blob.as_slice());
         ByteStr {
-            len,
-            bytes: Box::new(bytes),
+            bytes: blob.to_vec(),
         }
     }
 }
 
 impl From<&SmallBlob> for ByteStr {
     fn from(blob: &SmallBlob) -> Self {
-        let len = blob.len_u16();
-        let mut bytes = [0u8; u16::MAX as usize];
-        bytes[0..(len as usize)].copy_from_slice(blob.as_slice());
         ByteStr {
-            len,
-            bytes: Box::new(bytes),
+            bytes: blob.to_vec(),
         }
     }
 }
@@ -131,16 +146,94 @@ impl TryFrom<&[u8]> for ByteStr {
                 value: len,
             });
         }
-        let mut bytes = [0u8; u16::MAX as usize];
-        bytes[0..len].copy_from_slice(slice.as_ref());
         Ok(ByteStr {
-            len: len as u16,
-            bytes: Box::new(bytes),
+            bytes: slice.to_vec(),
         })
     }
 }
 
+impl TryFrom<Vec<u8>> for ByteStr {
+    type Error = OverflowError;
+
+    fn try_from(bytes: Vec<u8>) -> Result<Self, Self::Error> {
+        if bytes.len() > ByteStr::MAX_LEN {
+            return Err(OverflowError {
+                max: ByteStr::MAX_LEN + 1,
+                value: bytes.len(),
+            });
+        }
+        Ok(ByteStr { bytes })
+    }
+}
+
+impl From<ByteStr> for Vec<u8> {
+    #[inline]
+    fn from(s: ByteStr) -> Self { s.bytes }
+}
+
+/// Parses byte string literal, which is either a double-quoted string with `\\`, `\"`, `\n`,
+/// `\r`, `\t`, `\0` and `\xNN` escapes, or a sequence of hex digits.
+impl FromStr for ByteStr {
+    type Err = ByteStrError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut bytes = Vec::with_capacity(s.len());
+        if let Some(text) = s.strip_prefix('"').and_then(|s| s.strip_suffix('"')) {
+            let mut chars = text.chars();
+            while let Some(c) = chars.next() {
+                let byte = match c {
+                    '"' => return Err(ByteStrError::InvalidLiteral),
+                    '\\' => match chars.next() {
+                        Some('\\') => b'\\',
+                        Some('"') => b'"',
+                        Some('n') => b'\n',
+                        Some('r') => b'\r',
+                        Some('t') => b'\t',
+                        Some('0') => 0,
+                        Some('x') => {
+                            let hi = chars.next().and_then(|c| c.to_digit(16));
+                            let lo = chars.next().and_then(|c| c.to_digit(16));
+                            match (hi, lo) {
+                                (Some(hi), Some(lo)) => (hi << 4 | lo) as u8,
+                                _ => return Err(ByteStrError::InvalidLiteral),
+                            }
+                        }
+                        _ => return Err(ByteStrError::InvalidLiteral),
+                    },
+                    c => {
+                        let mut buf = [0u8; 4];
+                        bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+                        continue;
+                    }
+                };
+                bytes.push(byte);
+            }
+        } else {
+            if s.is_empty() || s.len() % 2 != 0 {
+                return Err(ByteStrError::InvalidLiteral);
+            }
+            for pair in s.as_bytes().chunks(2) {
+                let hi = char::from(pair[0]).to_digit(16);
+                let lo = char::from(pair[1]).to_digit(16);
+                match (hi, lo) {
+                    (Some(hi), Some(lo)) => bytes.push((hi << 4 | lo) as u8),
+                    _ => return Err(ByteStrError::InvalidLiteral),
+                }
+            }
+        }
+        let len = bytes.len();
+        ByteStr::try_from(bytes).map_err(|_| ByteStrError::Overflow(len))
+    }
+}
+
 impl ByteStr {
+    /// Maximal length of the string, in bytes.
+    pub const MAX_LEN: usize = u16::MAX as usize;
+
+    /// Constructs empty string without allocating memory.
+    #[inline]
+    pub const fn new() -> ByteStr { ByteStr { bytes: Vec::new() } }
+
     /// Constructs blob from slice of bytes.
     ///
     /// Panics if the length of the slice is greater than `u16::MAX` bytes.
@@ -152,19 +245,23 @@ impl ByteStr {
 
     /// Returns correct length of the string, in range `0 ..= u16::MAX`
     #[inline]
-    pub fn len(&self) -> u16 { self.len }
+    pub fn len(&self) -> u16 { self.bytes.len() as u16 }
 
     /// Returns when the string has a zero length
     #[inline]
-    pub fn is_empty(&self) -> bool { self.len == 0 }
+    pub fn is_empty(&self) -> bool { self.bytes.is_empty() }
 
-    /// Adjusts the length of the string
+    /// Adjusts the length of the string, filling new bytes with zeros
     #[inline]
-    pub fn adjust_len(&mut self, new_len: u16) { self.len = new_len }
+    pub fn adjust_len(&mut self, new_len: u16) { self.bytes.resize(new_len as usize, 0) }
 
-    /// Extends the length of the string if necessary
+    /// Extends the length of the string if necessary, filling new bytes with zeros
     #[inline]
-    pub fn extend_len(&mut self, new_len: u16) { self.len = new_len.max(self.len) }
+    pub fn extend_len(&mut self, new_len: u16) {
+        if new_len > self.len() {
+            self.adjust_len(new_len)
+        }
+    }
 
     /// Fills range within a string with the provided byte value, increasing string length if
     /// necessary
@@ -178,6 +275,152 @@ impl ByteStr {
     /// Returns vector representation of the contained bytecode
     #[inline]
     pub fn to_vec(&self) -> Vec<u8> { self.as_ref().to_vec() }
+
+    /// Returns bytes of the string. Replaces formerly public `bytes` field, which exposed a
+    /// fixed-size buffer of `u16::MAX` bytes; unlike it, the returned slice ends at the string
+    /// length.
+    #[doc(hidden)]
+    #[inline]
+    #[deprecated(since = "0.11.0-beta.9", note = "use `as_ref` method")]
+    pub fn bytes(&self) -> &[u8] { &self.bytes }
+
+    /// Returns the number of bytes which may be still added to the string.
+    #[inline]
+    pub fn remaining(&self) -> u16 { u16::MAX - self.len() }
+
+    /// Returns byte at a given position, or `None` if the position is outside the string.
+    #[inline]
+    pub fn get(&self, pos: u16) -> Option<u8> { self.bytes.get(pos as usize).copied() }
+
+    /// Returns bytes within the given range.
+    pub fn slice(&self, range: impl RangeBounds<u16>) -> Result<&[u8], ByteStrError> {
+        let range = self.bounds(range)?;
+        Ok(&self.bytes[range])
+    }
+
+    /// Constructs a new string from bytes within the given range.
+    #[inline]
+    pub fn substr(&self, range: impl RangeBounds<u16>) -> Result<ByteStr, ByteStrError> {
+        self.slice(range).map(|bytes| ByteStr {
+            bytes: bytes.to_vec(),
+        })
+    }
+
+    /// Splits the string into two at the given position. The first string contains bytes
+    /// `[0, pos)` and the second one - bytes `[pos, len)`.
+    pub fn split_at(&self, pos: u16) -> Result<(ByteStr, ByteStr), ByteStrError> {
+        let pos = self.bounds(pos..)?.start;
+        let (first, second) = self.bytes.split_at(pos);
+        Ok((ByteStr::with(first), ByteStr::with(second)))
+    }
+
+    /// Appends a byte to the end of the string.
+    pub fn push(&mut self, byte: u8) -> Result<(), ByteStrError> {
+        self.check_grow(1)?;
+        self.bytes.push(byte);
+        Ok(())
+    }
+
+    /// Removes the last byte from the string and returns it, or `None` if the string is empty.
+    #[inline]
+    pub fn pop(&mut self) -> Option<u8> { self.bytes.pop() }
+
+    /// Appends all bytes from a slice to the end of the string.
+    pub fn extend_from_slice(&mut self, slice: impl AsRef<[u8]>) -> Result<(), ByteStrError> {
+        let slice = slice.as_ref();
+        self.check_grow(slice.len())?;
+        self.bytes.extend_from_slice(slice);
+        Ok(())
+    }
+
+    /// Appends all bytes from an iterator to the end of the string. If the iterator yields more
+    /// bytes than the string may hold, errors leaving the string unmodified.
+    pub fn try_extend(&mut self, iter: impl IntoIterator<Item = u8>) -> Result<(), ByteStrError> {
+        let len = self.bytes.len();
+        for byte in iter {
+            if let Err(err) = self.push(byte) {
+                self.bytes.truncate(len);
+                return Err(err);
+            }
+        }
+        Ok(())
+    }
+
+    /// Inserts a byte at a given position, shifting all bytes after it to the right.
+    pub fn insert(&mut self, pos: u16, byte: u8) -> Result<(), ByteStrError> {
+        self.insert_slice(pos, [byte])
+    }
+
+    /// Inserts all bytes from a slice at a given position, shifting all bytes after it to the
+    /// right.
+    pub fn insert_slice(&mut self, pos: u16, slice: impl AsRef<[u8]>) -> Result<(), ByteStrError> {
+        let slice = slice.as_ref();
+        let pos = self.bounds(pos..)?.start;
+        self.check_grow(slice.len())?;
+        self.bytes.splice(pos..pos, slice.iter().copied());
+        Ok(())
+    }
+
+    /// Removes and returns byte at a given position, shifting all bytes after it to the left.
+    pub fn remove(&mut self, pos: u16) -> Result<u8, ByteStrError> {
+        let range = self.bounds(pos..=pos)?;
+        Ok(self.bytes.remove(range.start))
+    }
+
+    /// Replaces bytes within the given range with the bytes from a slice, returning the removed
+    /// bytes. The length of the replacement does not need to match the length of the range.
+    pub fn splice(
+        &mut self,
+        range: impl RangeBounds<u16>,
+        replace_with: impl AsRef<[u8]>,
+    ) -> Result<ByteStr, ByteStrError> {
+        let replace_with = replace_with.as_ref();
+        let range = self.bounds(range)?;
+        let new_len = self.bytes.len() - range.len() + replace_with.len();
+        if new_len > Self::MAX_LEN {
+            return Err(ByteStrError::Overflow(new_len));
+        }
+        let removed = self.bytes.splice(range, replace_with.iter().copied()).collect();
+        Ok(ByteStr { bytes: removed })
+    }
+
+    /// Shortens the string to the given length. Has no effect if the string is already shorter.
+    #[inline]
+    pub fn truncate(&mut self, len: u16) { self.bytes.truncate(len as usize) }
+
+    /// Removes all bytes from the string.
+    #[inline]
+    pub fn clear(&mut self) { self.bytes.clear() }
+
+    fn check_grow(&self, additional: usize) -> Result<(), ByteStrError> {
+        let new_len = self.bytes.len() + additional;
+        if new_len > Self::MAX_LEN {
+            return Err(ByteStrError::Overflow(new_len));
+        }
+        Ok(())
+    }
+
+    fn bounds(&self, range: impl RangeBounds<u16>) -> Result<Range<usize>, ByteStrError> {
+        let len = self.bytes.len();
+        let start = match range.start_bound() {
+            Bound::Included(start) => *start as usize,
+            Bound::Excluded(start) => *start as usize + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(end) => *end as usize + 1,
+            Bound::Excluded(end) => *end as usize,
+            Bound::Unbounded => len,
+        };
+        if start > end || end > len {
+            return Err(ByteStrError::OutOfBounds {
+                start,
+                end,
+                len: self.len(),
+            });
+        }
+        Ok(start..end)
+    }
 }
 
 #[cfg(not(feature = "std"))]
@@ -201,7 +444,7 @@ impl Display for ByteStr {
 
         use amplify::hex::ToHex;
 
-        let vec = Vec::from(&self.bytes[..self.len as usize]);
+        let vec = self.bytes.clone();
         if f.alternate() {
             for (line, slice) in self.as_ref().chunks(16).enumerate() {
                 write!(f, "\x1B[0;35m{:>1$x}0  |  \x1B[0m", line, f.width().unwrap_or(1) - 1)?;
@@ -237,25 +480,17 @@ impl Display for ByteStr {
             // 4)..].to_hex())
         } else if let Ok(s) = String::from_utf8(vec) {
             f.write_str("\"")?;
-            let mut ctl = false;
             for c in s.chars() {
-                let v = c as u32;
-                if (c.is_control() && v != 0x20) || !(0x20..0x7F).contains(&v) {
-                    if !ctl {
-                        ctl = true;
-                    }
-                    if v <= 0xFF {
-                        write!(f, "{v:02X}")?;
-                    } else if v <= 0xFFFF {
-                        write!(f, "{v:04X}")?;
-                    } else {
-                        write!(f, "{v:08X}")?;
+                match c {
+                    '"' => f.write_str("\\\"")?,
+                    '\\' => f.write_str("\\\\")?,
+                    ' '..='~' => f.write_char(c)?,
+                    _ => {
+                        let mut buf = [0u8; 4];
+                        for byte in c.encode_utf8(&mut buf).bytes() {
+                            write!(f, "\\x{byte:02X}")?;
+                        }
                     }
-                } else {
-                    if ctl {
-                        ctl = false;
-                    }
-                    f.write_char(c)?;
                 }
             }
             f.write_str("\"")
@@ -332,3 +567,88 @@ mod _serde {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn edit() {
+        let mut s = ByteStr::new();
+        s.push(1).unwrap();
+        s.extend_from_slice([2, 3, 4]).unwrap();
+        s.insert(0, 0).unwrap();
+        assert_eq!(s.as_ref(), &[0, 1, 2, 3, 4]);
+        assert_eq!(s.remove(2), Ok(2));
+        assert_eq!(s.splice(1..3, [7, 7, 7]).unwrap().as_ref(), &[1, 3]);
+        assert_eq!(s.as_ref(), &[0, 7, 7, 7, 4]);
+        assert_eq!(s.pop(), Some(4));
+        s.truncate(2);
+        assert_eq!(s.as_ref(), &[0, 7]);
+
+        let err = ByteStrError::OutOfBounds {
+            start: 3,
+            end: 4,
+            len: 2,
+        };
+        assert_eq!(s.remove(3), Err(err));
+        assert_eq!(
+            s.insert(3, 0),
+            Err(ByteStrError::OutOfBounds {
+                start: 3,
+                end: 2,
+                len: 2
+            })
+        );
+        assert_eq!(s.as_ref(), &[0, 7]);
+    }
+
+    #[test]
+    fn slicing() {
+        let s = ByteStr::with(b"hello world");
+        assert_eq!(s.slice(..5), Ok(&b"hello"[..]));
+        assert_eq!(s.slice(6..=10), Ok(&b"world"[..]));
+        assert_eq!(s.substr(6..).unwrap(), ByteStr::with(b"world"));
+        assert!(s.slice(6..12).is_err());
+        assert!(s.slice((Bound::Included(6), Bound::Excluded(5))).is_err());
+        assert_eq!(s.get(4), Some(b'o'));
+        assert_eq!(s.get(11), None);
+
+        let (first, second) = s.split_at(5).unwrap();
+        assert_eq!(first, ByteStr::with(b"hello"));
+        assert_eq!(second, ByteStr::with(b" world"));
+        assert!(s.split_at(12).is_err());
+    }
+
+    #[test]
+    fn overflow() {
+        let mut s = ByteStr::with([0u8; ByteStr::MAX_LEN - 1]);
+        assert_eq!(s.remaining(), 1);
+        s.push(1).unwrap();
+        assert_eq!(s.push(2), Err(ByteStrError::Overflow(ByteStr::MAX_LEN + 1)));
+        assert_eq!(s.insert(0, 2), Err(ByteStrError::Overflow(ByteStr::MAX_LEN + 1)));
+        assert!(s.splice(..1, [1, 2]).is_err());
+        assert!(s.splice(..2, [1, 2]).is_ok());
+        s.truncate(u16::MAX - 2);
+        assert!(s.try_extend([1, 2, 3]).is_err());
+        assert_eq!(s.len(), u16::MAX - 2);
+        assert_eq!(s.get(u16::MAX - 3), Some(0));
+        assert!(ByteStr::try_from(vec![0u8; ByteStr::MAX_LEN + 1]).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn literal() {
+        let samples =
+            [&b"plain text"[..], b"\"quoted\\\" \n\x00\x7F", "ünï".as_bytes(), &[0xFF, 0]];
+        for bytes in samples {
+            let s = ByteStr::with(bytes);
+            assert_eq!(ByteStr::from_str(&s.to_string()), Ok(s));
+        }
+        assert_eq!(ByteStr::from_str(r#""a\x41\t""#).unwrap().as_ref(), b"aA\t");
+        assert_eq!(ByteStr::from_str("00ff").unwrap().as_ref(), &[0, 0xFF]);
+        assert_eq!(ByteStr::from_str("0ff"), Err(ByteStrError::InvalidLiteral));
+        assert_eq!(ByteStr::from_str(r#""a"b""#), Err(ByteStrError::InvalidLiteral));
+        assert_eq!(ByteStr::from_str(r#""\q""#), Err(ByteStrError::InvalidLiteral));
+    }
+}
@@ -35,6 +35,7 @@ use amplify::{confinement, IoError, Wrapper};
 use crate::data::encoding::DecodeError::InvalidBool;
 use crate::data::{ByteStr, FloatLayout, IntLayout, Layout, MaybeNumber, Number, NumberLayout};
 use crate::library::{IsaSegError, LibId, LibSite, SegmentError};
+use crate::reg::{CoreRegs, NumericRegister, Reg32, RegA, RegAFR, RegF, RegR};
 
 /// Trait for encodable container data structures used by AluVM and runtime environments
 pub trait Encode {
@@ -129,6 +130,12 @@ pub enum DecodeError {
     #[display(inner)]
     #[from]
     IsaSeg(IsaSegError),
+
+    /// cycle counter value {0} exceeds the cycle limit {1}
+    CycleLimit(u16, u16),
+
+    /// call stack depth {0} exceeds the call depth limit {1}
+    CallDepth(u16, u16),
 }
 
 /// Wrapper around collections which may contain at most [`u8::MAX`] elements
@@ -229,11 +236,55 @@ impl Decode for u16 {
     }
 }
 
+impl Encode for u32 {
+    type Error = io::Error;
+
+    #[inline]
+    fn encode(&self, mut writer: impl Write) -> Result<usize, Self::Error> {
+        writer.write_all(&self.to_le_bytes())?;
+        Ok(4)
+    }
+}
+
+impl Decode for u32 {
+    type Error = io::Error;
+
+    #[inline]
+    fn decode(mut reader: impl Read) -> Result<Self, Self::Error>
+    where Self: Sized {
+        let mut word = [0u8; 4];
+        reader.read_exact(&mut word)?;
+        Ok(u32::from_le_bytes(word))
+    }
+}
+
+impl Encode for u64 {
+    type Error = io::Error;
+
+    #[inline]
+    fn encode(&self, mut writer: impl Write) -> Result<usize, Self::Error> {
+        writer.write_all(&self.to_le_bytes())?;
+        Ok(8)
+    }
+}
+
+impl Decode for u64 {
+    type Error = io::Error;
+
+    #[inline]
+    fn decode(mut reader: impl Read) -> Result<Self, Self::Error>
+    where Self: Sized {
+        let mut word = [0u8; 8];
+        reader.read_exact(&mut word)?;
+        Ok(u64::from_le_bytes(word))
+    }
+}
+
 impl Encode for String {
     type Error = EncodeError;
 
     fn encode(&self, mut writer: impl Write) -> Result<usize, Self::Error> {
-        let len = self.as_bytes().len();
+        let len = self.len();
         if len > u8::MAX as usize {
             return Err(EncodeError::StringTooLong(len));
         }
@@ -605,3 +656,105 @@ impl Decode for LibSite {
         Ok(LibSite::with(pos, id))
     }
 }
+
+fn numeric_regs() -> impl Iterator<Item = RegAFR> {
+    RegA::ALL
+        .into_iter()
+        .map(RegAFR::A)
+        .chain(RegF::ALL.into_iter().map(RegAFR::F))
+        .chain(RegR::ALL.into_iter().map(RegAFR::R))
+}
+
+/// Encodes complete state of the registers, including control flow registers, call stack and
+/// resource limits. Values of numeric registers are encoded bit-wise, such that the decoded
+/// registers are exactly equal to the encoded ones.
+impl Encode for CoreRegs {
+    type Error = io::Error;
+
+    fn encode(&self, mut writer: impl Write) -> Result<usize, Self::Error> {
+        let mut count = 0;
+        for reg in numeric_regs() {
+            let values = Reg32::ALL.map(|idx| self.get_n_bits(reg, idx));
+            let mask = values
+                .iter()
+                .enumerate()
+                .filter(|(_, val)| val.is_some())
+                .fold(0u32, |mask, (idx, _)| mask | (1 << idx));
+            count += mask.encode(&mut writer)?;
+            for val in values.iter().filter_map(|val| **val) {
+                writer.write_all(val.as_ref())?;
+                count += val.len() as usize;
+            }
+        }
+
+        let mask = self
+            .s16
+            .iter()
+            .enumerate()
+            .filter(|(_, val)| val.is_some())
+            .fold(0u16, |mask, (idx, _)| mask | (1 << idx));
+        count += mask.encode(&mut writer)?;
+        for val in self.s16.iter().flatten() {
+            count += val.encode(&mut writer)?;
+        }
+
+        count += self.st0.encode(&mut writer)?;
+        count += self.cy0.encode(&mut writer)?;
+        count += self.ca0.encode(&mut writer)?;
+        count += self.cl0.is_some().encode(&mut writer)?;
+        count += self.cl0.unwrap_or_default().encode(&mut writer)?;
+        count += self.cy_limit.encode(&mut writer)?;
+        count += self.cp_limit.encode(&mut writer)?;
+        count += self.cp0.encode(&mut writer)?;
+        for site in self.call_stack() {
+            count += site.encode(&mut writer)?;
+        }
+        Ok(count)
+    }
+}
+
+impl Decode for CoreRegs {
+    type Error = DecodeError;
+
+    fn decode(mut reader: impl Read) -> Result<Self, Self::Error>
+    where Self: Sized {
+        let mut regs = CoreRegs::default();
+        for reg in numeric_regs() {
+            let mask = u32::decode(&mut reader)?;
+            for idx in Reg32::ALL.into_iter().filter(|idx| mask & (1 << idx.to_usize()) != 0) {
+                let mut vec = vec![0u8; reg.bytes() as usize];
+                reader.read_exact(&mut vec)?;
+                let val = Number::with(&vec, reg.layout())
+                    .ok_or(DecodeError::NumberLayout(reg.layout(), vec))?;
+                regs.set_n(reg, idx, val);
+            }
+        }
+
+        let mask = u16::decode(&mut reader)?;
+        for (idx, val) in regs.s16.iter_mut().enumerate() {
+            if mask & (1 << idx) != 0 {
+                *val = Some(ByteStr::decode(&mut reader)?);
+            }
+        }
+
+        regs.st0 = bool::decode(&mut reader)?;
+        regs.cy0 = u16::decode(&mut reader)?;
+        regs.ca0 = u64::decode(&mut reader)?;
+        let limited = bool::decode(&mut reader)?;
+        let limit = u64::decode(&mut reader)?;
+        regs.cl0 = limited.then_some(limit);
+        regs.cy_limit = u16::decode(&mut reader)?;
+        regs.cp_limit = u16::decode(&mut reader)?;
+        regs.cp0 = u16::decode(&mut reader)?;
+        if regs.cy0 > regs.cy_limit {
+            return Err(DecodeError::CycleLimit(regs.cy0, regs.cy_limit));
+        }
+        if regs.cp0 > regs.cp_limit {
+            return Err(DecodeError::CallDepth(regs.cp0, regs.cp_limit));
+        }
+        for _ in 0..regs.cp0 {
+            regs.cs0.push(LibSite::decode(&mut reader)?);
+        }
+        Ok(regs)
+    }
+}
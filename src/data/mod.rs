@@ -32,7 +32,7 @@ mod byte_str;
 pub mod encoding;
 mod number;
 
-pub use byte_str::ByteStr;
+pub use byte_str::{ByteStr, ByteStrError};
 pub use number::{
     FloatLayout, IntLayout, Layout, LiteralParseError, MaybeNumber, Number, NumberLayout, Step,
 };
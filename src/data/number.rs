@@ -525,12 +525,18 @@ impl MaybeNumber {
     }
 
     /// Transforms internal value layout returning whether this was possible without discarding any
-    /// bit information
+    /// bit information. If the layout conversion is not supported, the value is set to `None`.
     #[inline]
     pub fn reshape(&mut self, to: Layout) -> bool {
         match self.0 {
             None => true,
-            Some(ref mut val) => val.reshape(to),
+            Some(ref mut val) => {
+                let res = val.reshape(to);
+                if val.layout() != to {
+                    self.0 = None;
+                }
+                res
+            }
         }
     }
 }
@@ -619,7 +625,9 @@ impl FromStr for MaybeNumber {
     type Err = LiteralParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(if s.contains('p') || s.contains('.') {
+        Ok(if s == "~" {
+            MaybeNumber::none()
+        } else if s.contains('p') || s.contains('.') {
             ieee::Quad::from_str(s)?.into()
         } else {
             Number::from_str(s)?.into()
@@ -926,13 +934,14 @@ impl Number {
         if self.len() == 0 {
             return 0;
         }
+        let negative = self.is_negative();
         let empty_bytes = self[..]
             .iter()
             .rev()
-            .take_while(|&&v| if self.is_negative() { v == 0xff } else { v == 0 })
+            .take_while(|&&v| if negative { v == 0xff } else { v == 0 })
             .count() as u16;
         let index = if self.len() > empty_bytes { self.len() - empty_bytes - 1 } else { 0 };
-        let head_bits = match self.is_negative() {
+        let head_bits = match negative {
             true => 8 - self[index].leading_ones(),
             false => 8 - self[index].leading_zeros(),
         };
@@ -955,11 +964,11 @@ impl Number {
 
     /// Detects if the value is equal to zero
     pub fn is_zero(self) -> bool {
-        let mut clean = self.to_clean();
         if self.layout.is_float() {
-            clean = clean.without_sign().expect("should not fail when it is float");
+            let clean = self.to_clean().without_sign().expect("should not fail when it is float");
+            return clean.bytes == [0; 1024];
         }
-        clean.bytes == [0; 1024]
+        self[..].iter().all(|byte| *byte == 0)
     }
 
     /// Detects if the value is `NaN`. For integer layouts always false
@@ -1028,7 +1037,8 @@ impl Number {
     }
 
     /// Transforms internal value layout returning whether this was possible without discarding any
-    /// bit information
+    /// bit information. Layout conversions which are not supported yet leave the value unchanged
+    /// and return `false`.
     pub fn reshape(&mut self, to: Layout) -> bool {
         match (self.layout, to) {
             (from, to) if from == to => true,
@@ -1064,59 +1074,51 @@ impl Number {
                     FloatLayout::IeeeDouble => ieee::Double::from(*self).to_string(),
                     FloatLayout::X87DoubleExt => ieee::X87DoubleExtended::from(*self).to_string(),
                     FloatLayout::IeeeQuad => ieee::Quad::from(*self).to_string(),
-                    FloatLayout::IeeeOct => {
-                        unimplemented!("IEEE octal precision layout conversion")
-                    }
-                    FloatLayout::FloatTapered => unimplemented!("tapered float layout conversion"),
+                    // TODO: IEEE octal precision and tapered float layout conversions
+                    FloatLayout::IeeeOct | FloatLayout::FloatTapered => return false,
                 };
-                *self = match l2 {
-                    FloatLayout::BFloat16 => bf16::from_str(&value)
-                        .ok()
-                        .and_then(|v| MaybeNumber::from(v).0)
-                        .expect("float layout conversion"),
-                    FloatLayout::IeeeHalf => ieee::Half::from_str(&value)
-                        .ok()
-                        .and_then(|v| MaybeNumber::from(v).0)
-                        .expect("float layout conversion"),
-                    FloatLayout::IeeeSingle => ieee::Single::from_str(&value)
-                        .ok()
-                        .and_then(|v| MaybeNumber::from(v).0)
-                        .expect("float layout conversion"),
-                    FloatLayout::IeeeDouble => ieee::Double::from_str(&value)
-                        .ok()
-                        .and_then(|v| MaybeNumber::from(v).0)
-                        .expect("float layout conversion"),
+                let converted = match l2 {
+                    FloatLayout::BFloat16 => {
+                        bf16::from_str(&value).ok().and_then(|v| MaybeNumber::from(v).0)
+                    }
+                    FloatLayout::IeeeHalf => {
+                        ieee::Half::from_str(&value).ok().and_then(|v| MaybeNumber::from(v).0)
+                    }
+                    FloatLayout::IeeeSingle => {
+                        ieee::Single::from_str(&value).ok().and_then(|v| MaybeNumber::from(v).0)
+                    }
+                    FloatLayout::IeeeDouble => {
+                        ieee::Double::from_str(&value).ok().and_then(|v| MaybeNumber::from(v).0)
+                    }
                     FloatLayout::X87DoubleExt => ieee::X87DoubleExtended::from_str(&value)
                         .ok()
-                        .and_then(|v| MaybeNumber::from(v).0)
-                        .expect("float layout conversion"),
-                    FloatLayout::IeeeQuad => ieee::Quad::from_str(&value)
-                        .ok()
-                        .and_then(|v| MaybeNumber::from(v).0)
-                        .expect("float layout conversion"),
-                    FloatLayout::IeeeOct => {
-                        unimplemented!("IEEE octal precision layout conversion")
+                        .and_then(|v| MaybeNumber::from(v).0),
+                    FloatLayout::IeeeQuad => {
+                        ieee::Quad::from_str(&value).ok().and_then(|v| MaybeNumber::from(v).0)
                     }
-                    FloatLayout::FloatTapered => unimplemented!("tapered float layout conversion"),
+                    FloatLayout::IeeeOct | FloatLayout::FloatTapered => None,
                 };
+                if let Some(converted) = converted {
+                    *self = converted;
+                }
                 false
             }
             (Layout::Float(fl), Layout::Integer(_)) => {
                 let val = match fl {
-                    FloatLayout::BFloat16 => todo!("BFloat16 to integer conversion"),
                     FloatLayout::IeeeHalf => ieee::Half::from(*self).to_i256(256),
                     FloatLayout::IeeeSingle => ieee::Single::from(*self).to_i256(256),
                     FloatLayout::IeeeDouble => ieee::Double::from(*self).to_i256(256),
                     FloatLayout::X87DoubleExt => ieee::X87DoubleExtended::from(*self).to_i256(256),
                     FloatLayout::IeeeQuad => ieee::Quad::from(*self).to_i256(256),
                     FloatLayout::IeeeOct => ieee::Oct::from(*self).to_i256(256),
-                    FloatLayout::FloatTapered => unimplemented!("tapered float layout conversion"),
+                    // TODO: BFloat16 and tapered float to integer conversions
+                    FloatLayout::BFloat16 | FloatLayout::FloatTapered => return false,
                 };
                 *self = Number::from(val.value);
-                self.reshape(to);
-                val.status == Status::OK
+                self.reshape(to) && val.status == Status::OK
             }
-            (from, to) => todo!("Number layout reshape from {} to {}", from, to),
+            // TODO: integer to float conversion
+            (Layout::Integer(_), Layout::Float(_)) => false,
         }
     }
 
@@ -1224,7 +1226,10 @@ impl Display for Number {
             Layout::Integer(IntLayout { signed: false, .. }) if self.min_bit_len() < 512 => {
                 Display::fmt(&u512::from(self), f)
             }
-            Layout::Integer(IntLayout { .. }) => Display::fmt(&u1024::from(self), f),
+            Layout::Integer(IntLayout { .. }) if self.min_bit_len() <= 1024 => {
+                Display::fmt(&u1024::from(self), f)
+            }
+            Layout::Integer(IntLayout { .. }) => write!(f, "0x{:X}", self),
             Layout::Float(FloatLayout::BFloat16) => Display::fmt(&half::bf16::from(self), f),
             Layout::Float(FloatLayout::IeeeHalf) => Display::fmt(&ieee::Half::from(self), f),
             Layout::Float(FloatLayout::IeeeSingle) => Display::fmt(&ieee::Single::from(self), f),
@@ -1285,7 +1290,9 @@ impl LowerHex for Number {
             Layout::Integer(IntLayout { .. }) => {
                 #[cfg(feature = "std")]
                 {
-                    f.write_str(u1024::from(self).to_be_bytes().to_hex().trim_start_matches('0'))
+                    let mut be = self[..].to_vec();
+                    be.reverse();
+                    f.write_str(be.to_hex().trim_start_matches('0'))
                 }
                 #[cfg(not(feature = "std"))]
                 {
@@ -1615,8 +1622,8 @@ impl Display for Step {
                 x if x >= 0 => f.write_str("add"),
                 _ => unreachable!(),
             }
-        } else if val.abs() > 1 {
-            Display::fmt(&if val >= 0 { val } else { -val }, f)?;
+        } else if val.unsigned_abs() > 1 {
+            Display::fmt(&val.unsigned_abs(), f)?;
             f.write_char(',')
         } else {
             Ok(())
@@ -1669,6 +1676,17 @@ mod tests {
         assert!(num.is_zero());
         let num = Number::from(1);
         assert!(!num.is_zero());
+        let num = Number::from(-1i8);
+        assert!(!num.is_zero());
+    }
+
+    #[test]
+    fn min_bit_len_test() {
+        assert_eq!(Number::from(0u16).min_bit_len(), 0);
+        assert_eq!(Number::from(5u16).min_bit_len(), 3);
+        assert_eq!(Number::from(-1i16).min_bit_len(), 1);
+        assert_eq!(Number::from(-129i16).min_bit_len(), 9);
+        assert_eq!(Number::from(300i16).min_bit_len(), 10);
     }
 
     #[test]
@@ -0,0 +1,416 @@
+// Reference rust implementation of AluVM (arithmetic logic unit virtual machine).
+// To find more on AluVM please check <https://aluvm.org>
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2021-2024 by
+//     Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2021-2022 LNP/BP Standards Association. All rights reserved.
+// Copyright (C) 2023-2024 UBIDECO Labs,
+//     Institute for Distributed and Cognitive Computing, Switzerland.
+//     All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Step-by-step debugger for AluVM programs
+
+#[cfg(feature = "alloc")]
+use alloc::collections::BTreeSet;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use crate::data::RegValue;
+use crate::isa::{CostModel, HaltReason, InstructionSet};
+use crate::library::{CodeEofError, ExecLib, LibExit, LibId, LibResolver, LibSite};
+use crate::reg::{CoreRegs, Reg};
+use crate::{ExecReport, Vm, VmSnapshot};
+
+/// Event which has interrupted program execution under [`Debugger`]
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum DebugEvent {
+    /// A single instruction was executed and the execution may continue
+    Step,
+
+    /// Execution has reached a breakpoint. The instruction at the breakpoint is not executed yet.
+    Breakpoint(LibSite),
+
+    /// Value of a watched register was changed by the instruction at the provided location
+    Watchpoint {
+        /// Location of the instruction which has changed the register
+        site: LibSite,
+
+        /// Watched register
+        reg: Reg,
+
+        /// Register value before the instruction execution
+        old: RegValue,
+
+        /// Register value after the instruction execution
+        new: RegValue,
+    },
+
+    /// Execution is suspended by an instruction with the provided host request. Stepping further
+    /// resumes the execution.
+    Yield(u16),
+
+    /// Execution has halted and can't be continued
+    Halt(ExecReport),
+}
+
+/// Debugging session for a program executed by a [`Vm`], allowing to run the program instruction
+/// by instruction, or until it reaches a breakpoint or modifies a watched register.
+pub struct Debugger<'vm, Isa, Cost, Resolver>
+where
+    Isa: InstructionSet,
+    Cost: CostModel<Isa>,
+    Resolver: LibResolver,
+    Resolver::Lib: ExecLib<Isa>,
+{
+    vm: &'vm mut Vm<Isa, Cost>,
+    lib_resolver: Resolver,
+    site: LibSite,
+    report: Option<ExecReport>,
+    breakpoints: BTreeSet<LibSite>,
+    watchpoints: BTreeSet<Reg>,
+    started: bool,
+    verified: BTreeSet<LibId>,
+}
+
+impl<'vm, Isa, Cost, Resolver> Debugger<'vm, Isa, Cost, Resolver>
+where
+    Isa: InstructionSet,
+    Cost: CostModel<Isa>,
+    Resolver: LibResolver,
+    Resolver::Lib: ExecLib<Isa>,
+{
+    /// Starts debugging session for the program starting at the provided entry point. No
+    /// instructions are executed until [`Debugger::step`] or [`Debugger::resume`] is called.
+    ///
+    /// The debugger follows the library policies of the virtual machine ([`Vm::lib_policy`] and
+    /// [`Vm::verify_libs`]) in the same way as [`Vm::run`].
+    pub fn new(vm: &'vm mut Vm<Isa, Cost>, entry_point: LibSite, lib_resolver: Resolver) -> Self {
+        Debugger {
+            vm,
+            lib_resolver,
+            site: entry_point,
+            report: None,
+            breakpoints: empty!(),
+            watchpoints: empty!(),
+            started: false,
+            verified: empty!(),
+        }
+    }
+
+    /// Returns location of the next instruction to execute.
+    #[inline]
+    pub fn site(&self) -> LibSite { self.site }
+
+    /// Returns execution report if the program has already halted.
+    #[inline]
+    pub fn report(&self) -> Option<&ExecReport> { self.report.as_ref() }
+
+    /// Detects whether the program has halted and can't be continued.
+    #[inline]
+    pub fn is_halted(&self) -> bool { self.report.is_some() }
+
+    /// Takes snapshot of the debugged virtual machine paused at the current location.
+    #[inline]
+    pub fn snapshot(&self) -> VmSnapshot { self.vm.snapshot(self.site) }
+
+    /// Returns registers of the debugged virtual machine.
+    #[inline]
+    pub fn registers(&self) -> &CoreRegs { &self.vm.registers }
+
+    /// Returns mutable registers of the debugged virtual machine, allowing to alter the program
+    /// state between steps.
+    #[inline]
+    pub fn registers_mut(&mut self) -> &mut CoreRegs { &mut self.vm.registers }
+
+    /// Returns return addresses stored in the call stack, starting from the outermost call.
+    #[inline]
+    pub fn call_stack(&self) -> &[LibSite] { self.vm.registers.call_stack() }
+
+    /// Decodes the next instruction to execute. Returns `None` if the program has halted or the
+    /// library containing the instruction is not known.
+    pub fn current_instr(&self) -> Option<Result<Isa, CodeEofError>> {
+        if self.is_halted() {
+            return None;
+        }
+        let lib = self.lib_resolver.resolve(self.site.lib).ok()?;
+        Some(lib.instr_at(self.site.pos))
+    }
+
+    /// Adds breakpoint at the provided location. Returns `false` if the breakpoint was already
+    /// present.
+    #[inline]
+    pub fn add_breakpoint(&mut self, site: LibSite) -> bool { self.breakpoints.insert(site) }
+
+    /// Removes breakpoint at the provided location. Returns `false` if there were no such
+    /// breakpoint.
+    #[inline]
+    pub fn remove_breakpoint(&mut self, site: LibSite) -> bool { self.breakpoints.remove(&site) }
+
+    /// Returns all set breakpoints.
+    #[inline]
+    pub fn breakpoints(&self) -> &BTreeSet<LibSite> { &self.breakpoints }
+
+    /// Adds register to the list of watched registers. Returns `false` if the register was already
+    /// watched.
+    #[inline]
+    pub fn add_watchpoint(&mut self, reg: impl Into<Reg>) -> bool {
+        self.watchpoints.insert(reg.into())
+    }
+
+    /// Removes register from the list of watched registers. Returns `false` if the register was
+    /// not watched.
+    #[inline]
+    pub fn remove_watchpoint(&mut self, reg: impl Into<Reg>) -> bool {
+        self.watchpoints.remove(&reg.into())
+    }
+
+    /// Returns all watched registers.
+    #[inline]
+    pub fn watchpoints(&self) -> &BTreeSet<Reg> { &self.watchpoints }
+
+    /// Executes a single instruction.
+    ///
+    /// # Returns
+    ///
+    /// - [`DebugEvent::Halt`] if the program has halted (now or before);
+    /// - [`DebugEvent::Yield`] if the instruction has suspended the execution;
+    /// - [`DebugEvent::Watchpoint`] if the instruction has changed value of a watched register;
+    /// - [`DebugEvent::Step`] otherwise.
+    ///
+    /// Halting and suspending the execution take priority over the watchpoints: the changes made
+    /// to the watched registers by such instructions are not reported.
+    pub fn step(&mut self, context: &Isa::Context<'_>) -> DebugEvent {
+        if let Some(report) = self.report {
+            return DebugEvent::Halt(report);
+        }
+
+        let site = self.site;
+        if !self.started {
+            self.started = true;
+            if let Err(halt) = self.vm.preflight(site, &self.lib_resolver) {
+                return self.refuse(site, halt);
+            }
+        }
+
+        let watched = self
+            .watchpoints
+            .iter()
+            .map(|reg| (*reg, self.vm.registers.get(*reg)))
+            .collect::<Vec<_>>();
+
+        let exit = match self.vm.load_lib(site, &self.lib_resolver, &mut self.verified) {
+            Ok(lib) => {
+                let regs = &mut self.vm.registers;
+                lib.step_with_id(site.lib, site.pos, regs, context, &self.vm.cost, &mut ())
+            }
+            Err(halt) => {
+                self.vm.registers.set_failure();
+                LibExit::Halt(site, halt)
+            }
+        };
+        match exit {
+            LibExit::Call(next) => self.site = next,
+            LibExit::Yield(next, request) => {
+                self.site = next;
+                return DebugEvent::Yield(request);
+            }
+            LibExit::Halt(site, halt) => return self.halt(site, halt),
+        }
+
+        watched
+            .into_iter()
+            .find_map(|(reg, old)| {
+                let new = self.vm.registers.get(reg);
+                (new != old).then_some(DebugEvent::Watchpoint {
+                    site,
+                    reg,
+                    old,
+                    new,
+                })
+            })
+            .unwrap_or(DebugEvent::Step)
+    }
+
+    /// Resumes program execution until it reaches a breakpoint, changes a watched register, yields
+    /// or halts. At least one instruction is always executed, even if the execution is currently
+    /// stopped at a breakpoint.
+    pub fn resume(&mut self, context: &Isa::Context<'_>) -> DebugEvent {
+        loop {
+            match self.step(context) {
+                DebugEvent::Step if self.breakpoints.contains(&self.site) => {
+                    return DebugEvent::Breakpoint(self.site);
+                }
+                DebugEvent::Step => continue,
+                event => return event,
+            }
+        }
+    }
+
+    fn refuse(&mut self, site: LibSite, halt: HaltReason) -> DebugEvent {
+        self.vm.registers.set_failure();
+        self.halt(site, halt)
+    }
+
+    fn halt(&mut self, site: LibSite, halt: HaltReason) -> DebugEvent {
+        let report = ExecReport::with(site, halt, &self.vm.registers);
+        self.report = Some(report);
+        DebugEvent::Halt(report)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::data::MaybeNumber;
+    use crate::isa::{ControlFlowOp, Instr, PutOp};
+    use crate::library::{Lib, LibId};
+    use crate::reg::{Reg32, RegA};
+    use crate::LibPolicy;
+
+    #[test]
+    fn step_break_watch() {
+        let code: Vec<Instr> = vec![
+            Instr::ControlFlow(ControlFlowOp::Test),
+            Instr::Put(PutOp::PutA(RegA::A16, Reg32::Reg0, MaybeNumber::from(5u16).into())),
+            Instr::ControlFlow(ControlFlowOp::Test),
+            Instr::ControlFlow(ControlFlowOp::Ret),
+        ];
+        let lib = Lib::assemble(&code).unwrap();
+        let lib_id = lib.id();
+        let mut vm = Vm::<Instr>::new();
+        let mut debugger = vm.debugger(LibSite::with(0, lib_id), |_: LibId| Some(&lib));
+
+        assert_eq!(debugger.current_instr(), Some(Ok(code[0].clone())));
+        assert_eq!(debugger.step(&()), DebugEvent::Step);
+        assert_eq!(debugger.site(), LibSite::with(1, lib_id));
+        assert_eq!(debugger.current_instr(), Some(Ok(code[1].clone())));
+
+        debugger.add_watchpoint(Reg::A(RegA::A16, Reg32::Reg0));
+        assert_eq!(debugger.resume(&()), DebugEvent::Watchpoint {
+            site: LibSite::with(1, lib_id),
+            reg: Reg::A(RegA::A16, Reg32::Reg0),
+            old: MaybeNumber::none().into(),
+            new: MaybeNumber::from(5u16).into(),
+        });
+        assert_eq!(debugger.current_instr(), Some(Ok(code[2].clone())));
+
+        let ret = LibSite::with(debugger.site().pos + 1, lib_id);
+        debugger.add_breakpoint(ret);
+        assert_eq!(debugger.resume(&()), DebugEvent::Breakpoint(ret));
+        assert_eq!(debugger.current_instr(), Some(Ok(code[3].clone())));
+
+        let DebugEvent::Halt(report) = debugger.resume(&()) else {
+            panic!("program must halt");
+        };
+        assert_eq!(report.halt, HaltReason::Stop);
+        assert_eq!(report.site, ret);
+        assert!(debugger.is_halted());
+        assert_eq!(debugger.step(&()), DebugEvent::Halt(report));
+        assert_eq!(debugger.current_instr(), None);
+    }
+
+    #[test]
+    fn halt_over_watchpoint() {
+        let put: Instr =
+            Instr::Put(PutOp::PutA(RegA::A16, Reg32::Reg0, MaybeNumber::from(5u16).into()));
+        let lib = Lib::assemble(&[put]).unwrap();
+        let mut vm = Vm::<Instr>::new();
+        let mut debugger = vm.debugger(LibSite::with(0, lib.id()), |_: LibId| Some(&lib));
+        debugger.add_watchpoint(Reg::A(RegA::A16, Reg32::Reg0));
+
+        let DebugEvent::Halt(report) = debugger.step(&()) else {
+            panic!("program must halt");
+        };
+        assert_eq!(report.halt, HaltReason::EndOfCode);
+        assert_eq!(debugger.registers().a16(Reg32::Reg0), Some(5));
+    }
+
+    #[test]
+    fn call_stack() {
+        let code: Vec<Instr> = vec![
+            Instr::ControlFlow(ControlFlowOp::Routine(4)),
+            Instr::ControlFlow(ControlFlowOp::Ret),
+            Instr::ControlFlow(ControlFlowOp::Routine(8)),
+            Instr::ControlFlow(ControlFlowOp::Ret),
+            Instr::ControlFlow(ControlFlowOp::Ret),
+        ];
+        let lib = Lib::assemble(&code).unwrap();
+        let lib_id = lib.id();
+        let mut vm = Vm::<Instr>::new();
+        let mut debugger = vm.debugger(LibSite::with(0, lib_id), |_: LibId| Some(&lib));
+
+        assert_eq!(debugger.step(&()), DebugEvent::Step);
+        assert_eq!(debugger.site(), LibSite::with(4, lib_id));
+        assert_eq!(debugger.call_stack(), &[LibSite::with(3, lib_id)]);
+        assert_eq!(debugger.step(&()), DebugEvent::Step);
+        assert_eq!(debugger.site(), LibSite::with(8, lib_id));
+        assert_eq!(debugger.call_stack(), &[LibSite::with(3, lib_id), LibSite::with(7, lib_id)]);
+        assert_eq!(debugger.step(&()), DebugEvent::Step);
+        assert_eq!(debugger.site(), LibSite::with(7, lib_id));
+        assert_eq!(debugger.call_stack(), &[LibSite::with(3, lib_id)]);
+        assert_eq!(debugger.step(&()), DebugEvent::Step);
+        assert_eq!(debugger.site(), LibSite::with(3, lib_id));
+        assert!(debugger.call_stack().is_empty());
+    }
+
+    #[test]
+    fn unverified_lib() {
+        let callee =
+            Lib::assemble::<Instr>(&[Instr::ControlFlow(ControlFlowOp::Jmp(100))]).unwrap();
+        let code: Vec<Instr> = vec![
+            Instr::ControlFlow(ControlFlowOp::Test),
+            Instr::ControlFlow(ControlFlowOp::Call(LibSite::with(0, callee.id()))),
+        ];
+        let lib = Lib::assemble(&code).unwrap();
+        let resolver = |id: LibId| [&lib, &callee].into_iter().find(|lib| lib.id() == id);
+        let mut vm = Vm::<Instr>::new();
+        vm.verify_libs = true;
+        let mut debugger = vm.debugger(LibSite::with(0, lib.id()), resolver);
+
+        assert_eq!(debugger.step(&()), DebugEvent::Step);
+        let DebugEvent::Halt(report) = debugger.resume(&()) else {
+            panic!("program must halt");
+        };
+        assert_eq!(report.halt, HaltReason::Unverified(callee.id()));
+        assert_eq!(report.site, LibSite::with(0, callee.id()));
+        assert!(!report.status);
+    }
+
+    #[test]
+    fn preflight() {
+        let callee = Lib::assemble::<Instr>(&[Instr::ControlFlow(ControlFlowOp::Ret)]).unwrap();
+        let code: Vec<Instr> = vec![
+            Instr::ControlFlow(ControlFlowOp::Test),
+            Instr::ControlFlow(ControlFlowOp::Call(LibSite::with(0, callee.id()))),
+        ];
+        let lib = Lib::assemble(&code).unwrap();
+        let site = LibSite::with(0, lib.id());
+        let mut vm = Vm::<Instr>::new();
+        vm.lib_policy = LibPolicy::Preflight;
+        let mut debugger = vm.debugger(site, |id: LibId| (id == lib.id()).then_some(&lib));
+
+        let DebugEvent::Halt(report) = debugger.step(&()) else {
+            panic!("program must halt");
+        };
+        assert_eq!(report.halt, HaltReason::LibNotFound(callee.id()));
+        assert_eq!(report.site, site);
+        assert_eq!(report.complexity, 0);
+        assert!(!report.status);
+    }
+}
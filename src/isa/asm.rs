@@ -0,0 +1,1299 @@
+// Reference rust implementation of AluVM (arithmetic logic unit virtual machine).
+// To find more on AluVM please check <https://aluvm.org>
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2021-2024 by
+//     Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2021-2022 LNP/BP Standards Association. All rights reserved.
+// Copyright (C) 2023-2024 UBIDECO Labs,
+//     Institute for Distributed and Cognitive Computing, Switzerland.
+//     All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Runtime assembler reading AluVM assembly text.
+//!
+//! The assembler accepts the same syntax which is produced by the `Display` implementation of
+//! [`Instr`]: one instruction per line, consisting of a mnemonic, optional `.`-separated flags
+//! and a comma-separated list of operands, for instance `add.uc a16[1],a16[2]`. Text after `;`
+//! till the end of the line is a comment. Instructions which are not known to the core ISA are
+//! parsed with the [`FromStr`] implementation of the ISA extension type.
+//!
+//! Instead of raw code offsets, `jmp`, `jif` and `routine` may reference labels, which are
+//! resolved into offsets during the assembly:
+//!
+//! ```text
+//! loop:   inc     a16[0]
+//!         lt.u    a16[0],a16[1]
+//!         jif     :loop
+//!         ret
+//! ```
+
+#[cfg(feature = "alloc")]
+use alloc::borrow::ToOwned;
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
+#[cfg(feature = "alloc")]
+use alloc::collections::BTreeSet;
+#[cfg(feature = "alloc")]
+use alloc::string::{String, ToString};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter};
+use core::str::FromStr;
+
+#[cfg(feature = "curve25519")]
+use super::Curve25519Op;
+#[cfg(feature = "secp256k1")]
+use super::Secp256k1Op;
+use super::{
+    ArithmeticOp, BitwiseOp, BytesOp, CmpOp, ControlFlowOp, DigestOp, Flag, Instr, InstructionSet,
+    MoveOp, ParseFlagError, PutOp, ReservedOp,
+};
+use crate::data::{ByteStr, ByteStrError, LiteralParseError, MaybeNumber, Step};
+use crate::library::{AssemblerError, Lib, LibSite, LibSiteParseError};
+#[cfg(any(feature = "secp256k1", feature = "curve25519"))]
+use crate::reg::RegBlockAR;
+use crate::reg::{
+    NumericRegister, ParseRegError, Reg, Reg16, Reg32, Reg8, RegA, RegA2, RegAF, RegAFR, RegAR,
+    RegR, RegS,
+};
+
+/// Kinds of errors in the assembly text
+#[derive(Debug, Display)]
+#[display(doc_comments)]
+pub enum ParseErrorKind {
+    /// unknown instruction `{0}`.
+    UnknownInstr(String),
+
+    /// instruction `{mnemonic}` requires {expected} operand(s), while {actual} are given.
+    Arity {
+        /// Instruction mnemonic
+        mnemonic: String,
+        /// Number of operands required by the instruction
+        expected: usize,
+        /// Number of operands provided in the source
+        actual: usize,
+    },
+
+    /// instruction `{0}` does not take flags.
+    UnexpectedFlags(String),
+
+    /// operand `{0}` is not valid for this instruction.
+    InvalidOperand(String),
+
+    /// string literal is not terminated.
+    UnterminatedString,
+
+    /// {0}
+    Flag(ParseFlagError),
+
+    /// {0}
+    Register(ParseRegError),
+
+    /// {0}
+    Literal(LiteralParseError),
+
+    /// {0}
+    ByteStr(ByteStrError),
+
+    /// {0}
+    LibSite(LibSiteParseError),
+
+    /// label `{0}` is defined more than once.
+    DuplicateLabel(String),
+
+    /// label `{0}` is not defined.
+    UndefinedLabel(String),
+
+    /// label `{0}` points to the end of the code, where execution can't continue.
+    LabelAtEnd(String),
+}
+
+/// Error parsing assembly text, pointing to the line and column (both starting from 1) where the
+/// problem was found
+#[derive(Debug, Display)]
+#[cfg_attr(feature = "std", derive(Error))]
+#[display("{line}:{col}: {kind}")]
+pub struct ParseError {
+    /// Line number, starting from 1
+    pub line: usize,
+    /// Column number in characters, starting from 1
+    pub col: usize,
+    /// Description of the problem
+    pub kind: ParseErrorKind,
+}
+
+/// Errors assembling library from the assembly text
+#[derive(Debug, Display, From)]
+#[cfg_attr(feature = "std", derive(Error))]
+#[display(inner)]
+pub enum AsmError {
+    /// Error parsing the assembly text
+    #[from]
+    Parse(ParseError),
+
+    /// Error encoding parsed instructions into the library
+    #[from]
+    Assemble(AssemblerError),
+}
+
+/// Control flow operations which may target a label instead of a raw code offset
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Display)]
+pub enum Jump {
+    /// Unconditional jump, see [`ControlFlowOp::Jmp`]
+    #[display("jmp")]
+    Jmp,
+
+    /// Conditional jump, see [`ControlFlowOp::Jif`]
+    #[display("jif")]
+    Jif,
+
+    /// Subroutine call, see [`ControlFlowOp::Routine`]
+    #[display("routine")]
+    Routine,
+}
+
+impl Jump {
+    /// Constructs control flow operation jumping to the provided code offset
+    pub fn to_op(self, pos: u16) -> ControlFlowOp {
+        match self {
+            Jump::Jmp => ControlFlowOp::Jmp(pos),
+            Jump::Jif => ControlFlowOp::Jif(pos),
+            Jump::Routine => ControlFlowOp::Routine(pos),
+        }
+    }
+}
+
+/// Item of the assembly code: an instruction, a label definition or a jump to a label.
+///
+/// Labels are resolved into code offsets by [`Lib::assemble`].
+#[derive(Clone, PartialEq, Eq, Hash, Debug, From)]
+pub enum AsmItem<Isa: InstructionSet> {
+    /// Instruction encoded as is
+    #[from]
+    Instr(Isa),
+
+    /// Label pointing to the offset of the next instruction
+    Label(String),
+
+    /// Jump to the offset of a label
+    Jump(Jump, String),
+}
+
+impl<Isa: InstructionSet> Display for AsmItem<Isa> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            AsmItem::Instr(instr) => Display::fmt(instr, f),
+            AsmItem::Label(label) => write!(f, "{label}:"),
+            AsmItem::Jump(jump, label) => write!(f, "{:<8}:{label}", jump.to_string()),
+        }
+    }
+}
+
+/// Code which can be assembled into a library with [`Lib::assemble`]. Implemented by all
+/// instruction sets and by [`AsmItem`], which may also define and reference labels.
+pub trait AsmCode {
+    /// Instruction set of the code
+    type Isa: InstructionSet;
+
+    /// Returns instruction to encode, if any
+    fn instr(&self) -> Option<&Self::Isa>;
+
+    /// Returns label defined at the current code offset, if any
+    fn label(&self) -> Option<&str> { None }
+
+    /// Returns jump to a label which has to be resolved into a code offset, if any
+    fn jump(&self) -> Option<(Jump, &str)> { None }
+}
+
+impl<Isa: InstructionSet> AsmCode for Isa {
+    type Isa = Isa;
+
+    fn instr(&self) -> Option<&Isa> { Some(self) }
+}
+
+impl<Isa: InstructionSet> AsmCode for AsmItem<Isa> {
+    type Isa = Isa;
+
+    fn instr(&self) -> Option<&Isa> {
+        match self {
+            AsmItem::Instr(instr) => Some(instr),
+            _ => None,
+        }
+    }
+
+    fn label(&self) -> Option<&str> {
+        match self {
+            AsmItem::Label(label) => Some(label),
+            _ => None,
+        }
+    }
+
+    fn jump(&self) -> Option<(Jump, &str)> {
+        match self {
+            AsmItem::Jump(jump, label) => Some((*jump, label)),
+            _ => None,
+        }
+    }
+}
+
+/// Parses assembly text into a list of instructions and labels.
+///
+/// A label is defined with `name:` at the beginning of a line, optionally followed by an
+/// instruction, and is referenced by `jmp`, `jif` and `routine` as `:name`. Jumps may target only
+/// labels which are followed by an instruction.
+pub fn parse<Ext>(source: &str) -> Result<Vec<AsmItem<Instr<Ext>>>, ParseError>
+where Ext: InstructionSet + FromStr {
+    let mut code = Vec::new();
+    let mut labels = BTreeSet::new();
+    // Labels defined after the last parsed instruction
+    let mut trailing = BTreeSet::new();
+    let mut jumps = Vec::new();
+    for (no, line) in source.lines().enumerate() {
+        let Some(stmt) = Stmt::with(no + 1, line)? else {
+            continue;
+        };
+        if let Some(label) = stmt.label {
+            if !labels.insert(label) {
+                let kind = ParseErrorKind::DuplicateLabel(label.to_owned());
+                return Err(stmt.error(label, kind));
+            }
+            trailing.insert(label);
+            code.push(AsmItem::Label(label.to_owned()));
+        }
+        if stmt.mnemonic.is_empty() {
+            continue;
+        }
+        trailing.clear();
+        let item = stmt.item()?;
+        if let AsmItem::Jump(_, label) = &item {
+            let kind = ParseErrorKind::UndefinedLabel(label.clone());
+            jumps.push((label.clone(), stmt.error(stmt.ops[0], kind)));
+        }
+        code.push(item);
+    }
+    for (label, mut err) in jumps {
+        if trailing.contains(label.as_str()) {
+            err.kind = ParseErrorKind::LabelAtEnd(label);
+            return Err(err);
+        }
+        if !labels.contains(label.as_str()) {
+            return Err(err);
+        }
+    }
+    Ok(code)
+}
+
+/// Parses assembly text and assembles the instructions into a library with [`Lib::assemble`]
+pub fn assemble<Ext>(source: &str) -> Result<Lib, AsmError>
+where Ext: InstructionSet + FromStr {
+    let code = parse::<Ext>(source)?;
+    Ok(Lib::assemble(&code)?)
+}
+
+/// Returns byte offset of `sub` string slice within `line`
+fn offset(line: &str, sub: &str) -> usize { sub.as_ptr() as usize - line.as_ptr() as usize }
+
+/// Parses `u16` value written either as a decimal or as a `0x`-prefixed hexadecimal number
+fn parse_u16(s: &str) -> Result<u16, LiteralParseError> {
+    Ok(match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => u16::from_str_radix(hex, 16)?,
+        None => u16::from_str(s)?,
+    })
+}
+
+/// Returns whether the string is a valid label name: an identifier made of ASCII letters, digits
+/// and underscores, not starting with a digit
+fn is_label(s: &str) -> bool {
+    let mut chars = s.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Single instruction line split into the label, mnemonic, flags and operands
+struct Stmt<'s> {
+    line: usize,
+    text: &'s str,
+    label: Option<&'s str>,
+    code: &'s str,
+    mnemonic: &'s str,
+    name: &'s str,
+    flags: Option<&'s str>,
+    ops: Vec<&'s str>,
+}
+
+impl<'s> Stmt<'s> {
+    /// Splits source line into statement parts. Returns `None` for empty and comment-only lines.
+    fn with(line: usize, text: &'s str) -> Result<Option<Self>, ParseError> {
+        let mut quote = None;
+        let mut escape = false;
+        let mut end = text.len();
+        for (pos, c) in text.char_indices() {
+            match (quote, c) {
+                (Some(_), _) if escape => escape = false,
+                (Some(_), '\\') => escape = true,
+                (Some(_), '"') => quote = None,
+                (Some(_), _) => {}
+                (None, '"') => quote = Some(pos),
+                (None, ';') => {
+                    end = pos;
+                    break;
+                }
+                (None, _) => {}
+            }
+        }
+        if let Some(pos) = quote {
+            return Err(ParseError {
+                line,
+                col: text[..pos].chars().count() + 1,
+                kind: ParseErrorKind::UnterminatedString,
+            });
+        }
+
+        let code = text[..end].trim();
+        if code.is_empty() {
+            return Ok(None);
+        }
+        let first = code.split(char::is_whitespace).next().unwrap_or_default();
+        let label = first.strip_suffix(':').filter(|label| is_label(label));
+        let code = match label {
+            Some(_) => code[first.len()..].trim_start(),
+            None => code,
+        };
+        let (mnemonic, rest) = code.split_once(char::is_whitespace).unwrap_or((code, ""));
+        let (name, flags) = match mnemonic.split_once('.') {
+            Some((name, flags)) => (name, Some(flags)),
+            None => (mnemonic, None),
+        };
+
+        let mut ops = Vec::new();
+        let mut start = 0;
+        let mut quoted = false;
+        let mut escape = false;
+        for (pos, c) in rest.char_indices() {
+            match c {
+                _ if escape => escape = false,
+                '\\' if quoted => escape = true,
+                '"' => quoted = !quoted,
+                ',' if !quoted => {
+                    ops.push(rest[start..pos].trim());
+                    start = pos + 1;
+                }
+                _ => {}
+            }
+        }
+        let last = rest[start..].trim();
+        if !last.is_empty() || !ops.is_empty() {
+            ops.push(last);
+        }
+        // `Display` for step operations ends the operand list with a comma
+        if ops.len() > 1 && ops.last() == Some(&"") {
+            ops.pop();
+        }
+
+        Ok(Some(Stmt {
+            line,
+            text,
+            label,
+            code,
+            mnemonic,
+            name,
+            flags,
+            ops,
+        }))
+    }
+
+    fn error(&self, at: &str, kind: ParseErrorKind) -> ParseError {
+        ParseError {
+            line: self.line,
+            col: self.text[..offset(self.text, at)].chars().count() + 1,
+            kind,
+        }
+    }
+
+    fn invalid(&self, idx: usize) -> ParseError {
+        let op = self.ops[idx];
+        self.error(op, ParseErrorKind::InvalidOperand(op.to_owned()))
+    }
+
+    /// Checks that the statement has exactly `count` operands
+    fn arity(&self, count: usize) -> Result<(), ParseError> {
+        if self.ops.len() != count || self.ops.iter().any(|op| op.is_empty()) {
+            return Err(self.error(self.mnemonic, ParseErrorKind::Arity {
+                mnemonic: self.name.to_owned(),
+                expected: count,
+                actual: self.ops.len(),
+            }));
+        }
+        Ok(())
+    }
+
+    /// Checks that the statement has exactly `count` operands and no flags
+    fn plain(&self, count: usize) -> Result<(), ParseError> {
+        if self.flags.is_some() {
+            return Err(
+                self.error(self.mnemonic, ParseErrorKind::UnexpectedFlags(self.name.to_owned()))
+            );
+        }
+        self.arity(count)
+    }
+
+    fn flag<F: Flag>(&self) -> Result<F, ParseError> {
+        let flags = self.flags.unwrap_or_default();
+        F::from_str(flags).map_err(|err| self.error(self.mnemonic, ParseErrorKind::Flag(err)))
+    }
+
+    fn parse<T: FromStr>(
+        &self,
+        idx: usize,
+        kind: impl FnOnce(T::Err) -> ParseErrorKind,
+    ) -> Result<T, ParseError> {
+        let op = self.ops[idx];
+        T::from_str(op).map_err(|err| self.error(op, kind(err)))
+    }
+
+    fn reg(&self, idx: usize) -> Result<Reg, ParseError> {
+        self.parse(idx, ParseErrorKind::Register)
+    }
+
+    fn regs(&self, idx: usize) -> Result<RegS, ParseError> {
+        self.parse(idx, ParseErrorKind::Register)
+    }
+
+    fn a(&self, idx: usize) -> Result<(RegA, Reg32), ParseError> {
+        match self.reg(idx)? {
+            Reg::A(reg, index) => Ok((reg, index)),
+            _ => Err(self.invalid(idx)),
+        }
+    }
+
+    fn r(&self, idx: usize) -> Result<(RegR, Reg32), ParseError> {
+        match self.reg(idx)? {
+            Reg::R(reg, index) => Ok((reg, index)),
+            _ => Err(self.invalid(idx)),
+        }
+    }
+
+    fn a2(&self, idx: usize) -> Result<(RegA2, Reg32), ParseError> {
+        match self.reg(idx)? {
+            Reg::A(RegA::A8, index) => Ok((RegA2::A8, index)),
+            Reg::A(RegA::A16, index) => Ok((RegA2::A16, index)),
+            _ => Err(self.invalid(idx)),
+        }
+    }
+
+    fn af(&self, idx: usize) -> Result<(RegAF, Reg32), ParseError> {
+        match self.reg(idx)? {
+            Reg::A(reg, index) => Ok((RegAF::A(reg), index)),
+            Reg::F(reg, index) => Ok((RegAF::F(reg), index)),
+            _ => Err(self.invalid(idx)),
+        }
+    }
+
+    fn ar(&self, idx: usize) -> Result<(RegAR, Reg32), ParseError> {
+        match self.reg(idx)? {
+            Reg::A(reg, index) => Ok((RegAR::A(reg), index)),
+            Reg::R(reg, index) => Ok((RegAR::R(reg), index)),
+            _ => Err(self.invalid(idx)),
+        }
+    }
+
+    /// Parses operand which must be a register of a specific bit dimension, returning its index
+    fn fixed(&self, idx: usize, reg: impl Into<RegAFR>) -> Result<Reg32, ParseError> {
+        let (expected, parsed) = (reg.into(), self.reg(idx)?);
+        match parsed {
+            Reg::A(reg, index) if RegAFR::A(reg) == expected => Ok(index),
+            Reg::F(reg, index) if RegAFR::F(reg) == expected => Ok(index),
+            Reg::R(reg, index) if RegAFR::R(reg) == expected => Ok(index),
+            _ => Err(self.invalid(idx)),
+        }
+    }
+
+    fn idx16(&self, idx: usize, index: Reg32) -> Result<Reg16, ParseError> {
+        Reg16::try_from(index).map_err(|_| self.invalid(idx))
+    }
+
+    fn idx8(&self, idx: usize, index: Reg32) -> Result<Reg8, ParseError> {
+        Reg8::try_from(index).map_err(|_| self.invalid(idx))
+    }
+
+    fn offset(&self, idx: usize) -> Result<u16, ParseError> {
+        let op = self.ops[idx];
+        parse_u16(op).map_err(|err| self.error(op, ParseErrorKind::Literal(err)))
+    }
+
+    /// Parses number literal, converting it to the layout of the destination register
+    fn number(
+        &self,
+        idx: usize,
+        reg: impl NumericRegister,
+    ) -> Result<Box<MaybeNumber>, ParseError> {
+        let mut num: MaybeNumber = self.parse(idx, ParseErrorKind::Literal)?;
+        let layout = reg.layout();
+        let defined = num.is_some();
+        // float conversions may round the value, which is fine for a literal
+        if !num.reshape(layout) && (!layout.is_float() || defined && num.is_none()) {
+            return Err(self.invalid(idx));
+        }
+        Ok(Box::new(num))
+    }
+
+    fn bool(&self, idx: usize) -> Result<bool, ParseError> {
+        match self.ops[idx] {
+            "true" => Ok(true),
+            "false" => Ok(false),
+            _ => Err(self.invalid(idx)),
+        }
+    }
+
+    fn site(&self, idx: usize) -> Result<LibSite, ParseError> {
+        self.parse(idx, ParseErrorKind::LibSite)
+    }
+
+    fn item<Ext>(&self) -> Result<AsmItem<Instr<Ext>>, ParseError>
+    where Ext: InstructionSet + FromStr {
+        let jump = match self.name {
+            "jmp" => Jump::Jmp,
+            "jif" => Jump::Jif,
+            "routine" => Jump::Routine,
+            _ => return self.instr().map(AsmItem::Instr),
+        };
+        self.plain(1)?;
+        match self.ops[0].strip_prefix(':') {
+            Some(label) if is_label(label) => Ok(AsmItem::Jump(jump, label.to_owned())),
+            Some(_) => Err(self.invalid(0)),
+            None => Ok(AsmItem::Instr(Instr::ControlFlow(jump.to_op(self.offset(0)?)))),
+        }
+    }
+
+    fn instr<Ext>(&self) -> Result<Instr<Ext>, ParseError>
+    where Ext: InstructionSet + FromStr {
+        Ok(match self.name {
+            "fail" => {
+                self.plain(0)?;
+                Instr::ControlFlow(ControlFlowOp::Fail)
+            }
+            "test" => {
+                self.plain(0)?;
+                Instr::ControlFlow(ControlFlowOp::Test)
+            }
+            "ret" => {
+                self.plain(0)?;
+                Instr::ControlFlow(ControlFlowOp::Ret)
+            }
+            "nop" => {
+                self.plain(0)?;
+                Instr::Nop
+            }
+            "call" | "exec" => {
+                self.plain(1)?;
+                let site = self.site(0)?;
+                Instr::ControlFlow(match self.name {
+                    "call" => ControlFlowOp::Call(site),
+                    _ => ControlFlowOp::Exec(site),
+                })
+            }
+
+            "clr" => {
+                self.plain(1)?;
+                Instr::Put(match self.reg(0)? {
+                    Reg::A(reg, idx) => PutOp::ClrA(reg, idx),
+                    Reg::F(reg, idx) => PutOp::ClrF(reg, idx),
+                    Reg::R(reg, idx) => PutOp::ClrR(reg, idx),
+                    Reg::S(_) => return Err(self.invalid(0)),
+                })
+            }
+            "put" => {
+                self.plain(2)?;
+                match self.reg(0)? {
+                    Reg::A(reg, idx) => Instr::Put(PutOp::PutA(reg, idx, self.number(1, reg)?)),
+                    Reg::F(reg, idx) => Instr::Put(PutOp::PutF(reg, idx, self.number(1, reg)?)),
+                    Reg::R(reg, idx) => Instr::Put(PutOp::PutR(reg, idx, self.number(1, reg)?)),
+                    Reg::S(reg) => {
+                        let s: ByteStr = self.parse(1, ParseErrorKind::ByteStr)?;
+                        Instr::Bytes(BytesOp::Put(reg, Box::new(s), false))
+                    }
+                }
+            }
+            "putif" => {
+                self.plain(2)?;
+                Instr::Put(match self.reg(0)? {
+                    Reg::A(reg, idx) => PutOp::PutIfA(reg, idx, self.number(1, reg)?),
+                    Reg::R(reg, idx) => PutOp::PutIfR(reg, idx, self.number(1, reg)?),
+                    _ => return Err(self.invalid(0)),
+                })
+            }
+
+            "mov" | "dup" | "swp" => {
+                self.plain(2)?;
+                let op = match (self.name, self.reg(0)?, self.reg(1)?) {
+                    ("mov", Reg::S(src), Reg::S(dst)) => {
+                        return Ok(Instr::Bytes(BytesOp::Mov(src, dst)))
+                    }
+                    ("swp", Reg::S(src), Reg::S(dst)) => {
+                        return Ok(Instr::Bytes(BytesOp::Swp(src, dst)))
+                    }
+                    ("mov", Reg::A(r1, i1), Reg::A(r2, i2)) if r1 == r2 => MoveOp::MovA(r1, i1, i2),
+                    ("dup", Reg::A(r1, i1), Reg::A(r2, i2)) if r1 == r2 => MoveOp::DupA(r1, i1, i2),
+                    ("swp", Reg::A(r1, i1), Reg::A(r2, i2)) if r1 == r2 => MoveOp::SwpA(r1, i1, i2),
+                    ("mov", Reg::F(r1, i1), Reg::F(r2, i2)) if r1 == r2 => MoveOp::MovF(r1, i1, i2),
+                    ("dup", Reg::F(r1, i1), Reg::F(r2, i2)) if r1 == r2 => MoveOp::DupF(r1, i1, i2),
+                    ("swp", Reg::F(r1, i1), Reg::F(r2, i2)) if r1 == r2 => MoveOp::SwpF(r1, i1, i2),
+                    ("mov", Reg::R(r1, i1), Reg::R(r2, i2)) if r1 == r2 => MoveOp::MovR(r1, i1, i2),
+                    ("dup", Reg::R(r1, i1), Reg::R(r2, i2)) if r1 == r2 => MoveOp::DupR(r1, i1, i2),
+                    _ => return Err(self.invalid(1)),
+                };
+                Instr::Move(op)
+            }
+            "cpy" | "cnv" | "spy" => {
+                self.plain(2)?;
+                Instr::Move(match (self.name, self.reg(0)?, self.reg(1)?) {
+                    ("cpy", Reg::A(r1, i1), Reg::A(r2, i2)) => MoveOp::CpyA(r1, i1, r2, i2),
+                    ("cpy", Reg::R(r1, i1), Reg::R(r2, i2)) => MoveOp::CpyR(r1, i1, r2, i2),
+                    ("cnv", Reg::A(r1, i1), Reg::A(r2, i2)) => MoveOp::CnvA(r1, i1, r2, i2),
+                    ("cnv", Reg::F(r1, i1), Reg::F(r2, i2)) => MoveOp::CnvF(r1, i1, r2, i2),
+                    ("cnv", Reg::A(r1, i1), Reg::F(r2, i2)) => MoveOp::CnvAF(r1, i1, r2, i2),
+                    ("cnv", Reg::F(r1, i1), Reg::A(r2, i2)) => MoveOp::CnvFA(r1, i1, r2, i2),
+                    ("spy", Reg::A(r1, i1), Reg::R(r2, i2)) => MoveOp::SpyAR(r1, i1, r2, i2),
+                    _ => return Err(self.invalid(1)),
+                })
+            }
+
+            "gt" | "lt" => {
+                self.arity(2)?;
+                let gt = self.name == "gt";
+                Instr::Cmp(match (self.reg(0)?, self.reg(1)?) {
+                    (Reg::A(r1, i1), Reg::A(r2, i2)) if r1 == r2 => {
+                        let flag = self.flag()?;
+                        if gt {
+                            CmpOp::GtA(flag, r1, i1, i2)
+                        } else {
+                            CmpOp::LtA(flag, r1, i1, i2)
+                        }
+                    }
+                    (Reg::F(r1, i1), Reg::F(r2, i2)) if r1 == r2 => {
+                        let flag = self.flag()?;
+                        if gt {
+                            CmpOp::GtF(flag, r1, i1, i2)
+                        } else {
+                            CmpOp::LtF(flag, r1, i1, i2)
+                        }
+                    }
+                    (Reg::R(r1, i1), Reg::R(r2, i2)) if r1 == r2 => {
+                        self.plain(2)?;
+                        if gt {
+                            CmpOp::GtR(r1, i1, i2)
+                        } else {
+                            CmpOp::LtR(r1, i1, i2)
+                        }
+                    }
+                    _ => return Err(self.invalid(1)),
+                })
+            }
+            "eq" => {
+                self.arity(2)?;
+                match (self.reg(0)?, self.reg(1)?) {
+                    (Reg::A(r1, i1), Reg::A(r2, i2)) if r1 == r2 => {
+                        Instr::Cmp(CmpOp::EqA(self.flag()?, r1, i1, i2))
+                    }
+                    (Reg::F(r1, i1), Reg::F(r2, i2)) if r1 == r2 => {
+                        Instr::Cmp(CmpOp::EqF(self.flag()?, r1, i1, i2))
+                    }
+                    (Reg::R(r1, i1), Reg::R(r2, i2)) if r1 == r2 => {
+                        Instr::Cmp(CmpOp::EqR(self.flag()?, r1, i1, i2))
+                    }
+                    (Reg::S(s1), Reg::S(s2)) => {
+                        self.plain(2)?;
+                        Instr::Bytes(BytesOp::Eq(s1, s2))
+                    }
+                    _ => return Err(self.invalid(1)),
+                }
+            }
+            "ifz" | "ifn" => {
+                self.plain(1)?;
+                Instr::Cmp(match (self.name, self.reg(0)?) {
+                    ("ifz", Reg::A(reg, idx)) => CmpOp::IfZA(reg, idx),
+                    ("ifz", Reg::R(reg, idx)) => CmpOp::IfZR(reg, idx),
+                    ("ifn", Reg::A(reg, idx)) => CmpOp::IfNA(reg, idx),
+                    ("ifn", Reg::R(reg, idx)) => CmpOp::IfNR(reg, idx),
+                    _ => return Err(self.invalid(0)),
+                })
+            }
+            "st" => {
+                self.arity(1)?;
+                let flag = self.flag()?;
+                let (reg, idx) = self.a(0)?;
+                Instr::Cmp(CmpOp::St(flag, reg, self.idx8(0, idx)?))
+            }
+            "inv" => {
+                self.plain(1)?;
+                if self.ops[0] != "st0" {
+                    return Err(self.invalid(0));
+                }
+                Instr::Cmp(CmpOp::StInv)
+            }
+
+            "add" | "sub" | "mul" | "div" if self.flags.is_some() => {
+                self.arity(2)?;
+                Instr::Arithmetic(match (self.name, self.reg(0)?, self.reg(1)?) {
+                    ("add", Reg::A(r1, i1), Reg::A(r2, i2)) if r1 == r2 => {
+                        ArithmeticOp::AddA(self.flag()?, r1, i1, i2)
+                    }
+                    ("sub", Reg::A(r1, i1), Reg::A(r2, i2)) if r1 == r2 => {
+                        ArithmeticOp::SubA(self.flag()?, r1, i1, i2)
+                    }
+                    ("mul", Reg::A(r1, i1), Reg::A(r2, i2)) if r1 == r2 => {
+                        ArithmeticOp::MulA(self.flag()?, r1, i1, i2)
+                    }
+                    ("div", Reg::A(r1, i1), Reg::A(r2, i2)) if r1 == r2 => {
+                        ArithmeticOp::DivA(self.flag()?, r1, i1, i2)
+                    }
+                    ("add", Reg::F(r1, i1), Reg::F(r2, i2)) if r1 == r2 => {
+                        ArithmeticOp::AddF(self.flag()?, r1, i1, i2)
+                    }
+                    ("sub", Reg::F(r1, i1), Reg::F(r2, i2)) if r1 == r2 => {
+                        ArithmeticOp::SubF(self.flag()?, r1, i1, i2)
+                    }
+                    ("mul", Reg::F(r1, i1), Reg::F(r2, i2)) if r1 == r2 => {
+                        ArithmeticOp::MulF(self.flag()?, r1, i1, i2)
+                    }
+                    ("div", Reg::F(r1, i1), Reg::F(r2, i2)) if r1 == r2 => {
+                        ArithmeticOp::DivF(self.flag()?, r1, i1, i2)
+                    }
+                    _ => return Err(self.invalid(1)),
+                })
+            }
+            "inc" | "dec" => {
+                self.plain(1)?;
+                let (reg, idx) = self.a(0)?;
+                let step = if self.name == "inc" { 1 } else { -1 };
+                Instr::Arithmetic(ArithmeticOp::Stp(reg, idx, Step::with(step)))
+            }
+            "add" | "sub" => {
+                if self.ops.len() == 1 {
+                    self.plain(1)?;
+                } else {
+                    self.plain(2)?;
+                }
+                let (reg, idx) = self.a(0)?;
+                let val = match self.ops.len() {
+                    2 => self.parse::<u8>(1, |err| ParseErrorKind::Literal(err.into()))?,
+                    _ => 0,
+                };
+                let step = match self.name {
+                    "add" => i8::try_from(val).map_err(|_| self.invalid(1))?,
+                    _ => i8::try_from(-(val as i16)).map_err(|_| self.invalid(1))?,
+                };
+                Instr::Arithmetic(ArithmeticOp::Stp(reg, idx, Step::with(step)))
+            }
+            "rem" => {
+                self.plain(2)?;
+                let (r1, i1) = self.a(0)?;
+                let (r2, i2) = self.a(1)?;
+                Instr::Arithmetic(ArithmeticOp::Rem(r1, i1, r2, i2))
+            }
+            "neg" | "abs" => {
+                self.plain(1)?;
+                let (reg, idx) = self.af(0)?;
+                let idx = self.idx16(0, idx)?;
+                Instr::Arithmetic(match self.name {
+                    "neg" => ArithmeticOp::Neg(reg, idx),
+                    _ => ArithmeticOp::Abs(reg, idx),
+                })
+            }
+
+            "and" | "or" | "xor" => {
+                self.plain(3)?;
+                let (reg, i1) = self.ar(0)?;
+                let mut idx = [self.idx16(0, i1)?; 3];
+                for (no, idx) in idx.iter_mut().enumerate().skip(1) {
+                    match self.ar(no)? {
+                        (r, i) if r == reg => *idx = self.idx16(no, i)?,
+                        _ => return Err(self.invalid(no)),
+                    }
+                }
+                Instr::Bitwise(match self.name {
+                    "and" => BitwiseOp::And(reg, idx[0], idx[1], idx[2]),
+                    "or" => BitwiseOp::Or(reg, idx[0], idx[1], idx[2]),
+                    _ => BitwiseOp::Xor(reg, idx[0], idx[1], idx[2]),
+                })
+            }
+            "not" => {
+                self.plain(1)?;
+                let (reg, idx) = self.ar(0)?;
+                Instr::Bitwise(BitwiseOp::Not(reg, self.idx16(0, idx)?))
+            }
+            "shl" | "scl" | "scr" => {
+                self.plain(2)?;
+                let (shift, i1) = self.a2(0)?;
+                let (reg, i2) = self.ar(1)?;
+                Instr::Bitwise(match self.name {
+                    "shl" => BitwiseOp::Shl(shift, i1, reg, i2),
+                    "scl" => BitwiseOp::Scl(shift, i1, reg, i2),
+                    _ => BitwiseOp::Scr(shift, i1, reg, i2),
+                })
+            }
+            "shr" if self.flags.is_some() => {
+                self.arity(2)?;
+                let (shift, i1) = self.a2(0)?;
+                let (reg, i2) = self.a(1)?;
+                Instr::Bitwise(BitwiseOp::ShrA(self.flag()?, shift, self.idx16(0, i1)?, reg, i2))
+            }
+            "shr" => {
+                self.plain(2)?;
+                let (shift, i1) = self.a2(0)?;
+                let (reg, i2) = self.r(1)?;
+                Instr::Bitwise(BitwiseOp::ShrR(shift, i1, reg, i2))
+            }
+            "rev" if self.ops.len() == 2 => {
+                self.plain(2)?;
+                Instr::Bytes(BytesOp::Rev(self.regs(0)?, self.regs(1)?))
+            }
+            "rev" => {
+                self.plain(1)?;
+                Instr::Bitwise(match self.reg(0)? {
+                    Reg::A(reg, idx) => BitwiseOp::RevA(reg, idx),
+                    Reg::R(reg, idx) => BitwiseOp::RevR(reg, idx),
+                    _ => return Err(self.invalid(0)),
+                })
+            }
+
+            "fill" => {
+                self.arity(4)?;
+                Instr::Bytes(BytesOp::Fill(
+                    self.regs(0)?,
+                    self.fixed(1, RegA::A16)?,
+                    self.fixed(2, RegA::A16)?,
+                    self.fixed(3, RegA::A8)?,
+                    self.flag()?,
+                ))
+            }
+            "len" => {
+                self.plain(2)?;
+                let (reg, idx) = self.a(1)?;
+                Instr::Bytes(BytesOp::Len(self.regs(0)?, reg, idx))
+            }
+            "cnt" => {
+                self.plain(3)?;
+                let i1 = self.fixed(1, RegA::A8)?;
+                let i2 = self.fixed(2, RegA::A16)?;
+                Instr::Bytes(BytesOp::Cnt(self.regs(0)?, self.idx16(1, i1)?, self.idx16(2, i2)?))
+            }
+            "con" => {
+                self.plain(5)?;
+                Instr::Bytes(BytesOp::Con(
+                    self.regs(0)?,
+                    self.regs(1)?,
+                    self.fixed(2, RegA::A16)?,
+                    self.fixed(3, RegA::A16)?,
+                    self.fixed(4, RegA::A16)?,
+                ))
+            }
+            "find" => {
+                self.plain(3)?;
+                if self.fixed(0, RegA::A16)? != Reg32::Reg0 {
+                    return Err(self.invalid(0));
+                }
+                Instr::Bytes(BytesOp::Find(self.regs(1)?, self.regs(2)?))
+            }
+            "extr" | "inj" => {
+                self.plain(3)?;
+                let (reg, i1) = self.ar(1)?;
+                let i2 = match self.name {
+                    "extr" => self.fixed(2, RegA::A16)?,
+                    _ => match self.ar(2)? {
+                        (r, i) if r == reg => i,
+                        _ => return Err(self.invalid(2)),
+                    },
+                };
+                let (i1, i2) = (self.idx16(1, i1)?, self.idx16(2, i2)?);
+                Instr::Bytes(match self.name {
+                    "extr" => BytesOp::Extr(self.regs(0)?, reg, i1, i2),
+                    _ => BytesOp::Inj(self.regs(0)?, reg, i1, i2),
+                })
+            }
+            "join" => {
+                self.plain(3)?;
+                Instr::Bytes(BytesOp::Join(self.regs(0)?, self.regs(1)?, self.regs(2)?))
+            }
+            "splt" => {
+                self.arity(4)?;
+                Instr::Bytes(BytesOp::Splt(
+                    self.flag()?,
+                    self.fixed(1, RegA::A16)?,
+                    self.regs(0)?,
+                    self.regs(2)?,
+                    self.regs(3)?,
+                ))
+            }
+            "ins" => {
+                self.arity(3)?;
+                Instr::Bytes(BytesOp::Ins(
+                    self.flag()?,
+                    self.fixed(1, RegA::A16)?,
+                    self.regs(0)?,
+                    self.regs(2)?,
+                ))
+            }
+            "del" => {
+                self.arity(6)?;
+                let (r1, i1) = self.a2(2)?;
+                let (r2, i2) = self.a2(3)?;
+                Instr::Bytes(BytesOp::Del(
+                    self.flag()?,
+                    r1,
+                    i1,
+                    r2,
+                    i2,
+                    self.bool(4)?,
+                    self.bool(5)?,
+                    self.regs(0)?,
+                    self.regs(1)?,
+                ))
+            }
+
+            "ripemd" | "sha2" | "blake3" => {
+                self.plain(2)?;
+                let src = self.regs(0)?;
+                Instr::Digest(match (self.name, self.r(1)?) {
+                    ("ripemd", (RegR::R160, idx)) => DigestOp::Ripemd(src, self.idx16(1, idx)?),
+                    ("sha2", (RegR::R256, idx)) => DigestOp::Sha256(src, self.idx16(1, idx)?),
+                    ("sha2", (RegR::R512, idx)) => DigestOp::Sha512(src, self.idx16(1, idx)?),
+                    ("blake3", (RegR::R256, idx)) => DigestOp::Blake3(src, self.idx16(1, idx)?),
+                    _ => return Err(self.invalid(1)),
+                })
+            }
+
+            #[cfg(feature = "secp256k1")]
+            "secpgen" => {
+                self.plain(2)?;
+                let src = self.fixed(0, RegR::R256)?;
+                let dst = self.fixed(1, RegR::R512)?;
+                Instr::Secp256k1(Secp256k1Op::Gen(src, self.idx8(1, dst)?))
+            }
+            #[cfg(feature = "secp256k1")]
+            "secpmul" => {
+                self.plain(3)?;
+                let (block, scalar) = self.scalar(0)?;
+                let src = self.fixed(1, RegR::R512)?;
+                let dst = self.fixed(2, RegR::R512)?;
+                Instr::Secp256k1(Secp256k1Op::Mul(block, scalar, src, dst))
+            }
+            #[cfg(feature = "secp256k1")]
+            "secpadd" | "secpneg" => {
+                self.plain(2)?;
+                let src = self.fixed(0, RegR::R512)?;
+                let dst = self.fixed(1, RegR::R512)?;
+                let dst = self.idx8(1, dst)?;
+                Instr::Secp256k1(match self.name {
+                    "secpadd" => Secp256k1Op::Add(src, dst),
+                    _ => Secp256k1Op::Neg(src, dst),
+                })
+            }
+
+            #[cfg(feature = "curve25519")]
+            "edgen" | "edneg" => {
+                self.plain(2)?;
+                let src = self.fixed(0, RegR::R256)?;
+                let dst = self.fixed(1, RegR::R256)?;
+                let dst = self.idx8(1, dst)?;
+                Instr::Curve25519(match self.name {
+                    "edgen" => Curve25519Op::Gen(src, dst),
+                    _ => Curve25519Op::Neg(src, dst),
+                })
+            }
+            #[cfg(feature = "curve25519")]
+            "edmul" => {
+                self.plain(3)?;
+                let (block, scalar) = self.scalar(0)?;
+                let src = self.fixed(1, RegR::R256)?;
+                let dst = self.fixed(2, RegR::R256)?;
+                Instr::Curve25519(Curve25519Op::Mul(block, scalar, src, dst))
+            }
+            #[cfg(feature = "curve25519")]
+            "edadd" => {
+                self.plain(4)?;
+                Instr::Curve25519(Curve25519Op::Add(
+                    self.fixed(0, RegR::R256)?,
+                    self.fixed(1, RegR::R256)?,
+                    self.fixed(2, RegR::R256)?,
+                    self.bool(3)?,
+                ))
+            }
+
+            name if name.starts_with("rsrv:") => {
+                self.plain(0)?;
+                let op = ReservedOp::from_str(self.mnemonic)
+                    .map_err(|err| self.error(self.mnemonic, ParseErrorKind::Literal(err)))?;
+                Instr::ReservedInstruction(op)
+            }
+
+            _ => Instr::ExtensionCodes(Ext::from_str(self.code).map_err(|_| {
+                self.error(self.mnemonic, ParseErrorKind::UnknownInstr(self.name.to_owned()))
+            })?),
+        })
+    }
+
+    /// Parses scalar operand of elliptic curve multiplication, which is either `a256` or `r256`
+    /// register
+    #[cfg(any(feature = "secp256k1", feature = "curve25519"))]
+    fn scalar(&self, idx: usize) -> Result<(RegBlockAR, Reg32), ParseError> {
+        match self.reg(idx)? {
+            Reg::A(RegA::A256, index) => Ok((RegBlockAR::A, index)),
+            Reg::R(RegR::R256, index) => Ok((RegBlockAR::R, index)),
+            _ => Err(self.invalid(idx)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use amplify::num::apfloat::{ieee, Float};
+
+    use super::*;
+    use crate::isa::{
+        DeleteFlag, ExtendFlag, FloatEqFlag, InsertFlag, IntFlags, MergeFlag, NoneEqFlag,
+        RoundingFlag, SignFlag, SplitFlag,
+    };
+    use crate::library::LibId;
+    use crate::reg::RegF;
+
+    fn s(idx: u8) -> RegS { RegS::from(idx) }
+
+    fn program() -> Vec<Instr> {
+        let lib = LibId::with("ALU", b"", b"", &none!());
+        let half = ieee::Single::from_bits(0x3F00_0000u32.into());
+        #[allow(unused_mut)]
+        let mut code = vec![
+            Instr::ControlFlow(ControlFlowOp::Test),
+            Instr::ControlFlow(ControlFlowOp::Jif(0x10)),
+            Instr::ControlFlow(ControlFlowOp::Routine(3)),
+            Instr::ControlFlow(ControlFlowOp::Call(LibSite::with(0x10, lib))),
+            Instr::ControlFlow(ControlFlowOp::Exec(LibSite::with(0, lib))),
+            Instr::Put(PutOp::ClrF(RegF::F32, Reg32::Reg7)),
+            Instr::Put(PutOp::PutA(RegA::A64, Reg32::Reg1, Box::new(0xDEAD_BEEFu64.into()))),
+            Instr::Put(PutOp::PutF(RegF::F32, Reg32::Reg3, Box::new(half.into()))),
+            Instr::Put(PutOp::PutIfR(RegR::R128, Reg32::Reg2, Box::new(7u128.into()))),
+            Instr::Bytes(BytesOp::Put(s(1), Box::new(ByteStr::with("a, \"b\"; c")), false)),
+            Instr::Move(MoveOp::SwpF(RegF::F64, Reg32::Reg1, Reg32::Reg31)),
+            Instr::Move(MoveOp::CnvFA(RegF::F16, Reg32::Reg1, RegA::A8, Reg32::Reg2)),
+            Instr::Move(MoveOp::SpyAR(RegA::A128, Reg32::Reg1, RegR::R128, Reg32::Reg2)),
+            Instr::Bytes(BytesOp::Mov(s(1), s(2))),
+            Instr::Cmp(CmpOp::GtA(SignFlag::Signed, RegA::A8, Reg32::Reg1, Reg32::Reg2)),
+            Instr::Cmp(CmpOp::LtF(FloatEqFlag::Exact, RegF::F32, Reg32::Reg1, Reg32::Reg2)),
+            Instr::Cmp(CmpOp::GtR(RegR::R256, Reg32::Reg1, Reg32::Reg2)),
+            Instr::Cmp(CmpOp::EqR(NoneEqFlag::NonEqual, RegR::R160, Reg32::Reg3, Reg32::Reg4)),
+            Instr::Cmp(CmpOp::IfNA(RegA::A16, Reg32::Reg5)),
+            Instr::Cmp(CmpOp::St(MergeFlag::Or, RegA::A8, Reg8::Reg7)),
+            Instr::Cmp(CmpOp::StInv),
+            Instr::Bytes(BytesOp::Eq(s(3), s(4))),
+            Instr::Arithmetic(ArithmeticOp::AddA(
+                IntFlags::unsigned_checked(),
+                RegA::A16,
+                Reg32::Reg1,
+                Reg32::Reg2,
+            )),
+            Instr::Arithmetic(ArithmeticOp::DivF(
+                RoundingFlag::Ceil,
+                RegF::F128,
+                Reg32::Reg1,
+                Reg32::Reg2,
+            )),
+            Instr::Arithmetic(ArithmeticOp::Stp(RegA::A32, Reg32::Reg0, Step::with(1))),
+            Instr::Arithmetic(ArithmeticOp::Stp(RegA::A32, Reg32::Reg0, Step::with(-1))),
+            Instr::Arithmetic(ArithmeticOp::Stp(RegA::A32, Reg32::Reg0, Step::with(0))),
+            Instr::Arithmetic(ArithmeticOp::Stp(RegA::A32, Reg32::Reg0, Step::with(127))),
+            Instr::Arithmetic(ArithmeticOp::Stp(RegA::A32, Reg32::Reg0, Step::with(-128))),
+            Instr::Arithmetic(ArithmeticOp::Abs(RegAF::F(RegF::F16B), Reg16::Reg15)),
+            Instr::Bitwise(BitwiseOp::Xor(
+                RegAR::R(RegR::R512),
+                Reg16::Reg1,
+                Reg16::Reg2,
+                Reg16::Reg3,
+            )),
+            Instr::Bitwise(BitwiseOp::Scr(
+                RegA2::A16,
+                Reg32::Reg1,
+                RegAR::A(RegA::A64),
+                Reg32::Reg2,
+            )),
+            Instr::Bitwise(BitwiseOp::ShrA(
+                SignFlag::Signed,
+                RegA2::A8,
+                Reg16::Reg1,
+                RegA::A32,
+                Reg32::Reg20,
+            )),
+            Instr::Bitwise(BitwiseOp::ShrR(RegA2::A8, Reg32::Reg1, RegR::R1024, Reg32::Reg2)),
+            Instr::Bitwise(BitwiseOp::RevR(RegR::R8192, Reg32::Reg2)),
+            Instr::Bytes(BytesOp::Fill(
+                s(1),
+                Reg32::Reg1,
+                Reg32::Reg2,
+                Reg32::Reg3,
+                ExtendFlag::Fail,
+            )),
+            Instr::Bytes(BytesOp::Cnt(s(1), Reg16::Reg1, Reg16::Reg2)),
+            Instr::Bytes(BytesOp::Con(s(1), s(2), Reg32::Reg3, Reg32::Reg4, Reg32::Reg5)),
+            Instr::Bytes(BytesOp::Find(s(1), s(2))),
+            Instr::Bytes(BytesOp::Extr(s(1), RegAR::A(RegA::A8), Reg16::Reg1, Reg16::Reg2)),
+            Instr::Bytes(BytesOp::Inj(s(1), RegAR::R(RegR::R128), Reg16::Reg1, Reg16::Reg2)),
+            Instr::Bytes(BytesOp::Splt(SplitFlag::NoneNone, Reg32::Reg1, s(2), s(3), s(4))),
+            Instr::Bytes(BytesOp::Ins(InsertFlag::Extend, Reg32::Reg1, s(2), s(3))),
+            Instr::Bytes(BytesOp::Del(
+                DeleteFlag::Extend,
+                RegA2::A8,
+                Reg32::Reg1,
+                RegA2::A16,
+                Reg32::Reg2,
+                true,
+                false,
+                s(3),
+                s(4),
+            )),
+            Instr::Bytes(BytesOp::Rev(s(1), s(15))),
+            Instr::Digest(DigestOp::Sha512(s(1), Reg16::Reg2)),
+            Instr::Digest(DigestOp::Ripemd(s(1), Reg16::Reg2)),
+            Instr::ReservedInstruction(ReservedOp(0x7A)),
+            Instr::Nop,
+        ];
+        #[cfg(feature = "secp256k1")]
+        code.extend([
+            Instr::Secp256k1(Secp256k1Op::Gen(Reg32::Reg1, Reg8::Reg2)),
+            Instr::Secp256k1(Secp256k1Op::Mul(
+                RegBlockAR::A,
+                Reg32::Reg1,
+                Reg32::Reg2,
+                Reg32::Reg3,
+            )),
+            Instr::Secp256k1(Secp256k1Op::Neg(Reg32::Reg1, Reg8::Reg2)),
+        ]);
+        #[cfg(feature = "curve25519")]
+        code.extend([
+            Instr::Curve25519(Curve25519Op::Mul(
+                RegBlockAR::R,
+                Reg32::Reg1,
+                Reg32::Reg2,
+                Reg32::Reg3,
+            )),
+            Instr::Curve25519(Curve25519Op::Add(Reg32::Reg1, Reg32::Reg2, Reg32::Reg3, true)),
+            Instr::Curve25519(Curve25519Op::Neg(Reg32::Reg1, Reg8::Reg2)),
+        ]);
+        code.push(Instr::ControlFlow(ControlFlowOp::Ret));
+        code
+    }
+
+    #[test]
+    fn roundtrip() {
+        let code = program();
+        let source = code.iter().map(|instr| format!("{instr}\n")).collect::<String>();
+        let items = code.iter().cloned().map(AsmItem::Instr).collect::<Vec<_>>();
+        assert_eq!(parse::<ReservedOp>(&source).unwrap(), items);
+
+        let lib = assemble::<ReservedOp>(&source).unwrap();
+        assert_eq!(lib, Lib::assemble(&code).unwrap());
+    }
+
+    #[test]
+    fn comments() {
+        let source = "; program header\n\n  put s16[1],\"a;b\" ; comment\n\tret;\n";
+        let code = parse::<ReservedOp>(source).unwrap();
+        assert_eq!(code, vec![
+            AsmItem::Instr(Instr::Bytes(BytesOp::Put(s(1), Box::new(ByteStr::with("a;b")), false))),
+            AsmItem::Instr(Instr::ControlFlow(ControlFlowOp::Ret)),
+        ]);
+    }
+
+    #[test]
+    fn labels() {
+        let source = "\
+            routine :sub\n\
+            jmp     :end\n\
+            sub:\n\
+            loop:   inc a16[0] ; counter\n\
+                    jif :loop\n\
+                    ret\n\
+            end:    ret\n";
+        let code = parse::<ReservedOp>(source).unwrap();
+        assert_eq!(code[0], AsmItem::Jump(Jump::Routine, s!("sub")));
+        assert_eq!(code[2], AsmItem::Label(s!("sub")));
+        assert_eq!(code[3], AsmItem::Label(s!("loop")));
+        assert_eq!(code.len(), 9);
+        let text = code.iter().map(|item| format!("{item}\n")).collect::<String>();
+        assert_eq!(parse::<ReservedOp>(&text).unwrap(), code);
+
+        let inc = Instr::Arithmetic(ArithmeticOp::Stp(RegA::A16, Reg32::Reg0, Step::with(1)));
+        let size = |code: &[Instr]| Lib::assemble(code).unwrap().code_segment().len() as u16;
+        let sub = size(&vec![Instr::ControlFlow(ControlFlowOp::Jmp(0)); 2]);
+        let end = sub
+            + size(&[
+                inc.clone(),
+                Instr::ControlFlow(ControlFlowOp::Jif(0)),
+                Instr::ControlFlow(ControlFlowOp::Ret),
+            ]);
+        let resolved = vec![
+            Instr::ControlFlow(ControlFlowOp::Routine(sub)),
+            Instr::ControlFlow(ControlFlowOp::Jmp(end)),
+            inc,
+            Instr::ControlFlow(ControlFlowOp::Jif(sub)),
+            Instr::ControlFlow(ControlFlowOp::Ret),
+            Instr::ControlFlow(ControlFlowOp::Ret),
+        ];
+        let lib = assemble::<ReservedOp>(source).unwrap();
+        assert_eq!(lib, Lib::assemble(&resolved).unwrap());
+        assert_eq!(lib.disassemble::<Instr>().unwrap(), resolved);
+
+        let jump = AsmItem::<Instr>::Jump(Jump::Jmp, s!("start"));
+        let err = Lib::assemble(core::slice::from_ref(&jump)).unwrap_err();
+        assert_eq!(err, AssemblerError::UndefinedLabel(0));
+        let label = AsmItem::Label(s!("start"));
+        let err = Lib::assemble(&[label.clone(), jump.clone(), label]).unwrap_err();
+        assert_eq!(err, AssemblerError::DuplicateLabel(2));
+        let err = Lib::assemble(&[jump, AsmItem::Label(s!("start"))]).unwrap_err();
+        assert_eq!(err, AssemblerError::LabelAtEnd(0));
+    }
+
+    #[test]
+    fn errors() {
+        let err = |source: &str| {
+            let err = parse::<ReservedOp>(source).unwrap_err();
+            (err.line, err.col, err.to_string())
+        };
+        assert_eq!(err("ret\n  jump 0x10"), (2, 3, s!("2:3: unknown instruction `jump`.")));
+        assert_eq!(
+            err("add.uc a16[1]"),
+            (1, 1, s!("1:1: instruction `add` requires 2 operand(s), while 1 are given."))
+        );
+        assert_eq!(
+            err("mov a16[1], a32[2]"),
+            (1, 13, s!("1:13: operand `a32[2]` is not valid for this instruction."))
+        );
+        assert_eq!(err("clr a16[32]"), (1, 5, s!("1:5: `a16[32]` is not a valid register")));
+        assert_eq!(err("gt a8[1],a8[2]").1, 1);
+        assert_eq!(err("ret.u").2, s!("1:1: instruction `ret` does not take flags."));
+        assert_eq!(err("put s16[1],\"abc").2, s!("1:12: string literal is not terminated."));
+        assert_eq!(err("a: ret\n  jmp :c\nb: ret"), (2, 7, s!("2:7: label `c` is not defined.")));
+        assert_eq!(
+            err("a: ret\n  jif :a\na: ret").2,
+            s!("3:1: label `a` is defined more than once.")
+        );
+        assert_eq!(
+            err("jmp :b\nb:\n; end").2,
+            s!("1:5: label `b` points to the end of the code, where execution can't continue.")
+        );
+        assert_eq!(err("jmp :1a").2, s!("1:5: operand `:1a` is not valid for this instruction."));
+    }
+}
@@ -80,6 +80,11 @@ pub trait Bytecode {
     #[inline]
     fn call_site(&self) -> Option<LibSite> { None }
 
+    /// If the instruction jumps to an offset within the code segment of the same library,
+    /// returns the offset.
+    #[inline]
+    fn jump_target(&self) -> Option<u16> { None }
+
     /// Writes the instruction as bytecode
     fn encode<W>(&self, writer: &mut W) -> Result<(), BytecodeError>
     where W: Write {
@@ -144,6 +149,14 @@ where Extension: InstructionSet
         }
     }
 
+    fn jump_target(&self) -> Option<u16> {
+        match self {
+            Instr::ControlFlow(instr) => instr.jump_target(),
+            Instr::ExtensionCodes(instr) => instr.jump_target(),
+            _ => None,
+        }
+    }
+
     fn encode_args<W>(&self, writer: &mut W) -> Result<(), BytecodeError>
     where W: Write {
         match self {
@@ -169,42 +182,37 @@ where Extension: InstructionSet
     where R: Read {
         let instr = reader.peek_u8()?;
         Ok(match instr {
-            instr if ControlFlowOp::instr_range().contains(&instr) => {
-                Instr::ControlFlow(ControlFlowOp::decode(reader)?)
-            }
-            instr if PutOp::instr_range().contains(&instr) => Instr::Put(PutOp::decode(reader)?),
-            instr if MoveOp::instr_range().contains(&instr) => Instr::Move(MoveOp::decode(reader)?),
-            instr if CmpOp::instr_range().contains(&instr) => Instr::Cmp(CmpOp::decode(reader)?),
-            instr if ArithmeticOp::instr_range().contains(&instr) => {
-                Instr::Arithmetic(ArithmeticOp::decode(reader)?)
-            }
-            instr if BitwiseOp::instr_range().contains(&instr) => {
-                Instr::Bitwise(BitwiseOp::decode(reader)?)
-            }
-            instr if BytesOp::instr_range().contains(&instr) => {
-                Instr::Bytes(BytesOp::decode(reader)?)
-            }
-            instr if DigestOp::instr_range().contains(&instr) => {
-                Instr::Digest(DigestOp::decode(reader)?)
-            }
+            INSTR_FAIL..=INSTR_RET => Instr::ControlFlow(ControlFlowOp::decode(reader)?),
+            INSTR_CLRA..=INSTR_PUTIFR => Instr::Put(PutOp::decode(reader)?),
+            INSTR_MOV..=INSTR_CFA => Instr::Move(MoveOp::decode(reader)?),
+            INSTR_LGT..=INSTR_STINV => Instr::Cmp(CmpOp::decode(reader)?),
+            INSTR_ADD..=INSTR_REM => Instr::Arithmetic(ArithmeticOp::decode(reader)?),
+            INSTR_AND..=INSTR_REVR => Instr::Bitwise(BitwiseOp::decode(reader)?),
+            INSTR_PUT..=INSTR_REV => Instr::Bytes(BytesOp::decode(reader)?),
+            INSTR_RIPEMD..=INSTR_SHA512 => Instr::Digest(DigestOp::decode(reader)?),
             #[cfg(feature = "secp256k1")]
-            instr if Secp256k1Op::instr_range().contains(&instr) => {
-                Instr::Secp256k1(Secp256k1Op::decode(reader)?)
-            }
+            INSTR_SECP_GEN..=INSTR_SECP_NEG => Instr::Secp256k1(Secp256k1Op::decode(reader)?),
             #[cfg(feature = "curve25519")]
-            instr if Curve25519Op::instr_range().contains(&instr) => {
-                Instr::Curve25519(Curve25519Op::decode(reader)?)
-            }
+            INSTR_ED_GEN..=INSTR_ED_NEG => Instr::Curve25519(Curve25519Op::decode(reader)?),
             INSTR_RESV_FROM..=INSTR_RESV_TO => {
                 Instr::ReservedInstruction(ReservedOp::decode(reader)?)
             }
-            INSTR_NOP => Instr::Nop,
+            INSTR_NOP => {
+                reader.read_u8()?;
+                Instr::Nop
+            }
             INSTR_ISAE_FROM..=INSTR_ISAE_TO => Instr::ExtensionCodes(Extension::decode(reader)?),
-            x => unreachable!("unable to classify instruction {:#010b}", x),
         })
     }
 }
 
+/// Byte length of the `routine` instruction: the opcode followed by the 16-bit code offset.
+pub(crate) const ROUTINE_LEN: u16 = 3;
+
+/// Byte length of the `call` instruction: the opcode followed by the 16-bit code offset and the
+/// 8-bit index of the library in the libs segment.
+pub(crate) const CALL_LEN: u16 = 4;
+
 impl Bytecode for ControlFlowOp {
     #[inline]
     fn instr_range() -> RangeInclusive<u8> { INSTR_FAIL..=INSTR_RET }
@@ -230,6 +238,16 @@ impl Bytecode for ControlFlowOp {
         }
     }
 
+    #[inline]
+    fn jump_target(&self) -> Option<u16> {
+        match self {
+            ControlFlowOp::Jmp(pos) | ControlFlowOp::Jif(pos) | ControlFlowOp::Routine(pos) => {
+                Some(*pos)
+            }
+            _ => None,
+        }
+    }
+
     fn encode_args<W>(&self, writer: &mut W) -> Result<(), BytecodeError>
     where W: Write {
         match self {
@@ -0,0 +1,106 @@
+// Reference rust implementation of AluVM (arithmetic logic unit virtual machine).
+// To find more on AluVM please check <https://aluvm.org>
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2021-2024 by
+//     Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2021-2022 LNP/BP Standards Association. All rights reserved.
+// Copyright (C) 2023-2024 UBIDECO Labs,
+//     Institute for Distributed and Cognitive Computing, Switzerland.
+//     All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Complexity (cost) schedules for the instruction execution
+
+use super::InstructionSet;
+use crate::data::ByteStr;
+use crate::reg::{CoreRegs, NumericRegister, Reg};
+
+/// Schedule of computational costs for the executed instructions.
+///
+/// The cost of each instruction is accumulated into `ca0` register and checked against the
+/// complexity limit (`cl0`). The cost is computed over the register state before the instruction
+/// is executed, so it may depend on the instruction operands (like the length of the processed
+/// byte strings).
+pub trait CostModel<Isa>
+where Isa: InstructionSet
+{
+    /// Returns cost of executing the instruction with the given register state.
+    fn cost(&self, instr: &Isa, regs: &CoreRegs) -> u64;
+}
+
+/// Default cost schedule, using complexity hardcoded in the instruction set
+/// ([`InstructionSet::complexity`]).
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub struct DefaultCost;
+
+impl<Isa> CostModel<Isa> for DefaultCost
+where Isa: InstructionSet
+{
+    #[inline]
+    fn cost(&self, instr: &Isa, _: &CoreRegs) -> u64 { instr.complexity() }
+}
+
+/// Cost schedule adding to the instruction complexity a cost proportional to the amount of the
+/// data the instruction reads: the width of the source numeric registers and the actual length of
+/// the source byte strings.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct SizedCost {
+    /// Cost charged per each byte of the instruction source data
+    pub per_byte: u64,
+}
+
+impl<Isa> CostModel<Isa> for SizedCost
+where Isa: InstructionSet
+{
+    fn cost(&self, instr: &Isa, regs: &CoreRegs) -> u64 {
+        // Instructions may report the same source register more than once, but it must be charged
+        // only once. Instructions read just a few registers, so they are de-duplicated on the
+        // stack, falling back to the register set for the instructions reading more of them.
+        let mut seen = [None::<Reg>; 8];
+        let mut count = 0usize;
+        let mut overflow = false;
+        let mut bytes = 0u64;
+        instr.for_each_src_reg(|reg| {
+            if seen[..count].contains(&Some(reg)) {
+                return;
+            }
+            match seen.get_mut(count) {
+                Some(slot) => {
+                    *slot = Some(reg);
+                    count += 1;
+                    bytes += src_bytes(reg, regs);
+                }
+                None => overflow = true,
+            }
+        });
+        if overflow {
+            bytes = instr.src_regs().into_iter().map(|reg| src_bytes(reg, regs)).sum();
+        }
+        instr.complexity().saturating_add(bytes.saturating_mul(self.per_byte))
+    }
+}
+
+/// Returns number of bytes read by an instruction from the source register.
+fn src_bytes(reg: Reg, regs: &CoreRegs) -> u64 {
+    let bytes = match reg {
+        Reg::A(a, _) => a.bytes(),
+        Reg::F(f, _) => f.bytes(),
+        Reg::R(r, _) => r.bytes(),
+        Reg::S(s) => regs.s16(s).map(ByteStr::len).unwrap_or_default(),
+    };
+    u64::from(bytes)
+}
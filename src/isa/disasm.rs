@@ -0,0 +1,287 @@
+// Reference rust implementation of AluVM (arithmetic logic unit virtual machine).
+// To find more on AluVM please check <https://aluvm.org>
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2021-2024 by
+//     Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2021-2022 LNP/BP Standards Association. All rights reserved.
+// Copyright (C) 2023-2024 UBIDECO Labs,
+//     Institute for Distributed and Cognitive Computing, Switzerland.
+//     All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Disassembler producing AluVM assembly text from the library bytecode.
+//!
+//! Unlike [`Lib::print_disassemble`], which produces a plain listing, [`disassemble`] emits text
+//! which is accepted by the [`asm`](super::asm) assembler and re-assembles into the same library.
+//! Targets of jumps and routine calls are replaced with synthesized labels: `sub_XXXX` for
+//! routines and `loc_XXXX` for other jump targets, where `XXXX` is the code offset. Code offsets,
+//! references to the data segment and calls into external libraries are written as comments.
+
+#[cfg(feature = "alloc")]
+use alloc::collections::BTreeMap;
+#[cfg(feature = "alloc")]
+use alloc::string::{String, ToString};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::fmt::Write as _;
+
+use super::asm::{AsmItem, Jump};
+use super::{Bytecode, BytesOp, ControlFlowOp, Instr, InstructionSet, PutOp};
+use crate::library::{Cursor, Lib, Read};
+use crate::reg::NumericRegister;
+
+/// Errors disassembling library
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug, Display)]
+#[cfg_attr(feature = "std", derive(Error))]
+#[display(doc_comments)]
+pub enum DisasmError {
+    /// instruction at offset {0:#06X} can't be decoded.
+    Decode(u16),
+
+    /// library can't be reproduced from its disassembly since it either has a non-canonical
+    /// encoding or uses a different set of ISA extensions.
+    Irreproducible,
+}
+
+/// Decodes library code segment into a list of instructions, each of which is paired with its
+/// offset in the code segment.
+pub fn decode<Ext>(lib: &Lib) -> Result<Vec<(u16, Instr<Ext>)>, DisasmError>
+where Ext: InstructionSet {
+    let mut code = Vec::new();
+    let mut reader = Cursor::with(lib.code_segment(), lib.data_segment(), lib.libs_segment());
+    while !reader.is_eof() {
+        let pos = reader.pos();
+        let instr = Instr::decode(&mut reader).map_err(|_| DisasmError::Decode(pos))?;
+        code.push((pos, instr));
+    }
+    Ok(code)
+}
+
+/// Disassembles library into assembly text, which can be assembled back into the same library.
+///
+/// # Errors
+///
+/// Fails with [`DisasmError::Irreproducible`] if assembling the decoded instructions does not
+/// result in the same library, which may happen only for libraries which were not produced by
+/// [`Lib::assemble`].
+pub fn disassemble<Ext>(lib: &Lib) -> Result<String, DisasmError>
+where Ext: InstructionSet {
+    let code = decode::<Ext>(lib)?;
+    let instrs = lib.disassemble::<Instr<Ext>>().map_err(|_| DisasmError::Irreproducible)?;
+    if Lib::assemble(&instrs).as_ref() != Ok(lib) {
+        return Err(DisasmError::Irreproducible);
+    }
+
+    let labels = labels(&code);
+    let mut text = format!("; {}\n", lib.id());
+    for (pos, instr) in &code {
+        if let Some(label) = labels.get(pos) {
+            writeln!(text, "{label}:").ok();
+        }
+        let line = match jump(instr).and_then(|(jump, target)| Some((jump, labels.get(&target)?))) {
+            Some((jump, label)) => AsmItem::<Instr<Ext>>::Jump(jump, label.clone()).to_string(),
+            None => instr.to_string(),
+        };
+        write!(text, "        {line:<40} ; @x{pos:06X}").ok();
+        if let Some((offset, len)) = data_ref(lib, *pos, instr) {
+            write!(text, ", data {offset:#06X}..{:#06X}", offset as usize + len as usize).ok();
+        }
+        if let Some(index) = instr.call_site().and_then(|site| lib.libs_segment().index(site.lib)) {
+            write!(text, ", external lib #{index}").ok();
+        }
+        text.push('\n');
+    }
+    Ok(text)
+}
+
+/// Returns label-addressable jump operation and its target offset
+fn jump<Ext>(instr: &Instr<Ext>) -> Option<(Jump, u16)>
+where Ext: InstructionSet {
+    match instr {
+        Instr::ControlFlow(ControlFlowOp::Jmp(pos)) => Some((Jump::Jmp, *pos)),
+        Instr::ControlFlow(ControlFlowOp::Jif(pos)) => Some((Jump::Jif, *pos)),
+        Instr::ControlFlow(ControlFlowOp::Routine(pos)) => Some((Jump::Routine, *pos)),
+        _ => None,
+    }
+}
+
+/// Synthesizes labels for all jump targets which point to an instruction. Targets pointing inside
+/// an instruction, to the end of the code segment or beyond it are left as raw offsets, since the
+/// assembler does not accept labels which are not followed by an instruction.
+fn labels<Ext>(code: &[(u16, Instr<Ext>)]) -> BTreeMap<u16, String>
+where Ext: InstructionSet {
+    let mut targets = BTreeMap::<u16, bool>::new();
+    for (jump, pos) in code.iter().filter_map(|(_, instr)| jump(instr)) {
+        if code.binary_search_by_key(&pos, |(offset, _)| *offset).is_ok() {
+            *targets.entry(pos).or_default() |= jump == Jump::Routine;
+        }
+    }
+    targets
+        .into_iter()
+        .map(|(pos, routine)| (pos, format!("{}_{pos:04x}", if routine { "sub" } else { "loc" })))
+        .collect()
+}
+
+/// Returns offset and length of the data segment range referenced by the instruction
+fn data_ref<Ext>(lib: &Lib, pos: u16, instr: &Instr<Ext>) -> Option<(u16, u16)>
+where Ext: InstructionSet {
+    let len = match instr {
+        Instr::Put(PutOp::PutA(reg, ..) | PutOp::PutIfA(reg, ..)) => Some(reg.bytes()),
+        Instr::Put(PutOp::PutF(reg, ..)) => Some(reg.bytes()),
+        Instr::Put(PutOp::PutR(reg, ..) | PutOp::PutIfR(reg, ..)) => Some(reg.bytes()),
+        Instr::Bytes(BytesOp::Put(..)) => None,
+        _ => return None,
+    };
+    // data offset follows the instruction code and the destination register bytes
+    let mut reader = Cursor::with(lib.code_segment(), lib.data_segment(), lib.libs_segment());
+    reader.seek(pos + 2).ok()?;
+    let offset = reader.read_u16().ok()?;
+    let len = match len {
+        Some(len) => len,
+        None => reader.read_u16().ok()?,
+    };
+    Some((offset, len))
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::boxed::Box;
+
+    use amplify::num::u5;
+
+    use super::*;
+    use crate::data::{ByteStr, Step};
+    use crate::isa::{asm, ArithmeticOp, ReservedOp};
+    use crate::library::{LibId, LibSite};
+    use crate::reg::{Reg32, RegA, RegR, RegS};
+
+    /// Generates pseudo-random program with labels, jumps, data references and external calls
+    fn program(seed: u64) -> Vec<AsmItem<Instr>> {
+        let mut state = seed;
+        let mut next = |max: u64| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state % max
+        };
+        let libs =
+            [LibId::with("ALU", b"\x00", b"", &none!()), LibId::with("ALU", b"", b"", &none!())];
+        let len = 8 + next(40);
+        let mut code = vec![];
+        for no in 0..len {
+            code.push(AsmItem::Label(format!("l{no}")));
+            let reg = Reg32::from(u5::with(next(32) as u8));
+            code.push(match next(9) {
+                0 => AsmItem::Jump(Jump::Jmp, format!("l{}", next(len))),
+                1 => AsmItem::Jump(Jump::Jif, format!("l{}", next(len))),
+                2 => AsmItem::Jump(Jump::Routine, format!("l{}", next(len))),
+                3 => AsmItem::Instr(Instr::ControlFlow(ControlFlowOp::Jif(next(0x200) as u16))),
+                4 => AsmItem::Instr(Instr::Put(PutOp::PutA(
+                    RegA::A64,
+                    reg,
+                    Box::new(next(u64::MAX).into()),
+                ))),
+                5 => AsmItem::Instr(Instr::Put(PutOp::PutIfR(
+                    RegR::R128,
+                    reg,
+                    Box::new((next(u64::MAX) as u128 * 3).into()),
+                ))),
+                6 => AsmItem::Instr(Instr::Bytes(BytesOp::Put(
+                    RegS::from(next(16) as u8),
+                    Box::new(ByteStr::with(format!("data \"{}\"\n", next(4)))),
+                    false,
+                ))),
+                7 => AsmItem::Instr(Instr::ControlFlow(ControlFlowOp::Call(LibSite::with(
+                    next(0x100) as u16,
+                    libs[next(2) as usize],
+                )))),
+                _ => AsmItem::Instr(Instr::Arithmetic(ArithmeticOp::Stp(
+                    RegA::A16,
+                    reg,
+                    Step::with(next(7) as i8 - 3),
+                ))),
+            });
+        }
+        code
+    }
+
+    #[test]
+    fn roundtrip() {
+        for seed in 1..=100 {
+            let lib = Lib::assemble(&program(seed)).unwrap();
+            let text = disassemble::<ReservedOp>(&lib).unwrap();
+            assert_eq!(asm::assemble::<ReservedOp>(&text).unwrap(), lib, "seed {seed}:\n{text}");
+        }
+    }
+
+    #[test]
+    fn offsets() {
+        let lib = Lib::assemble(&program(7)).unwrap();
+        let code = decode::<ReservedOp>(&lib).unwrap();
+        assert_eq!(code.first().unwrap().0, 0);
+        for (pair, instr) in code.windows(2).zip(lib.disassemble::<Instr>().unwrap()) {
+            assert_eq!(pair[0].1, instr);
+            assert_eq!(lib.decode_at::<Instr>(pair[0].0).unwrap(), instr);
+            assert!(pair[0].0 < pair[1].0);
+        }
+    }
+
+    #[test]
+    fn annotations() {
+        let lib_id = LibId::with("ALU", b"", b"", &none!());
+        let inc: Instr =
+            Instr::Arithmetic(ArithmeticOp::Stp(RegA::A16, Reg32::Reg0, Step::with(1)));
+        let lib = Lib::assemble(&[
+            AsmItem::Jump(Jump::Routine, s!("sub")),
+            AsmItem::Instr(Instr::ControlFlow(ControlFlowOp::Jmp(1))),
+            AsmItem::Instr(Instr::ControlFlow(ControlFlowOp::Call(LibSite::with(4, lib_id)))),
+            AsmItem::Label(s!("sub")),
+            AsmItem::Label(s!("loop")),
+            AsmItem::Instr(Instr::Put(PutOp::PutA(RegA::A64, Reg32::Reg1, Box::new(7u64.into())))),
+            AsmItem::Instr(inc),
+            AsmItem::Jump(Jump::Jif, s!("loop")),
+            AsmItem::Instr(Instr::ControlFlow(ControlFlowOp::Jmp(0x17))),
+        ])
+        .unwrap();
+        let text = disassemble::<ReservedOp>(&lib).unwrap();
+        let lines = text.lines().collect::<Vec<_>>();
+        assert_eq!(lines[0], format!("; {}", lib.id()));
+        assert!(lines[1].starts_with("        routine :sub_000a "));
+        assert!(lines[2].starts_with("        jmp     0x0001 "));
+        assert!(lines[3].ends_with("; @x000006, external lib #0"));
+        assert_eq!(lines[4], "sub_000a:");
+        assert!(lines[5].ends_with("; @x00000A, data 0x0000..0x0008"));
+        assert!(lines[7].starts_with("        jif     :sub_000a "));
+        assert!(lines[8].starts_with("        jmp     0x0017 "));
+        assert_eq!(lines.len(), 9);
+    }
+
+    #[test]
+    fn irreproducible() {
+        let lib = Lib::assemble(&program(3)).unwrap();
+        let mut data = lib.data_segment().to_vec();
+        data.push(0);
+        let isae = lib.isae_segment();
+        let libs = lib.libs_segment().clone();
+        let lib = Lib::with(&isae, lib.code_segment().to_vec(), data, libs).unwrap();
+        assert_eq!(disassemble::<ReservedOp>(&lib), Err(DisasmError::Irreproducible));
+        assert!(decode::<ReservedOp>(&lib).is_ok());
+
+        let lib = Lib::with(&isae, vec![0x06], vec![], none!()).unwrap();
+        assert_eq!(decode::<ReservedOp>(&lib), Err(DisasmError::Decode(0)));
+    }
+}
@@ -28,6 +28,8 @@ use alloc::boxed::Box;
 use alloc::collections::BTreeSet;
 #[cfg(feature = "alloc")]
 use alloc::string::{String, ToString};
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
 use core::cmp::Ordering;
 use core::ops::{BitAnd, BitOr, BitXor, Neg, Rem, Shl, Shr};
 
@@ -35,12 +37,17 @@ use sha2::Digest;
 
 use super::{
     ArithmeticOp, BitwiseOp, Bytecode, BytesOp, CmpOp, ControlFlowOp, Curve25519Op, DigestOp,
-    Instr, MoveOp, PutOp, ReservedOp, Secp256k1Op,
+    Instr, MoveOp, PutOp, ReservedOp, Secp256k1Op, CALL_LEN, ROUTINE_LEN,
 };
 use crate::data::{ByteStr, MaybeNumber, Number, NumberLayout};
-use crate::isa::{ExtendFlag, FloatEqFlag, IntFlags, MergeFlag, NoneEqFlag, SignFlag};
-use crate::library::{constants, IsaName, IsaSeg, LibSite};
-use crate::reg::{CoreRegs, NumericRegister, Reg, Reg32, RegA, RegA2, RegAR, RegBlockAR, RegR};
+use crate::isa::{
+    DeleteFlag, ExtendFlag, FloatEqFlag, InsertFlag, IntFlags, MergeFlag, NoneEqFlag, SignFlag,
+    SplitFlag,
+};
+use crate::library::{constants, IsaName, IsaSeg, LibId, LibSite};
+use crate::reg::{
+    CoreRegs, NumericRegister, Reg, Reg32, RegA, RegA2, RegAR, RegBlockAR, RegF, RegR,
+};
 
 /// Turing machine movement after instruction execution
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
@@ -51,6 +58,9 @@ pub enum ExecStep {
     /// Stop and fail program execution
     Fail,
 
+    /// Stop and fail program execution for the provided reason (like exhausted cycle counter)
+    Halt(HaltReason),
+
     /// Move to the next instruction
     Next,
 
@@ -59,6 +69,48 @@ pub enum ExecStep {
 
     /// Jump to another code fragment
     Call(LibSite),
+
+    /// Suspend program execution, passing request with the provided code to the host. The
+    /// execution may be resumed from the next instruction.
+    Yield(u16),
+}
+
+/// Reason for which the program execution has halted
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Display)]
+#[display(doc_comments)]
+pub enum HaltReason {
+    /// execution stopped by an instruction (like `ret` with an empty call stack).
+    Stop,
+
+    /// execution reached the end of the code segment.
+    EndOfCode,
+
+    /// execution failed by an instruction (like `fail` or a failed `test`).
+    Fail,
+
+    /// accumulated complexity has reached the complexity limit.
+    ComplexityLimit,
+
+    /// number of jumps and calls has exceeded the cycle counter capacity.
+    CycleLimit,
+
+    /// call stack overflow.
+    CallStackOverflow,
+
+    /// jump to a non-existing code offset.
+    InvalidJump,
+
+    /// code segment contains invalid instruction.
+    DecodeError,
+
+    /// library {0} required for the execution is not found.
+    LibNotFound(LibId),
+
+    /// library {0} has not passed bytecode verification.
+    Unverified(LibId),
+
+    /// execution is suspended by an instruction with the host request {0}.
+    Yield(u16),
 }
 
 /// Trait for instructions
@@ -88,6 +140,11 @@ pub trait InstructionSet: Bytecode + core::fmt::Display + core::fmt::Debug {
     #[inline]
     fn is_supported(id: &IsaName) -> bool { Self::isa_ids().contains(id) }
 
+    /// ISA extensions required to execute this specific instruction. Defaults to all extensions
+    /// used by the instruction set.
+    #[inline]
+    fn isa_ext(&self) -> IsaSeg { Self::isa_ids() }
+
     /// Lists all registers which are used by the instruction.
     fn regs(&self) -> BTreeSet<Reg> {
         let mut regs = self.src_regs();
@@ -101,6 +158,16 @@ pub trait InstructionSet: Bytecode + core::fmt::Display + core::fmt::Debug {
     /// List of registers which value may be changed by the instruction.
     fn dst_regs(&self) -> BTreeSet<Reg>;
 
+    /// Calls `f` for each of the registers listed by [`InstructionSet::src_regs`]. Instruction
+    /// sets may override it to avoid allocating the register set, which matters for the observers
+    /// called at each of the execution steps. Unlike the set, the same register may be reported
+    /// more than once.
+    fn for_each_src_reg(&self, f: impl FnMut(Reg)) { self.src_regs().into_iter().for_each(f) }
+
+    /// Calls `f` for each of the registers listed by [`InstructionSet::dst_regs`], like
+    /// [`InstructionSet::for_each_src_reg`] does for the source registers.
+    fn for_each_dst_reg(&self, f: impl FnMut(Reg)) { self.dst_regs().into_iter().for_each(f) }
+
     /// Returns computational complexity of the instruction
     fn complexity(&self) -> u64;
 
@@ -116,6 +183,30 @@ pub trait InstructionSet: Bytecode + core::fmt::Display + core::fmt::Debug {
     /// Returns whether further execution should be stopped.
     // TODO: Take the instruction by reference
     fn exec(&self, regs: &mut CoreRegs, site: LibSite, context: &Self::Context<'_>) -> ExecStep;
+
+    /// Returns function executing this instruction with the same effect as
+    /// [`InstructionSet::exec`].
+    ///
+    /// The interpreter selects the function once per decoded instruction and then calls it
+    /// directly; [`LoadedLib`](crate::library::LoadedLib) keeps it together with the pre-decoded
+    /// instruction. Instruction sets may return functions specialized for the instruction kind
+    /// and its operands, which skip matching the other instructions on each execution. Defaults
+    /// to [`InstructionSet::exec`].
+    #[inline]
+    fn exec_fn(&self) -> ExecFn<Self> { Self::exec }
+}
+
+/// Function executing an instruction, as returned by [`InstructionSet::exec_fn`].
+pub type ExecFn<Isa> =
+    for<'ctx> fn(&Isa, &mut CoreRegs, LibSite, &<Isa as InstructionSet>::Context<'ctx>) -> ExecStep;
+
+/// Collects registers reported by `for_each_src_reg` or `for_each_dst_reg` into a set.
+fn collect_regs(visit: impl FnOnce(&mut dyn FnMut(Reg))) -> BTreeSet<Reg> {
+    let mut regs = BTreeSet::new();
+    visit(&mut |reg| {
+        regs.insert(reg);
+    });
+    regs
 }
 
 impl<Extension> InstructionSet for Instr<Extension>
@@ -133,6 +224,26 @@ where Extension: InstructionSet
         set
     }
 
+    fn isa_ext(&self) -> IsaSeg {
+        match self {
+            Instr::Digest(instr) => instr.isa_ext(),
+            #[cfg(feature = "secp256k1")]
+            Instr::Secp256k1(instr) => instr.isa_ext(),
+            #[cfg(feature = "curve25519")]
+            Instr::Curve25519(instr) => instr.isa_ext(),
+            Instr::ExtensionCodes(instr) => instr.isa_ext(),
+            Instr::ControlFlow(_)
+            | Instr::Put(_)
+            | Instr::Move(_)
+            | Instr::Cmp(_)
+            | Instr::Arithmetic(_)
+            | Instr::Bitwise(_)
+            | Instr::Bytes(_)
+            | Instr::ReservedInstruction(_)
+            | Instr::Nop => IsaSeg::with(constants::ISA_ID_ALU),
+        }
+    }
+
     fn src_regs(&self) -> BTreeSet<Reg> {
         match self {
             Instr::ControlFlow(instr) => instr.src_regs(),
@@ -153,6 +264,26 @@ where Extension: InstructionSet
         }
     }
 
+    fn for_each_src_reg(&self, f: impl FnMut(Reg)) {
+        match self {
+            Instr::ControlFlow(instr) => instr.for_each_src_reg(f),
+            Instr::Put(instr) => instr.for_each_src_reg(f),
+            Instr::Move(instr) => instr.for_each_src_reg(f),
+            Instr::Cmp(instr) => instr.for_each_src_reg(f),
+            Instr::Arithmetic(instr) => instr.for_each_src_reg(f),
+            Instr::Bitwise(instr) => instr.for_each_src_reg(f),
+            Instr::Bytes(instr) => instr.for_each_src_reg(f),
+            Instr::Digest(instr) => instr.for_each_src_reg(f),
+            #[cfg(feature = "secp256k1")]
+            Instr::Secp256k1(instr) => instr.for_each_src_reg(f),
+            #[cfg(feature = "curve25519")]
+            Instr::Curve25519(instr) => instr.for_each_src_reg(f),
+            Instr::ExtensionCodes(instr) => instr.for_each_src_reg(f),
+            Instr::ReservedInstruction(instr) => instr.for_each_src_reg(f),
+            Instr::Nop => {}
+        }
+    }
+
     fn dst_regs(&self) -> BTreeSet<Reg> {
         match self {
             Instr::ControlFlow(instr) => instr.dst_regs(),
@@ -173,6 +304,26 @@ where Extension: InstructionSet
         }
     }
 
+    fn for_each_dst_reg(&self, f: impl FnMut(Reg)) {
+        match self {
+            Instr::ControlFlow(instr) => instr.for_each_dst_reg(f),
+            Instr::Put(instr) => instr.for_each_dst_reg(f),
+            Instr::Move(instr) => instr.for_each_dst_reg(f),
+            Instr::Cmp(instr) => instr.for_each_dst_reg(f),
+            Instr::Arithmetic(instr) => instr.for_each_dst_reg(f),
+            Instr::Bitwise(instr) => instr.for_each_dst_reg(f),
+            Instr::Bytes(instr) => instr.for_each_dst_reg(f),
+            Instr::Digest(instr) => instr.for_each_dst_reg(f),
+            #[cfg(feature = "secp256k1")]
+            Instr::Secp256k1(instr) => instr.for_each_dst_reg(f),
+            #[cfg(feature = "curve25519")]
+            Instr::Curve25519(instr) => instr.for_each_dst_reg(f),
+            Instr::ExtensionCodes(instr) => instr.for_each_dst_reg(f),
+            Instr::ReservedInstruction(instr) => instr.for_each_dst_reg(f),
+            Instr::Nop => {}
+        }
+    }
+
     fn complexity(&self) -> u64 {
         match self {
             Instr::ControlFlow(instr) => instr.complexity(),
@@ -213,6 +364,140 @@ where Extension: InstructionSet
             Instr::Nop => ExecStep::Next,
         }
     }
+
+    fn exec_fn(&self) -> ExecFn<Self> {
+        match self {
+            Instr::Cmp(
+                CmpOp::GtA(SignFlag::Unsigned, reg, _, _)
+                | CmpOp::LtA(SignFlag::Unsigned, reg, _, _),
+            )
+            | Instr::Cmp(CmpOp::EqA(_, reg, _, _))
+            | Instr::Arithmetic(ArithmeticOp::Stp(reg, _, _))
+                if reg.bits() <= 64 =>
+            {
+                exec_a64
+            }
+            Instr::Arithmetic(
+                ArithmeticOp::AddA(flags, reg, _, _)
+                | ArithmeticOp::SubA(flags, reg, _, _)
+                | ArithmeticOp::MulA(flags, reg, _, _),
+            ) if !flags.signed && reg.bits() <= 64 => exec_a64,
+            Instr::ControlFlow(_) => exec_control_flow,
+            Instr::Put(_) => exec_put,
+            Instr::Move(_) => exec_move,
+            Instr::Cmp(_) => exec_cmp,
+            Instr::Arithmetic(_) => exec_arithmetic,
+            Instr::Bitwise(_) => exec_bitwise,
+            Instr::Bytes(_) => exec_bytes,
+            Instr::Digest(_) => exec_digest,
+            #[cfg(feature = "secp256k1")]
+            Instr::Secp256k1(_) => exec_secp256k1,
+            #[cfg(feature = "curve25519")]
+            Instr::Curve25519(_) => exec_curve25519,
+            Instr::ExtensionCodes(_) | Instr::ReservedInstruction(_) | Instr::Nop => Self::exec,
+        }
+    }
+}
+
+/// Defines functions returned by [`Instr::exec_fn`], each of which executes instructions of a
+/// single [`Instr`] variant.
+macro_rules! exec_variant {
+    ($( $(#[$attr:meta])* $name:ident => $variant:ident ),+ $(,)?) => { $(
+        $(#[$attr])*
+        fn $name<Ext>(
+            instr: &Instr<Ext>,
+            regs: &mut CoreRegs,
+            site: LibSite,
+            ctx: &Ext::Context<'_>,
+        ) -> ExecStep
+        where
+            Ext: InstructionSet,
+        {
+            match instr {
+                Instr::$variant(op) => op.exec(regs, site, &()),
+                _ => instr.exec(regs, site, ctx),
+            }
+        }
+    )+ };
+}
+
+exec_variant! {
+    exec_control_flow => ControlFlow,
+    exec_put => Put,
+    exec_move => Move,
+    exec_cmp => Cmp,
+    exec_arithmetic => Arithmetic,
+    exec_bitwise => Bitwise,
+    exec_bytes => Bytes,
+    exec_digest => Digest,
+    #[cfg(feature = "secp256k1")]
+    exec_secp256k1 => Secp256k1,
+    #[cfg(feature = "curve25519")]
+    exec_curve25519 => Curve25519,
+}
+
+/// Executes unsigned integer arithmetics and comparisons on `a8`-`a64` registers with native
+/// integers, skipping the conversion of the register values into [`Number`]s. Produces the same
+/// result as [`InstructionSet::exec`] for the instructions selected by [`Instr::exec_fn`].
+fn exec_a64<Ext>(
+    instr: &Instr<Ext>,
+    regs: &mut CoreRegs,
+    site: LibSite,
+    ctx: &Ext::Context<'_>,
+) -> ExecStep
+where
+    Ext: InstructionSet,
+{
+    let get2 = |regs: &CoreRegs, reg: RegA, idx1: Reg32, idx2: Reg32| {
+        regs.a_u64(reg, idx1).zip(regs.a_u64(reg, idx2))
+    };
+    regs.st0 = match instr {
+        Instr::Cmp(CmpOp::GtA(SignFlag::Unsigned, reg, idx1, idx2)) => {
+            get2(regs, *reg, *idx1, *idx2).map(|(val1, val2)| val1 > val2) == Some(true)
+        }
+        Instr::Cmp(CmpOp::LtA(SignFlag::Unsigned, reg, idx1, idx2)) => {
+            get2(regs, *reg, *idx1, *idx2).map(|(val1, val2)| val1 < val2) == Some(true)
+        }
+        Instr::Cmp(CmpOp::EqA(st, reg, idx1, idx2)) => get2(regs, *reg, *idx1, *idx2)
+            .map(|(val1, val2)| val1 == val2)
+            .unwrap_or(*st == NoneEqFlag::Equal),
+        Instr::Arithmetic(ArithmeticOp::Stp(reg, idx, step)) => {
+            let step = step.as_i8();
+            let res = regs.a_u64(*reg, *idx).and_then(|val| match step < 0 {
+                true => val.checked_sub(step.unsigned_abs() as u64),
+                false => fit_a64(*reg, val as u128 + step as u128, false),
+            });
+            regs.set_a_u64(*reg, *idx, res)
+        }
+        Instr::Arithmetic(ArithmeticOp::AddA(flags, reg, src, srcdst)) => {
+            let res = get2(regs, *reg, *src, *srcdst)
+                .and_then(|(val1, val2)| fit_a64(*reg, val1 as u128 + val2 as u128, flags.wrap));
+            regs.set_a_u64(*reg, *srcdst, res)
+        }
+        Instr::Arithmetic(ArithmeticOp::SubA(_, reg, src, srcdst)) => {
+            // Unsigned subtraction fails on underflow even if wrapping is allowed
+            let res =
+                get2(regs, *reg, *src, *srcdst).and_then(|(val1, val2)| val1.checked_sub(val2));
+            regs.set_a_u64(*reg, *srcdst, res)
+        }
+        Instr::Arithmetic(ArithmeticOp::MulA(flags, reg, src, srcdst)) => {
+            let res = get2(regs, *reg, *src, *srcdst)
+                .and_then(|(val1, val2)| fit_a64(*reg, val1 as u128 * val2 as u128, flags.wrap));
+            regs.set_a_u64(*reg, *srcdst, res)
+        }
+        _ => return instr.exec(regs, site, ctx),
+    };
+    ExecStep::Next
+}
+
+/// Fits result of an unsigned operation into `a8`-`a64` register, truncating it on overflow if
+/// `wrap` is set; returns `None` on overflow otherwise.
+fn fit_a64(reg: RegA, val: u128, wrap: bool) -> Option<u64> {
+    let max = u64::MAX >> (64 - reg.bits() as u32);
+    match val <= max as u128 {
+        true => Some(val as u64),
+        false => wrap.then_some(val as u64 & max),
+    }
 }
 
 impl InstructionSet for ControlFlowOp {
@@ -239,23 +524,27 @@ impl InstructionSet for ControlFlowOp {
                 }
             }
             ControlFlowOp::Jmp(offset) => {
-                regs.jmp().map(|_| ExecStep::Jump(*offset)).unwrap_or(ExecStep::Fail)
+                regs.jmp().map(|_| ExecStep::Jump(*offset)).unwrap_or_else(ExecStep::Halt)
             }
             ControlFlowOp::Jif(offset) => {
                 if regs.st0 {
-                    regs.jmp().map(|_| ExecStep::Jump(*offset)).unwrap_or(ExecStep::Fail)
+                    regs.jmp().map(|_| ExecStep::Jump(*offset)).unwrap_or_else(ExecStep::Halt)
                 } else {
                     ExecStep::Next
                 }
             }
             ControlFlowOp::Routine(offset) => {
-                regs.call(site).map(|_| ExecStep::Jump(*offset)).unwrap_or(ExecStep::Fail)
+                // Return to the instruction following the `routine` instruction
+                let ret = LibSite::with(site.pos.saturating_add(ROUTINE_LEN), site.lib);
+                regs.call(ret).map(|_| ExecStep::Jump(*offset)).unwrap_or_else(ExecStep::Halt)
             }
-            ControlFlowOp::Call(site) => {
-                regs.call(*site).map(|_| ExecStep::Call(*site)).unwrap_or(ExecStep::Fail)
+            ControlFlowOp::Call(callee) => {
+                // Return to the instruction following the `call` instruction
+                let ret = LibSite::with(site.pos.saturating_add(CALL_LEN), site.lib);
+                regs.call(ret).map(|_| ExecStep::Call(*callee)).unwrap_or_else(ExecStep::Halt)
             }
             ControlFlowOp::Exec(site) => {
-                regs.jmp().map(|_| ExecStep::Call(*site)).unwrap_or(ExecStep::Fail)
+                regs.jmp().map(|_| ExecStep::Call(*site)).unwrap_or_else(ExecStep::Halt)
             }
             ControlFlowOp::Ret => regs.ret().map(ExecStep::Call).unwrap_or(ExecStep::Stop),
         }
@@ -270,14 +559,16 @@ impl InstructionSet for PutOp {
 
     fn src_regs(&self) -> BTreeSet<Reg> { BTreeSet::new() }
 
-    fn dst_regs(&self) -> BTreeSet<Reg> {
+    fn dst_regs(&self) -> BTreeSet<Reg> { collect_regs(|f| self.for_each_dst_reg(f)) }
+
+    fn for_each_dst_reg(&self, mut f: impl FnMut(Reg)) {
         match self {
-            PutOp::ClrA(_, _) | PutOp::ClrF(_, _) | PutOp::ClrR(_, _) => BTreeSet::new(),
-            PutOp::PutA(reg, reg32, _) => bset![Reg::A(*reg, *reg32)],
-            PutOp::PutF(reg, reg32, _) => bset![Reg::F(*reg, *reg32)],
-            PutOp::PutR(reg, reg32, _) => bset![Reg::R(*reg, *reg32)],
-            PutOp::PutIfA(reg, reg32, _) => bset![Reg::A(*reg, *reg32)],
-            PutOp::PutIfR(reg, reg32, _) => bset![Reg::R(*reg, *reg32)],
+            PutOp::ClrA(_, _) | PutOp::ClrF(_, _) | PutOp::ClrR(_, _) => {}
+            PutOp::PutA(reg, reg32, _) => f(Reg::A(*reg, *reg32)),
+            PutOp::PutF(reg, reg32, _) => f(Reg::F(*reg, *reg32)),
+            PutOp::PutR(reg, reg32, _) => f(Reg::R(*reg, *reg32)),
+            PutOp::PutIfA(reg, reg32, _) => f(Reg::A(*reg, *reg32)),
+            PutOp::PutIfR(reg, reg32, _) => f(Reg::R(*reg, *reg32)),
         }
     }
 
@@ -331,104 +622,117 @@ impl InstructionSet for MoveOp {
     #[inline]
     fn isa_ids() -> IsaSeg { IsaSeg::default() }
 
-    fn src_regs(&self) -> BTreeSet<Reg> {
+    fn src_regs(&self) -> BTreeSet<Reg> { collect_regs(|f| self.for_each_src_reg(f)) }
+
+    fn for_each_src_reg(&self, mut f: impl FnMut(Reg)) {
         match self {
             MoveOp::MovA(reg, idx1, _idx2) => {
-                bset![Reg::A(*reg, *idx1)]
+                f(Reg::A(*reg, *idx1));
             }
             MoveOp::DupA(reg, idx1, _idx2) => {
-                bset![Reg::A(*reg, *idx1)]
+                f(Reg::A(*reg, *idx1));
             }
             MoveOp::SwpA(reg, idx1, idx2) => {
-                bset![Reg::A(*reg, *idx1), Reg::A(*reg, *idx2)]
+                f(Reg::A(*reg, *idx1));
+                f(Reg::A(*reg, *idx2));
             }
             MoveOp::MovF(reg, idx1, _idx2) => {
-                bset![Reg::F(*reg, *idx1)]
+                f(Reg::F(*reg, *idx1));
             }
             MoveOp::DupF(reg, idx1, _idx2) => {
-                bset![Reg::F(*reg, *idx1)]
+                f(Reg::F(*reg, *idx1));
             }
             MoveOp::SwpF(reg, idx1, idx2) => {
-                bset![Reg::F(*reg, *idx1), Reg::F(*reg, *idx2)]
+                f(Reg::F(*reg, *idx1));
+                f(Reg::F(*reg, *idx2));
             }
             MoveOp::MovR(reg, idx1, _idx2) => {
-                bset![Reg::R(*reg, *idx1)]
+                f(Reg::R(*reg, *idx1));
             }
             MoveOp::DupR(reg, idx1, _idx2) => {
-                bset![Reg::R(*reg, *idx1)]
+                f(Reg::R(*reg, *idx1));
             }
 
             MoveOp::CpyA(sreg, sidx, _dreg, _didx) => {
-                bset![Reg::A(*sreg, *sidx)]
+                f(Reg::A(*sreg, *sidx));
             }
             MoveOp::CnvA(sreg, sidx, _dreg, _didx) => {
-                bset![Reg::A(*sreg, *sidx)]
+                f(Reg::A(*sreg, *sidx));
             }
             MoveOp::CnvF(sreg, sidx, _dreg, _didx) => {
-                bset![Reg::F(*sreg, *sidx)]
+                f(Reg::F(*sreg, *sidx));
             }
             MoveOp::CpyR(sreg, sidx, _dreg, _didx) => {
-                bset![Reg::R(*sreg, *sidx)]
+                f(Reg::R(*sreg, *sidx));
             }
             MoveOp::SpyAR(sreg, sidx, dreg, didx) => {
-                bset![Reg::A(*sreg, *sidx), Reg::R(*dreg, *didx)]
+                f(Reg::A(*sreg, *sidx));
+                f(Reg::R(*dreg, *didx));
             }
             MoveOp::CnvAF(sreg, sidx, _dreg, _didx) => {
-                bset![Reg::A(*sreg, *sidx)]
+                f(Reg::A(*sreg, *sidx));
             }
             MoveOp::CnvFA(sreg, sidx, _dreg, _didx) => {
-                bset![Reg::F(*sreg, *sidx)]
+                f(Reg::F(*sreg, *sidx));
             }
         }
     }
 
-    fn dst_regs(&self) -> BTreeSet<Reg> {
+    fn dst_regs(&self) -> BTreeSet<Reg> { collect_regs(|f| self.for_each_dst_reg(f)) }
+
+    fn for_each_dst_reg(&self, mut f: impl FnMut(Reg)) {
         match self {
             MoveOp::MovA(reg, idx1, idx2) => {
-                bset![Reg::A(*reg, *idx1), Reg::A(*reg, *idx2)]
+                f(Reg::A(*reg, *idx1));
+                f(Reg::A(*reg, *idx2));
             }
             MoveOp::DupA(reg, _idx1, idx2) => {
-                bset![Reg::A(*reg, *idx2)]
+                f(Reg::A(*reg, *idx2));
             }
             MoveOp::SwpA(reg, idx1, idx2) => {
-                bset![Reg::A(*reg, *idx1), Reg::A(*reg, *idx2)]
+                f(Reg::A(*reg, *idx1));
+                f(Reg::A(*reg, *idx2));
             }
             MoveOp::MovF(reg, idx1, idx2) => {
-                bset![Reg::F(*reg, *idx1), Reg::F(*reg, *idx2)]
+                f(Reg::F(*reg, *idx1));
+                f(Reg::F(*reg, *idx2));
             }
             MoveOp::DupF(reg, _idx1, idx2) => {
-                bset![Reg::F(*reg, *idx2)]
+                f(Reg::F(*reg, *idx2));
             }
             MoveOp::SwpF(reg, idx1, idx2) => {
-                bset![Reg::F(*reg, *idx1), Reg::F(*reg, *idx2)]
+                f(Reg::F(*reg, *idx1));
+                f(Reg::F(*reg, *idx2));
             }
             MoveOp::MovR(reg, idx1, idx2) => {
-                bset![Reg::R(*reg, *idx1), Reg::R(*reg, *idx2)]
+                f(Reg::R(*reg, *idx1));
+                f(Reg::R(*reg, *idx2));
             }
             MoveOp::DupR(reg, _idx1, idx2) => {
-                bset![Reg::R(*reg, *idx2)]
+                f(Reg::R(*reg, *idx2));
             }
 
             MoveOp::CpyA(_sreg, _sidx, dreg, didx) => {
-                bset![Reg::A(*dreg, *didx)]
+                f(Reg::A(*dreg, *didx));
             }
             MoveOp::CnvA(_sreg, _sidx, dreg, didx) => {
-                bset![Reg::A(*dreg, *didx)]
+                f(Reg::A(*dreg, *didx));
             }
             MoveOp::CnvF(_sreg, _sidx, dreg, didx) => {
-                bset![Reg::F(*dreg, *didx)]
+                f(Reg::F(*dreg, *didx));
             }
             MoveOp::CpyR(_sreg, _sidx, dreg, didx) => {
-                bset![Reg::R(*dreg, *didx)]
+                f(Reg::R(*dreg, *didx));
             }
             MoveOp::SpyAR(sreg, sidx, dreg, didx) => {
-                bset![Reg::A(*sreg, *sidx), Reg::R(*dreg, *didx)]
+                f(Reg::A(*sreg, *sidx));
+                f(Reg::R(*dreg, *didx));
             }
             MoveOp::CnvAF(_sreg, _sidx, dreg, didx) => {
-                bset![Reg::F(*dreg, *didx)]
+                f(Reg::F(*dreg, *didx));
             }
             MoveOp::CnvFA(_sreg, _sidx, dreg, didx) => {
-                bset![Reg::A(*dreg, *didx)]
+                f(Reg::A(*dreg, *didx));
             }
         }
     }
@@ -517,53 +821,63 @@ impl InstructionSet for CmpOp {
     #[inline]
     fn isa_ids() -> IsaSeg { IsaSeg::default() }
 
-    fn src_regs(&self) -> BTreeSet<Reg> {
+    fn src_regs(&self) -> BTreeSet<Reg> { collect_regs(|f| self.for_each_src_reg(f)) }
+
+    fn for_each_src_reg(&self, mut f: impl FnMut(Reg)) {
         match self {
             CmpOp::GtA(_, reg, idx1, idx2) => {
-                bset![Reg::A(*reg, *idx1), Reg::A(*reg, *idx2)]
+                f(Reg::A(*reg, *idx1));
+                f(Reg::A(*reg, *idx2));
             }
             CmpOp::LtA(_, reg, idx1, idx2) => {
-                bset![Reg::A(*reg, *idx1), Reg::A(*reg, *idx2)]
+                f(Reg::A(*reg, *idx1));
+                f(Reg::A(*reg, *idx2));
             }
             CmpOp::GtF(_, reg, idx1, idx2) => {
-                bset![Reg::F(*reg, *idx1), Reg::F(*reg, *idx2)]
+                f(Reg::F(*reg, *idx1));
+                f(Reg::F(*reg, *idx2));
             }
             CmpOp::LtF(_, reg, idx1, idx2) => {
-                bset![Reg::F(*reg, *idx1), Reg::F(*reg, *idx2)]
+                f(Reg::F(*reg, *idx1));
+                f(Reg::F(*reg, *idx2));
             }
             CmpOp::GtR(reg, idx1, idx2) => {
-                bset![Reg::R(*reg, *idx1), Reg::R(*reg, *idx2)]
+                f(Reg::R(*reg, *idx1));
+                f(Reg::R(*reg, *idx2));
             }
             CmpOp::LtR(reg, idx1, idx2) => {
-                bset![Reg::R(*reg, *idx1), Reg::R(*reg, *idx2)]
+                f(Reg::R(*reg, *idx1));
+                f(Reg::R(*reg, *idx2));
             }
             CmpOp::EqA(_, reg, idx1, idx2) => {
-                bset![Reg::A(*reg, *idx1), Reg::A(*reg, *idx2)]
+                f(Reg::A(*reg, *idx1));
+                f(Reg::A(*reg, *idx2));
             }
             CmpOp::EqF(_, reg, idx1, idx2) => {
-                bset![Reg::F(*reg, *idx1), Reg::F(*reg, *idx2)]
+                f(Reg::F(*reg, *idx1));
+                f(Reg::F(*reg, *idx2));
             }
             CmpOp::EqR(_, reg, idx1, idx2) => {
-                bset![Reg::R(*reg, *idx1), Reg::R(*reg, *idx2)]
+                f(Reg::R(*reg, *idx1));
+                f(Reg::R(*reg, *idx2));
             }
 
             CmpOp::IfZA(reg, idx) | CmpOp::IfNA(reg, idx) => {
-                bset![Reg::A(*reg, *idx)]
+                f(Reg::A(*reg, *idx));
             }
             CmpOp::IfZR(reg, idx) | CmpOp::IfNR(reg, idx) => {
-                bset![Reg::R(*reg, *idx)]
+                f(Reg::R(*reg, *idx));
             }
-            CmpOp::St(_, _, _) => BTreeSet::new(),
-            CmpOp::StInv => BTreeSet::new(),
+            CmpOp::St(_, _, _) => {}
+            CmpOp::StInv => {}
         }
     }
 
-    fn dst_regs(&self) -> BTreeSet<Reg> {
-        match self {
-            CmpOp::St(_, reg, idx) => {
-                bset![Reg::A(*reg, (*idx).into())]
-            }
-            _ => BTreeSet::new(),
+    fn dst_regs(&self) -> BTreeSet<Reg> { collect_regs(|f| self.for_each_dst_reg(f)) }
+
+    fn for_each_dst_reg(&self, mut f: impl FnMut(Reg)) {
+        if let CmpOp::St(_, reg, idx) = self {
+            f(Reg::A(*reg, (*idx).into()));
         }
     }
 
@@ -649,10 +963,13 @@ impl InstructionSet for CmpOp {
                 let st = Number::from(regs.st0 as u8);
                 let res = match (*regs.get_n(reg, idx), merge_flag) {
                     (None, _) | (_, MergeFlag::Set) => st,
-                    (Some(val), MergeFlag::Add) => val
-                        .int_add(st, IntFlags {
-                            signed: false,
-                            wrap: false,
+                    (Some(val), MergeFlag::Add) => st
+                        .reshaped(val.layout(), true)
+                        .and_then(|st| {
+                            val.int_add(st, IntFlags {
+                                signed: false,
+                                wrap: false,
+                            })
                         })
                         .unwrap_or(val),
                     (Some(val), MergeFlag::And) => val & st,
@@ -674,54 +991,61 @@ impl InstructionSet for ArithmeticOp {
     #[inline]
     fn isa_ids() -> IsaSeg { IsaSeg::default() }
 
-    fn src_regs(&self) -> BTreeSet<Reg> {
+    fn src_regs(&self) -> BTreeSet<Reg> { collect_regs(|f| self.for_each_src_reg(f)) }
+
+    fn for_each_src_reg(&self, mut f: impl FnMut(Reg)) {
         match self {
             ArithmeticOp::Neg(reg, idx) | ArithmeticOp::Abs(reg, idx) => {
-                bset![Reg::new(*reg, *idx)]
+                f(Reg::new(*reg, *idx));
             }
             ArithmeticOp::Stp(reg, idx, _) => {
-                bset![Reg::A(*reg, *idx)]
+                f(Reg::A(*reg, *idx));
             }
             ArithmeticOp::AddA(_, reg, src, srcdst)
             | ArithmeticOp::SubA(_, reg, src, srcdst)
             | ArithmeticOp::MulA(_, reg, src, srcdst)
             | ArithmeticOp::DivA(_, reg, src, srcdst) => {
-                bset![Reg::A(*reg, *src), Reg::A(*reg, *srcdst)]
+                f(Reg::A(*reg, *src));
+                f(Reg::A(*reg, *srcdst));
             }
             ArithmeticOp::AddF(_, reg, src, srcdst)
             | ArithmeticOp::SubF(_, reg, src, srcdst)
             | ArithmeticOp::MulF(_, reg, src, srcdst)
             | ArithmeticOp::DivF(_, reg, src, srcdst) => {
-                bset![Reg::F(*reg, *src), Reg::F(*reg, *srcdst)]
+                f(Reg::F(*reg, *src));
+                f(Reg::F(*reg, *srcdst));
             }
             ArithmeticOp::Rem(reg1, src, reg2, srcdst) => {
-                bset![Reg::A(*reg1, *src), Reg::A(*reg2, *srcdst)]
+                f(Reg::A(*reg1, *src));
+                f(Reg::A(*reg2, *srcdst));
             }
         }
     }
 
-    fn dst_regs(&self) -> BTreeSet<Reg> {
+    fn dst_regs(&self) -> BTreeSet<Reg> { collect_regs(|f| self.for_each_dst_reg(f)) }
+
+    fn for_each_dst_reg(&self, mut f: impl FnMut(Reg)) {
         match self {
             ArithmeticOp::Neg(reg, idx) | ArithmeticOp::Abs(reg, idx) => {
-                bset![Reg::new(*reg, *idx)]
+                f(Reg::new(*reg, *idx));
             }
             ArithmeticOp::Stp(reg, idx, _) => {
-                bset![Reg::A(*reg, *idx)]
+                f(Reg::A(*reg, *idx));
             }
             ArithmeticOp::AddA(_, reg, _src, srcdst)
             | ArithmeticOp::SubA(_, reg, _src, srcdst)
             | ArithmeticOp::MulA(_, reg, _src, srcdst)
             | ArithmeticOp::DivA(_, reg, _src, srcdst) => {
-                bset![Reg::A(*reg, *srcdst)]
+                f(Reg::A(*reg, *srcdst));
             }
             ArithmeticOp::AddF(_, reg, _src, srcdst)
             | ArithmeticOp::SubF(_, reg, _src, srcdst)
             | ArithmeticOp::MulF(_, reg, _src, srcdst)
             | ArithmeticOp::DivF(_, reg, _src, srcdst) => {
-                bset![Reg::F(*reg, *srcdst)]
+                f(Reg::F(*reg, *srcdst));
             }
             ArithmeticOp::Rem(_reg1, _src, reg2, srcdst) => {
-                bset![Reg::A(*reg2, *srcdst)]
+                f(Reg::A(*reg2, *srcdst));
             }
         }
     }
@@ -747,6 +1071,13 @@ impl InstructionSet for ArithmeticOp {
 
     fn exec(&self, regs: &mut CoreRegs, _: LibSite, _: &()) -> ExecStep {
         let is_some = match self {
+            // Arithmetic on tapered floats is not supported yet
+            ArithmeticOp::AddF(_, RegF::F512, _, srcdst)
+            | ArithmeticOp::SubF(_, RegF::F512, _, srcdst)
+            | ArithmeticOp::MulF(_, RegF::F512, _, srcdst)
+            | ArithmeticOp::DivF(_, RegF::F512, _, srcdst) => {
+                regs.set_n(RegF::F512, srcdst, MaybeNumber::none())
+            }
             ArithmeticOp::Abs(reg, idx) => {
                 regs.set_n(reg, idx, regs.get_n(reg, idx).and_then(Number::abs))
             }
@@ -808,21 +1139,17 @@ impl InstructionSet for ArithmeticOp {
                 idx,
                 regs.get_n(reg, idx).and_then(|val| {
                     if step.as_i8() < 0 {
-                        let mut n = Number::from(-step.as_i8());
-                        debug_assert!(
-                            n.reshape(val.layout()),
-                            "reshape target byte length is always greater"
-                        );
+                        let mut n = Number::from(step.as_i8().unsigned_abs());
+                        let reshaped = n.reshape(val.layout());
+                        debug_assert!(reshaped, "reshape target byte length is always greater");
                         val.int_sub(n, IntFlags {
                             signed: false,
                             wrap: false,
                         })
                     } else {
                         let mut n = Number::from(*step);
-                        debug_assert!(
-                            n.reshape(val.layout()),
-                            "reshape target byte length is always greater"
-                        );
+                        let reshaped = n.reshape(val.layout());
+                        debug_assert!(reshaped, "reshape target byte length is always greater");
                         val.int_add(n, IntFlags {
                             signed: false,
                             wrap: false,
@@ -845,76 +1172,86 @@ impl InstructionSet for BitwiseOp {
     #[inline]
     fn isa_ids() -> IsaSeg { IsaSeg::default() }
 
-    fn src_regs(&self) -> BTreeSet<Reg> {
+    fn src_regs(&self) -> BTreeSet<Reg> { collect_regs(|f| self.for_each_src_reg(f)) }
+
+    fn for_each_src_reg(&self, mut f: impl FnMut(Reg)) {
         match self {
             BitwiseOp::And(reg, idx1, idx2, _idx3)
             | BitwiseOp::Or(reg, idx1, idx2, _idx3)
             | BitwiseOp::Xor(reg, idx1, idx2, _idx3) => {
-                bset![Reg::new(*reg, *idx1), Reg::new(*reg, *idx2)]
+                f(Reg::new(*reg, *idx1));
+                f(Reg::new(*reg, *idx2));
             }
             BitwiseOp::Not(reg, idx) => {
-                bset![Reg::new(*reg, *idx)]
+                f(Reg::new(*reg, *idx));
             }
 
             BitwiseOp::Shl(a2, shift, reg, idx) => {
-                bset![Reg::new(*a2, *shift), Reg::new(*reg, *idx)]
+                f(Reg::new(*a2, *shift));
+                f(Reg::new(*reg, *idx));
             }
             BitwiseOp::ShrA(_, a2, shift, reg, idx) => {
-                bset![Reg::new(*a2, *shift), Reg::A(*reg, *idx)]
+                f(Reg::new(*a2, *shift));
+                f(Reg::A(*reg, *idx));
             }
             BitwiseOp::ShrR(a2, shift, reg, idx) => {
-                bset![Reg::new(*a2, *shift), Reg::R(*reg, *idx)]
+                f(Reg::new(*a2, *shift));
+                f(Reg::R(*reg, *idx));
             }
 
             BitwiseOp::Scl(a2, shift, reg, idx) => {
-                bset![Reg::new(*a2, *shift), Reg::new(*reg, *idx)]
+                f(Reg::new(*a2, *shift));
+                f(Reg::new(*reg, *idx));
             }
             BitwiseOp::Scr(a2, shift, reg, idx) => {
-                bset![Reg::new(*a2, *shift), Reg::new(*reg, *idx)]
+                f(Reg::new(*a2, *shift));
+                f(Reg::new(*reg, *idx));
             }
 
             BitwiseOp::RevA(reg, idx) => {
-                bset![Reg::A(*reg, *idx)]
+                f(Reg::A(*reg, *idx));
             }
             BitwiseOp::RevR(reg, idx) => {
-                bset![Reg::R(*reg, *idx)]
+                f(Reg::R(*reg, *idx));
             }
         }
     }
 
-    fn dst_regs(&self) -> BTreeSet<Reg> {
+    fn dst_regs(&self) -> BTreeSet<Reg> { collect_regs(|f| self.for_each_dst_reg(f)) }
+
+    fn for_each_dst_reg(&self, mut f: impl FnMut(Reg)) {
         match self {
             BitwiseOp::And(reg, _idx1, _idx2, idx3)
             | BitwiseOp::Or(reg, _idx1, _idx2, idx3)
             | BitwiseOp::Xor(reg, _idx1, _idx2, idx3) => {
-                bset![Reg::new(*reg, *idx3)]
+                f(Reg::new(*reg, *idx3));
             }
             BitwiseOp::Not(reg, idx) => {
-                bset![Reg::new(*reg, *idx)]
+                f(Reg::new(*reg, *idx));
             }
 
             BitwiseOp::Shl(_, _, reg, idx) => {
-                bset![Reg::new(*reg, *idx)]
+                f(Reg::new(*reg, *idx));
             }
             BitwiseOp::ShrA(_, _, _, reg, idx) => {
-                bset![Reg::A(*reg, *idx)]
+                f(Reg::A(*reg, *idx));
             }
             BitwiseOp::ShrR(_, _, reg, idx) => {
-                bset![Reg::R(*reg, *idx)]
+                f(Reg::R(*reg, *idx));
             }
 
             BitwiseOp::Scl(_, _, reg, idx) => {
-                bset![Reg::new(*reg, *idx)]
+                f(Reg::new(*reg, *idx));
             }
             BitwiseOp::Scr(_, _, reg, idx) => {
-                bset![Reg::new(*reg, *idx)]
+                f(Reg::new(*reg, *idx));
             }
 
             BitwiseOp::RevA(reg, idx) => {
-                bset![Reg::A(*reg, *idx)]
+                f(Reg::A(*reg, *idx));
             }
             BitwiseOp::RevR(reg, idx) => {
-                bset![Reg::R(*reg, *idx)]
+                f(Reg::R(*reg, *idx));
             }
         }
     }
@@ -967,7 +1304,10 @@ impl InstructionSet for BitwiseOp {
             }
             BitwiseOp::Shl(reg1, shift, reg2, srcdst) => match reg2 {
                 RegAR::A(a) => {
-                    let msb = regs.get_n(a, srcdst).unwrap_or_default()[a.bytes() - 1] & 0x80;
+                    let msb = regs
+                        .get_n(a, srcdst)
+                        .map(|val| val[a.bytes() - 1] & 0x80)
+                        .unwrap_or_default();
                     regs.st0 = msb == 0x80;
                     regs.op(reg2, srcdst, reg1, shift, reg2, srcdst, Shl::shl)
                 }
@@ -1010,7 +1350,10 @@ impl InstructionSet for BitwiseOp {
             }
             BitwiseOp::Scl(reg1, shift, reg2, srcdst) => match reg2 {
                 RegAR::A(_) => {
-                    let msb = regs.get_n(reg2, srcdst).unwrap_or_default()[reg2.bytes() - 1] & 0x80;
+                    let msb = regs
+                        .get_n(reg2, srcdst)
+                        .map(|val| val[reg2.bytes() - 1] & 0x80)
+                        .unwrap_or_default();
                     regs.st0 = msb == 0x80;
                     regs.op(reg2, srcdst, reg1, shift, reg2, srcdst, Number::scl)
                 }
@@ -1078,97 +1421,116 @@ impl InstructionSet for BytesOp {
     #[inline]
     fn isa_ids() -> IsaSeg { IsaSeg::default() }
 
-    fn src_regs(&self) -> BTreeSet<Reg> {
+    fn src_regs(&self) -> BTreeSet<Reg> { collect_regs(|f| self.for_each_src_reg(f)) }
+
+    fn for_each_src_reg(&self, mut f: impl FnMut(Reg)) {
         match self {
-            BytesOp::Put(_reg, _, _) => BTreeSet::new(),
+            BytesOp::Put(_reg, _, _) => {}
             BytesOp::Swp(reg1, reg2) | BytesOp::Find(reg1, reg2) => {
-                bset![Reg::S(*reg1), Reg::S(*reg2)]
+                f(Reg::S(*reg1));
+                f(Reg::S(*reg2));
             }
             BytesOp::Mov(reg1, _reg2) | BytesOp::Rev(reg1, _reg2) => {
-                bset![Reg::S(*reg1)]
+                f(Reg::S(*reg1));
             }
             BytesOp::Fill(reg, offset1, offset2, value, _) => {
-                bset![
-                    Reg::S(*reg),
-                    Reg::A(RegA::A16, *offset1),
-                    Reg::A(RegA::A16, *offset2),
-                    Reg::A(RegA::A8, *value)
-                ]
+                f(Reg::S(*reg));
+                f(Reg::A(RegA::A16, *offset1));
+                f(Reg::A(RegA::A16, *offset2));
+                f(Reg::A(RegA::A8, *value));
             }
             BytesOp::Len(src, _reg, _dst) => {
-                bset![Reg::S(*src)]
+                f(Reg::S(*src));
             }
             BytesOp::Cnt(src, byte, _cnt) => {
-                bset![Reg::S(*src), Reg::new(RegA::A8, *byte)]
+                f(Reg::S(*src));
+                f(Reg::new(RegA::A8, *byte));
             }
             BytesOp::Eq(reg1, reg2) => {
-                bset![Reg::S(*reg1), Reg::S(*reg2)]
+                f(Reg::S(*reg1));
+                f(Reg::S(*reg2));
             }
             BytesOp::Con(reg1, reg2, no, _offset, _len) => {
-                bset![Reg::S(*reg1), Reg::S(*reg2), Reg::A(RegA::A16, *no),]
+                f(Reg::S(*reg1));
+                f(Reg::S(*reg2));
+                f(Reg::A(RegA::A16, *no));
             }
             BytesOp::Extr(src, _dst, _index, offset) => {
-                bset![Reg::S(*src), Reg::new(RegA::A16, *offset)]
+                f(Reg::S(*src));
+                f(Reg::new(RegA::A16, *offset));
             }
             BytesOp::Inj(src1, src2, index, offset) => {
-                bset![Reg::S(*src1), Reg::new(*src2, *index), Reg::new(RegA::A16, *offset)]
+                f(Reg::S(*src1));
+                f(Reg::new(*src2, *index));
+                f(Reg::new(RegA::A16, *offset));
             }
             BytesOp::Join(src1, src2, _dst) => {
-                bset![Reg::S(*src1), Reg::S(*src2)]
+                f(Reg::S(*src1));
+                f(Reg::S(*src2));
             }
             BytesOp::Splt(_flag, offset, src, _dst1, _dst2) => {
-                bset![Reg::A(RegA::A16, *offset), Reg::S(*src)]
+                f(Reg::A(RegA::A16, *offset));
+                f(Reg::S(*src));
             }
-            BytesOp::Ins(_flag, offset, src, _dst) => {
-                bset![Reg::A(RegA::A16, *offset), Reg::S(*src)]
+            BytesOp::Ins(_flag, offset, src, dst) => {
+                f(Reg::A(RegA::A16, *offset));
+                f(Reg::S(*src));
+                f(Reg::S(*dst));
             }
             BytesOp::Del(_flag, reg1, offset1, reg2, offset2, _flag1, _flag2, src, _dst) => {
-                bset![Reg::new(*reg1, *offset1), Reg::new(*reg2, *offset2), Reg::S(*src)]
+                f(Reg::new(*reg1, *offset1));
+                f(Reg::new(*reg2, *offset2));
+                f(Reg::S(*src));
             }
         }
     }
 
-    fn dst_regs(&self) -> BTreeSet<Reg> {
+    fn dst_regs(&self) -> BTreeSet<Reg> { collect_regs(|f| self.for_each_dst_reg(f)) }
+
+    fn for_each_dst_reg(&self, mut f: impl FnMut(Reg)) {
         match self {
             BytesOp::Put(reg, _, _) => {
-                bset![Reg::S(*reg)]
+                f(Reg::S(*reg));
             }
             BytesOp::Swp(reg1, reg2) | BytesOp::Find(reg1, reg2) => {
-                bset![Reg::S(*reg1), Reg::S(*reg2)]
+                f(Reg::S(*reg1));
+                f(Reg::S(*reg2));
             }
             BytesOp::Mov(_reg1, reg2) | BytesOp::Rev(_reg1, reg2) => {
-                bset![Reg::S(*reg2)]
+                f(Reg::S(*reg2));
             }
             BytesOp::Fill(reg, _offset1, _offset2, _value, _) => {
-                bset![Reg::S(*reg)]
+                f(Reg::S(*reg));
             }
             BytesOp::Len(_src, reg, dst) => {
-                bset![Reg::A(*reg, *dst)]
+                f(Reg::A(*reg, *dst));
             }
             BytesOp::Cnt(_src, _byte, cnt) => {
-                bset![Reg::new(RegA::A16, *cnt)]
+                f(Reg::new(RegA::A16, *cnt));
             }
-            BytesOp::Eq(_reg1, _reg2) => BTreeSet::new(),
+            BytesOp::Eq(_reg1, _reg2) => {}
             BytesOp::Con(_reg1, _reg2, _no, offset, len) => {
-                bset![Reg::A(RegA::A16, *offset), Reg::A(RegA::A16, *len)]
+                f(Reg::A(RegA::A16, *offset));
+                f(Reg::A(RegA::A16, *len));
             }
             BytesOp::Extr(_src, dst, index, _offset) => {
-                bset![Reg::new(*dst, *index)]
+                f(Reg::new(*dst, *index));
             }
             BytesOp::Inj(src1, _src2, _index, _offset) => {
-                bset![Reg::S(*src1)]
+                f(Reg::S(*src1));
             }
             BytesOp::Join(_src1, _src2, dst) => {
-                bset![Reg::S(*dst)]
+                f(Reg::S(*dst));
             }
             BytesOp::Splt(_flag, _offset, _src, dst1, dst2) => {
-                bset![Reg::S(*dst1), Reg::S(*dst2)]
+                f(Reg::S(*dst1));
+                f(Reg::S(*dst2));
             }
             BytesOp::Ins(_flag, _offset, _src, dst) => {
-                bset![Reg::S(*dst)]
+                f(Reg::S(*dst));
             }
             BytesOp::Del(_flag, _reg1, _offset1, _reg2, _offset2, _flag1, _flag2, _src, dst) => {
-                bset![Reg::S(*dst)]
+                f(Reg::S(*dst));
             }
         }
     }
@@ -1197,25 +1559,30 @@ impl InstructionSet for BytesOp {
                 regs.s16[reg2.as_usize()] = bs1;
             }
             BytesOp::Fill(reg, offset1, offset2, value, flag) => {
-                let mut f = || -> Option<()> {
+                let mut f = || -> Option<bool> {
                     let o1 = regs.a16[offset1.to_usize()]?;
                     let o2 = regs.a16[offset2.to_usize()]?;
-                    let range = o1..o2;
+                    let (start, end) = (o1.min(o2), o1.max(o2));
                     let val = regs.a8[value.to_usize()]?;
-                    let ref mut bs = regs.s16[reg.as_usize()];
-                    let bs = if let Some(s) = bs {
-                        s
-                    } else {
-                        *bs = Some(ByteStr::default());
-                        bs.as_mut().expect("rust optionals are broken")
-                    };
-                    if bs.len() <= range.end && *flag == ExtendFlag::Fail {
-                        return None;
+                    let bs = regs.s16[reg.as_usize()].get_or_insert_with(ByteStr::default);
+                    let len = bs.len();
+                    if end <= len {
+                        bs.fill(start..end, val);
+                        return Some(true);
                     }
-                    bs.fill(range, val);
-                    Some(())
+                    if *flag == ExtendFlag::Fail {
+                        regs.s16[reg.as_usize()] = None;
+                        return Some(false);
+                    }
+                    if start > len {
+                        bs.fill(len..start, 0);
+                    }
+                    bs.fill(start..end, val);
+                    Some(false)
                 };
-                f().unwrap_or_else(|| regs.st0 = false);
+                if f() != Some(true) {
+                    regs.st0 = false;
+                }
             }
             BytesOp::Len(src, reg, dst) => {
                 let mut f = || -> Option<()> {
@@ -1262,6 +1629,9 @@ impl InstructionSet for BytesOp {
                     let (s1, s2) = regs.get_s2(*reg1, *reg2)?;
                     let r1 = s1.as_ref();
                     let r2 = s2.as_ref();
+                    if r2.is_empty() {
+                        return None;
+                    }
                     let count = r1.windows(r2.len()).filter(|r1| *r1 == r2).count();
                     assert!(count <= u16::MAX as usize);
                     regs.set_n(RegA::A16, Reg32::Reg0, count as u16);
@@ -1343,28 +1713,25 @@ impl InstructionSet for BytesOp {
                     let mut s = regs.get_s(*src)?.clone();
                     let val = regs.get_n(dst, index).map(|v| v)?;
                     let offset = regs.a16[*offset as u8 as usize]?;
-                    let end = offset.saturating_add(dst.layout().bytes() - 1);
-                    s.adjust_len(end);
-                    s.as_mut()[offset as usize..=end as usize].copy_from_slice(val.as_ref());
+                    let end = offset.checked_add(dst.layout().bytes())?;
+                    let len = s.len();
+                    if end > len {
+                        s.fill(len..end, 0);
+                    }
+                    s.as_mut()[offset as usize..end as usize].copy_from_slice(val.as_ref());
                     regs.s16[src.as_usize()] = Some(s);
                     Some(())
                 };
                 f().unwrap_or_else(|| {
                     regs.st0 = false;
-                    regs.set_n(dst, index, MaybeNumber::none());
+                    regs.s16[src.as_usize()] = None;
                 })
             }
             BytesOp::Join(src1, src2, dst) => {
                 let mut f = || -> Option<()> {
                     let (s1, s2) = regs.get_s2(*src1, *src2)?;
-                    if s1.len() as usize + s2.len() as usize > u16::MAX as usize {
-                        return None;
-                    }
-                    let len = s1.len() + s2.len();
                     let mut d = s1.clone();
-                    d.adjust_len(len);
-                    let mut d = ByteStr::with(s1);
-                    d.as_mut()[s1.len() as usize..].copy_from_slice(s2.as_ref());
+                    d.extend_from_slice(s2).ok()?;
                     regs.s16[dst.as_usize()] = Some(d);
                     Some(())
                 };
@@ -1374,13 +1741,174 @@ impl InstructionSet for BytesOp {
                 })
             }
             BytesOp::Splt(flag, offset, src, dst1, dst2) => {
-                todo!("#(6) complete bytestring opcode implementation")
+                let f = || -> Option<(Option<ByteStr>, Option<ByteStr>, bool)> {
+                    let s = regs.s16(*src)?;
+                    let offset = regs.a16[offset.to_usize()]?;
+                    let len = s.len();
+                    let zero = ByteStr::default;
+                    let non_empty = || if len > 0 { Some(s.clone()) } else { None };
+                    Some(match (offset, flag) {
+                        (0, SplitFlag::NoneNone) => (None, None, false),
+                        (0, SplitFlag::NoneNoneOnEmpty) => (None, non_empty(), false),
+                        (0, SplitFlag::NoneZeroOnEmpty) => {
+                            (None, Some(non_empty().unwrap_or_else(zero)), false)
+                        }
+                        (0, SplitFlag::ZeroZeroOnEmpty) => {
+                            (Some(zero()), Some(non_empty().unwrap_or_else(zero)), true)
+                        }
+                        (offset, _) if offset < len => {
+                            let (first, second) = s.as_ref().split_at(offset as usize);
+                            (Some(ByteStr::with(first)), Some(ByteStr::with(second)), true)
+                        }
+                        (
+                            _,
+                            SplitFlag::NoneNone
+                            | SplitFlag::NoneNoneOnEmpty
+                            | SplitFlag::NoneZeroOnEmpty
+                            | SplitFlag::ZeroZeroOnEmpty,
+                        ) => (None, None, false),
+                        (offset, SplitFlag::CutNone | SplitFlag::ZeroNone) if offset == len => {
+                            (Some(s.clone()), None, false)
+                        }
+                        (offset, SplitFlag::CutZero | SplitFlag::ZeroZero) if offset == len => {
+                            (Some(s.clone()), Some(zero()), true)
+                        }
+                        (_, SplitFlag::CutNone) => (Some(s.clone()), None, false),
+                        (_, SplitFlag::CutZero) => (Some(s.clone()), Some(zero()), false),
+                        (_, SplitFlag::ZeroNone) => (Some(zero()), None, false),
+                        (_, SplitFlag::ZeroZero) => (Some(zero()), Some(zero()), false),
+                    })
+                };
+                match f() {
+                    Some((first, second, st0)) => {
+                        regs.s16[dst1.as_usize()] = first;
+                        regs.s16[dst2.as_usize()] = second;
+                        if !st0 {
+                            regs.st0 = false;
+                        }
+                    }
+                    None => {
+                        regs.st0 = false;
+                        regs.s16[dst1.as_usize()] = None;
+                        regs.s16[dst2.as_usize()] = None;
+                    }
+                }
             }
             BytesOp::Ins(flag, offset, src, dst) => {
-                todo!("#(6) complete bytestring opcode implementation")
+                let f = || -> Option<(Option<ByteStr>, bool)> {
+                    let (s, d) = regs.get_s2(*src, *dst)?;
+                    let offset = regs.a16[offset.to_usize()]? as usize;
+                    let (src, dst) = (s.as_ref(), d.as_ref());
+                    let max = ByteStr::MAX_LEN;
+
+                    let splice = |pos: usize, src: &[u8]| -> Vec<u8> {
+                        let mut res = Vec::with_capacity(src.len() + dst.len());
+                        res.extend_from_slice(&dst[..pos]);
+                        res.extend_from_slice(src);
+                        res.extend_from_slice(&dst[pos..]);
+                        res
+                    };
+                    let extend = |src: &[u8]| -> Vec<u8> {
+                        let mut res = dst.to_vec();
+                        res.resize(offset, 0);
+                        res.extend_from_slice(src);
+                        res
+                    };
+                    // Inserts source inside the destination string, applying the length overflow
+                    // rules (6) to (8)
+                    let insert = |pos: usize| -> (Option<ByteStr>, bool) {
+                        if src.len() + dst.len() <= max {
+                            return (Some(ByteStr::with(splice(pos, src))), true);
+                        }
+                        let res = match flag {
+                            InsertFlag::Cut => {
+                                let mut res = splice(pos, src);
+                                res.truncate(max);
+                                Some(res)
+                            }
+                            InsertFlag::Shorten => Some(splice(pos, &src[..max - dst.len()])),
+                            _ => None,
+                        };
+                        (res.map(ByteStr::with), false)
+                    };
+
+                    if offset <= dst.len() {
+                        return Some(insert(offset));
+                    }
+                    // All cases which are not covered by the flag description leave the
+                    // destination in `None` state
+                    let res = if src.len() + dst.len() + offset <= max {
+                        match flag {
+                            InsertFlag::Extend => Some(ByteStr::with(extend(src))),
+                            InsertFlag::Append if src.len() <= dst.len() => insert(src.len()).0,
+                            _ => None,
+                        }
+                    } else {
+                        match flag {
+                            InsertFlag::ExtendCut => {
+                                let src = &src[..src.len().min(max - offset)];
+                                Some(ByteStr::with(extend(src)))
+                            }
+                            InsertFlag::FailOnLen | InsertFlag::Cut | InsertFlag::Shorten
+                                if src.len() <= dst.len() =>
+                            {
+                                insert(src.len()).0
+                            }
+                            _ => None,
+                        }
+                    };
+                    Some((res, false))
+                };
+                let (res, st0) = f().unwrap_or((None, false));
+                if !st0 {
+                    regs.st0 = false;
+                }
+                regs.s16[dst.as_usize()] = res;
             }
             BytesOp::Del(flag, reg1, offset1, reg2, offset2, flag1, flag2, src, dst) => {
-                todo!("#(6) complete bytestring opcode implementation")
+                let f = || -> Option<(Option<ByteStr>, bool)> {
+                    let offset = |reg: &RegA2, idx: &Reg32| match reg {
+                        RegA2::A8 => regs.a8[idx.to_usize()].map(u16::from),
+                        RegA2::A16 => regs.a16[idx.to_usize()],
+                    };
+                    let o1 = offset(reg1, offset1)?;
+                    let o2 = offset(reg2, offset2)?;
+                    let (start, end) = (o1.min(o2), o1.max(o2));
+                    let s = regs.s16(*src)?;
+                    let len = s.len();
+
+                    // All cases which are not covered by the flag description leave the
+                    // destination in `None` state
+                    if start > len {
+                        let res = match flag {
+                            DeleteFlag::Zero => Some(ByteStr::default()),
+                            _ => None,
+                        };
+                        return Some((res, !*flag1));
+                    }
+                    if end > len {
+                        let fragment = ByteStr::with(&s.as_ref()[start as usize..]);
+                        let res = match flag {
+                            DeleteFlag::Cut => Some(fragment),
+                            DeleteFlag::Extend => {
+                                let mut res = fragment;
+                                res.fill(len - start..end - start, 0);
+                                Some(res)
+                            }
+                            _ => None,
+                        };
+                        return Some((res, !*flag2));
+                    }
+
+                    let mut res = ByteStr::with(&s.as_ref()[..start as usize]);
+                    res.extend(s.as_ref()[end as usize..].iter().copied());
+                    Some((Some(res), true))
+                };
+                let (res, st0) = f().unwrap_or((None, false));
+                if !st0 {
+                    regs.st0 = false;
+                }
+                regs.s16[dst.as_usize()] = res;
             }
         }
         ExecStep::Next
@@ -1393,21 +1921,25 @@ impl InstructionSet for DigestOp {
     #[inline]
     fn isa_ids() -> IsaSeg { IsaSeg::with(constants::ISA_ID_BPDIGEST) }
 
-    fn src_regs(&self) -> BTreeSet<Reg> {
+    fn src_regs(&self) -> BTreeSet<Reg> { collect_regs(|f| self.for_each_src_reg(f)) }
+
+    fn for_each_src_reg(&self, mut f: impl FnMut(Reg)) {
         match self {
             DigestOp::Ripemd(src, _dst)
             | DigestOp::Sha256(src, _dst)
             | DigestOp::Blake3(src, _dst)
-            | DigestOp::Sha512(src, _dst) => bset![Reg::S(*src)],
+            | DigestOp::Sha512(src, _dst) => f(Reg::S(*src)),
         }
     }
 
-    fn dst_regs(&self) -> BTreeSet<Reg> {
+    fn dst_regs(&self) -> BTreeSet<Reg> { collect_regs(|f| self.for_each_dst_reg(f)) }
+
+    fn for_each_dst_reg(&self, mut f: impl FnMut(Reg)) {
         match self {
-            DigestOp::Ripemd(_src, dst) => bset![Reg::new(RegR::R160, *dst)],
-            DigestOp::Sha256(_src, dst) => bset![Reg::new(RegR::R256, *dst)],
-            DigestOp::Blake3(_src, dst) => bset![Reg::new(RegR::R256, *dst)],
-            DigestOp::Sha512(_src, dst) => bset![Reg::new(RegR::R512, *dst)],
+            DigestOp::Ripemd(_src, dst) => f(Reg::new(RegR::R160, *dst)),
+            DigestOp::Sha256(_src, dst) => f(Reg::new(RegR::R256, *dst)),
+            DigestOp::Blake3(_src, dst) => f(Reg::new(RegR::R256, *dst)),
+            DigestOp::Sha512(_src, dst) => f(Reg::new(RegR::R512, *dst)),
         }
     }
 
@@ -1465,39 +1997,46 @@ impl InstructionSet for Secp256k1Op {
     #[inline]
     fn isa_ids() -> IsaSeg { IsaSeg::with(constants::ISA_ID_SECP256K) }
 
-    fn src_regs(&self) -> BTreeSet<Reg> {
+    fn src_regs(&self) -> BTreeSet<Reg> { collect_regs(|f| self.for_each_src_reg(f)) }
+
+    fn for_each_src_reg(&self, mut f: impl FnMut(Reg)) {
         match self {
             Secp256k1Op::Gen(src, _dst) => {
-                bset![Reg::R(RegR::R256, *src)]
+                f(Reg::R(RegR::R256, *src));
             }
             Secp256k1Op::Mul(RegBlockAR::A, scal, src, _dst) => {
-                bset![Reg::A(RegA::A256, *scal), Reg::R(RegR::R512, *src)]
+                f(Reg::A(RegA::A256, *scal));
+                f(Reg::R(RegR::R512, *src));
             }
             Secp256k1Op::Mul(RegBlockAR::R, scal, src, _dst) => {
-                bset![Reg::R(RegR::R256, *scal), Reg::R(RegR::R512, *src)]
+                f(Reg::R(RegR::R256, *scal));
+                f(Reg::R(RegR::R512, *src));
             }
             Secp256k1Op::Add(src, srcdst) => {
-                bset![Reg::R(RegR::R512, *src), Reg::new(RegR::R512, *srcdst)]
+                f(Reg::R(RegR::R512, *src));
+                f(Reg::new(RegR::R512, *srcdst));
             }
             Secp256k1Op::Neg(src, _dst) => {
-                bset![Reg::R(RegR::R512, *src)]
+                f(Reg::R(RegR::R512, *src));
             }
         }
     }
 
-    fn dst_regs(&self) -> BTreeSet<Reg> {
+    fn dst_regs(&self) -> BTreeSet<Reg> { collect_regs(|f| self.for_each_dst_reg(f)) }
+
+    fn for_each_dst_reg(&self, mut f: impl FnMut(Reg)) {
         match self {
             Secp256k1Op::Gen(_src, dst) => {
-                bset![Reg::new(RegR::R512, *dst)]
+                f(Reg::new(RegR::R512, *dst));
             }
             Secp256k1Op::Mul(_, _, _src, dst) => {
-                bset![Reg::R(RegR::R512, *dst)]
+                f(Reg::R(RegR::R512, *dst));
             }
             Secp256k1Op::Add(_src, srcdst) => {
-                bset![Reg::new(RegR::R512, *srcdst)]
+                f(Reg::new(RegR::R512, *srcdst));
             }
             Secp256k1Op::Neg(_src, dst) => {
-                bset![Reg::new(RegR::R512, *dst)]
+                f(Reg::new(RegR::R512, *dst));
             }
         }
     }
@@ -1506,8 +2045,10 @@ impl InstructionSet for Secp256k1Op {
     fn complexity(&self) -> u64 { 1000 }
 
     #[cfg(not(feature = "secp256k1"))]
-    fn exec(&self, _: &mut CoreRegs, _: LibSite, _: &()) -> ExecStep {
-        unimplemented!("AluVM runtime compiled without support for Secp256k1 instructions")
+    fn exec(&self, regs: &mut CoreRegs, _: LibSite, _: &()) -> ExecStep {
+        // AluVM runtime compiled without support for Secp256k1 instructions
+        regs.st0 = false;
+        ExecStep::Fail
     }
 
     #[cfg(feature = "secp256k1")]
@@ -1532,9 +2073,10 @@ impl InstructionSet for Secp256k1Op {
             }
 
             Secp256k1Op::Mul(block, scal, src, dst) => {
-                let reg = block.into_reg(256).expect("register set does not match standard");
-                let res = regs
-                    .get_n(reg, scal)
+                let res = block
+                    .into_reg(256)
+                    .map(|reg| regs.get_n(reg, scal))
+                    .unwrap_or_default()
                     .and_then(|scal| {
                         regs.get_n(RegR::R512, src)
                             .and_then(|val| {
@@ -1553,7 +2095,9 @@ impl InstructionSet for Secp256k1Op {
                     .as_ref()
                     .map(PublicKey::serialize_uncompressed)
                     .map(|pk| Number::from_slice(&pk[1..]));
-                regs.set_n(RegR::R512, dst, res);
+                if !regs.set_n(RegR::R512, dst, res) {
+                    regs.st0 = false;
+                }
             }
 
             Secp256k1Op::Add(src, srcdst) => {
@@ -1608,39 +2152,46 @@ impl InstructionSet for Curve25519Op {
     #[inline]
     fn isa_ids() -> IsaSeg { IsaSeg::with(constants::ISA_ID_ED25519) }
 
-    fn src_regs(&self) -> BTreeSet<Reg> {
+    fn src_regs(&self) -> BTreeSet<Reg> { collect_regs(|f| self.for_each_src_reg(f)) }
+
+    fn for_each_src_reg(&self, mut f: impl FnMut(Reg)) {
         match self {
             Curve25519Op::Gen(src, _dst) => {
-                bset![Reg::R(RegR::R256, *src)]
+                f(Reg::R(RegR::R256, *src));
             }
             Curve25519Op::Mul(RegBlockAR::A, scal, src, _dst) => {
-                bset![Reg::A(RegA::A256, *scal), Reg::R(RegR::R512, *src)]
+                f(Reg::A(RegA::A256, *scal));
+                f(Reg::R(RegR::R256, *src));
             }
             Curve25519Op::Mul(RegBlockAR::R, scal, src, _dst) => {
-                bset![Reg::R(RegR::R256, *scal), Reg::R(RegR::R512, *src)]
+                f(Reg::R(RegR::R256, *scal));
+                f(Reg::R(RegR::R256, *src));
             }
             Curve25519Op::Add(src1, src2, _dst, _) => {
-                bset![Reg::R(RegR::R512, *src1), Reg::new(RegR::R512, *src2)]
+                f(Reg::R(RegR::R256, *src1));
+                f(Reg::new(RegR::R256, *src2));
             }
             Curve25519Op::Neg(src, _dst) => {
-                bset![Reg::R(RegR::R512, *src)]
+                f(Reg::R(RegR::R256, *src));
             }
         }
     }
 
-    fn dst_regs(&self) -> BTreeSet<Reg> {
+    fn dst_regs(&self) -> BTreeSet<Reg> { collect_regs(|f| self.for_each_dst_reg(f)) }
+
+    fn for_each_dst_reg(&self, mut f: impl FnMut(Reg)) {
         match self {
             Curve25519Op::Gen(_src, dst) => {
-                bset![Reg::new(RegR::R512, *dst)]
+                f(Reg::new(RegR::R256, *dst));
             }
             Curve25519Op::Mul(_, _, _src, dst) => {
-                bset![Reg::R(RegR::R512, *dst)]
+                f(Reg::R(RegR::R256, *dst));
             }
             Curve25519Op::Add(_src1, _src2, dst, _) => {
-                bset![Reg::new(RegR::R512, *dst)]
+                f(Reg::new(RegR::R256, *dst));
             }
             Curve25519Op::Neg(_src, dst) => {
-                bset![Reg::new(RegR::R512, *dst)]
+                f(Reg::new(RegR::R256, *dst));
             }
         }
     }
@@ -1649,13 +2200,72 @@ impl InstructionSet for Curve25519Op {
     fn complexity(&self) -> u64 { 1000 }
 
     #[cfg(not(feature = "curve25519"))]
-    fn exec(&self, _: &mut CoreRegs, _: LibSite, _: &()) -> ExecStep {
-        unimplemented!("AluVM runtime compiled without support for Curve25519 instructions")
+    fn exec(&self, regs: &mut CoreRegs, _: LibSite, _: &()) -> ExecStep {
+        // AluVM runtime compiled without support for Curve25519 instructions
+        regs.st0 = false;
+        ExecStep::Fail
     }
 
     #[cfg(feature = "curve25519")]
-    fn exec(&self, _regs: &mut CoreRegs, _site: LibSite, _: &()) -> ExecStep {
-        todo!("implement Curve256 operations")
+    fn exec(&self, regs: &mut CoreRegs, _site: LibSite, _: &()) -> ExecStep {
+        use curve25519_dalek::constants::ED25519_BASEPOINT_TABLE;
+        use curve25519_dalek::edwards::{CompressedEdwardsY, EdwardsPoint};
+        use curve25519_dalek::scalar::Scalar;
+        use curve25519_dalek::traits::IsIdentity;
+
+        let scalar = |val: Number| -> Option<Scalar> {
+            let mut buf = [0u8; 32];
+            buf.copy_from_slice(val.as_ref());
+            Scalar::from_canonical_bytes(buf)
+        };
+        let point = |val: Number| -> Option<EdwardsPoint> {
+            CompressedEdwardsY::from_slice(val.as_ref()).decompress()
+        };
+        let compress = |point: EdwardsPoint| Number::from_slice(point.compress().as_bytes());
+
+        let (dst, res) = match self {
+            Curve25519Op::Gen(src, dst) => {
+                let res = regs
+                    .get_n(RegR::R256, src)
+                    .and_then(scalar)
+                    .map(|scal| &scal * &ED25519_BASEPOINT_TABLE)
+                    .map(compress);
+                (Reg32::from(*dst), res)
+            }
+
+            Curve25519Op::Mul(block, scal, src, dst) => {
+                let res = block
+                    .into_reg(256)
+                    .map(|reg| regs.get_n(reg, scal))
+                    .unwrap_or_default()
+                    .and_then(scalar)
+                    .zip(regs.get_n(RegR::R256, src).and_then(point))
+                    .map(|(scal, point)| scal * point)
+                    .map(compress);
+                (*dst, res)
+            }
+
+            Curve25519Op::Add(src1, src2, dst, overflow) => {
+                let res = regs
+                    .get_n(RegR::R256, src1)
+                    .and_then(point)
+                    .zip(regs.get_n(RegR::R256, src2).and_then(point))
+                    .map(|(point1, point2)| point1 + point2)
+                    .filter(|point| *overflow || !point.is_identity())
+                    .map(compress);
+                (*dst, res)
+            }
+
+            Curve25519Op::Neg(src, dst) => {
+                let res =
+                    regs.get_n(RegR::R256, src).and_then(point).map(|point| -point).map(compress);
+                (Reg32::from(*dst), res)
+            }
+        };
+        if !regs.set_n(RegR::R256, dst, res) {
+            regs.st0 = false;
+        }
+        ExecStep::Next
     }
 }
 
@@ -1679,7 +2289,7 @@ impl InstructionSet for ReservedOp {
 #[cfg(test)]
 mod tests {
     use super::*;
-    #[cfg(feature = "secp256k1")]
+    #[cfg(any(feature = "secp256k1", feature = "curve25519"))]
     use crate::reg::{Reg8, RegBlockAR};
 
     #[test]
@@ -1824,6 +2434,238 @@ mod tests {
         assert!(!register.st0);
     }
 
+    fn put_s16(register: &mut CoreRegs, reg: u8, val: Option<&[u8]>) {
+        match val {
+            Some(val) => BytesOp::Put(reg.into(), Box::new(ByteStr::with(val)), false).exec(
+                register,
+                LibSite::default(),
+                &(),
+            ),
+            None => {
+                register.s16[reg as usize] = None;
+                ExecStep::Next
+            }
+        };
+    }
+
+    fn put_a(register: &mut CoreRegs, reg: RegA, idx: Reg32, val: u16) {
+        PutOp::PutA(reg, idx, MaybeNumber::from(val).into()).exec(
+            register,
+            LibSite::default(),
+            &(),
+        );
+    }
+
+    #[test]
+    fn bytes_splt_test() {
+        use SplitFlag::*;
+
+        let s = b"apple_banana";
+        // (flag, offset, src, dst1, dst2, st0)
+        #[allow(clippy::type_complexity)]
+        let cases: &[(
+            SplitFlag,
+            u16,
+            Option<&[u8]>,
+            Option<&[u8]>,
+            Option<&[u8]>,
+            bool,
+        )] = &[
+            (NoneNone, 5, Some(s), Some(b"apple"), Some(b"_banana"), true),
+            (ZeroZero, 1, Some(s), Some(b"a"), Some(b"pple_banana"), true),
+            (NoneNone, 5, None, None, None, false),
+            // offset == 0
+            (NoneNone, 0, Some(s), None, None, false),
+            (NoneNoneOnEmpty, 0, Some(s), None, Some(s), false),
+            (NoneNoneOnEmpty, 0, Some(b""), None, None, false),
+            (NoneZeroOnEmpty, 0, Some(s), None, Some(s), false),
+            (NoneZeroOnEmpty, 0, Some(b""), None, Some(b""), false),
+            (ZeroZeroOnEmpty, 0, Some(s), Some(b""), Some(s), true),
+            (ZeroZeroOnEmpty, 0, Some(b""), Some(b""), Some(b""), true),
+            // offset == src_len
+            (NoneNone, 12, Some(s), None, None, false),
+            (ZeroZeroOnEmpty, 12, Some(s), None, None, false),
+            (CutNone, 12, Some(s), Some(s), None, false),
+            (ZeroNone, 12, Some(s), Some(s), None, false),
+            (CutZero, 12, Some(s), Some(s), Some(b""), true),
+            (ZeroZero, 12, Some(s), Some(s), Some(b""), true),
+            // offset > src_len
+            (NoneNoneOnEmpty, 15, Some(s), None, None, false),
+            (CutNone, 15, Some(s), Some(s), None, false),
+            (CutZero, 15, Some(s), Some(s), Some(b""), false),
+            (ZeroNone, 15, Some(s), Some(b""), None, false),
+            (ZeroZero, 15, Some(s), Some(b""), Some(b""), false),
+        ];
+
+        for (flag, offset, src, dst1, dst2, st0) in cases {
+            let mut register = CoreRegs::default();
+            put_s16(&mut register, 0, *src);
+            put_s16(&mut register, 1, Some(b"garbage"));
+            put_s16(&mut register, 2, Some(b"garbage"));
+            put_a(&mut register, RegA::A16, Reg32::Reg0, *offset);
+            BytesOp::Splt(*flag, Reg32::Reg0, 0.into(), 1.into(), 2.into()).exec(
+                &mut register,
+                LibSite::default(),
+                &(),
+            );
+            let case = format!("splt.{flag} at {offset}");
+            assert_eq!(register.s16(1).map(ByteStr::to_vec).as_deref(), *dst1, "{case}");
+            assert_eq!(register.s16(2).map(ByteStr::to_vec).as_deref(), *dst2, "{case}");
+            assert_eq!(register.st0, *st0, "{case}");
+        }
+    }
+
+    #[test]
+    fn bytes_ins_test() {
+        use InsertFlag::*;
+
+        // (flag, offset, src, dst, result, st0)
+        #[allow(clippy::type_complexity)]
+        let cases: &[(InsertFlag, u16, &[u8], &[u8], Option<&[u8]>, bool)] = &[
+            (FailOnLen, 0, b"apple", b"_banana", Some(b"apple_banana"), true),
+            (FailOnLen, 7, b"_kiwi", b"_banana", Some(b"_banana_kiwi"), true),
+            (Shorten, 1, b"pp", b"ale", Some(b"apple"), true),
+            // offset > dst_len
+            (FailOnOffset, 9, b"kiwi", b"banana", None, false),
+            (Extend, 8, b"kiwi", b"banana", Some(b"banana\0\0kiwi"), false),
+            (FailOnOffsetLen, 8, b"kiwi", b"banana", None, false),
+            (ExtendCut, 8, b"kiwi", b"banana", None, false),
+            (Append, 8, b"kiwi", b"banana", Some(b"banakiwina"), false),
+            (Append, 8, b"kiwi", b"ban", None, false),
+            (FailOnLen, 8, b"kiwi", b"banana", None, false),
+            (Cut, 8, b"kiwi", b"banana", None, false),
+            // offset > dst_len && src_len + dst_len + offset > 2^16
+            (FailOnOffset, u16::MAX - 1, b"kiwi", b"banana", None, false),
+            (Extend, u16::MAX - 1, b"kiwi", b"banana", None, false),
+            (FailOnOffsetLen, u16::MAX - 1, b"kiwi", b"banana", None, false),
+            (Append, u16::MAX - 1, b"kiwi", b"banana", None, false),
+            (FailOnLen, u16::MAX - 1, b"kiwi", b"banana", Some(b"banakiwina"), false),
+            (Cut, u16::MAX - 1, b"kiwi", b"banana", Some(b"banakiwina"), false),
+            (Shorten, u16::MAX - 1, b"kiwi", b"banana", Some(b"banakiwina"), false),
+            (Shorten, u16::MAX - 1, b"kiwi", b"ban", None, false),
+        ];
+
+        for (flag, offset, src, dst, res, st0) in cases {
+            let mut register = CoreRegs::default();
+            put_s16(&mut register, 0, Some(src));
+            put_s16(&mut register, 1, Some(dst));
+            put_a(&mut register, RegA::A16, Reg32::Reg0, *offset);
+            BytesOp::Ins(*flag, Reg32::Reg0, 0.into(), 1.into()).exec(
+                &mut register,
+                LibSite::default(),
+                &(),
+            );
+            let case = format!("ins.{flag} at {offset}");
+            assert_eq!(register.s16(1).map(ByteStr::to_vec).as_deref(), *res, "{case}");
+            assert_eq!(register.st0, *st0, "{case}");
+        }
+
+        // Extending destination with zeros and cutting the source string
+        let mut register = CoreRegs::default();
+        put_s16(&mut register, 0, Some(b"kiwi"));
+        put_s16(&mut register, 1, Some(b"banana"));
+        put_a(&mut register, RegA::A16, Reg32::Reg0, u16::MAX - 3);
+        BytesOp::Ins(ExtendCut, Reg32::Reg0, 0.into(), 1.into()).exec(
+            &mut register,
+            LibSite::default(),
+            &(),
+        );
+        let res = register.s16(1).unwrap();
+        assert_eq!(res.len(), u16::MAX);
+        assert_eq!(&res.as_ref()[..6], b"banana");
+        assert_eq!(&res.as_ref()[(u16::MAX - 3) as usize..], b"kiw");
+        assert!(!register.st0);
+
+        // Length overflow with `offset <= dst_len`
+        let long = vec![0xFFu8; u16::MAX as usize - 2];
+        for (flag, res) in [
+            (FailOnLen, None),
+            (FailOnOffset, None),
+            (FailOnOffsetLen, None),
+            (Extend, None),
+            (Append, None),
+            (Cut, Some(&b"ki"[..])),
+            (Shorten, Some(&b"ki"[..])),
+            (ExtendCut, None),
+        ] {
+            let mut register = CoreRegs::default();
+            put_s16(&mut register, 0, Some(b"kiwi"));
+            put_s16(&mut register, 1, Some(&long));
+            put_a(&mut register, RegA::A16, Reg32::Reg0, 0);
+            BytesOp::Ins(flag, Reg32::Reg0, 0.into(), 1.into()).exec(
+                &mut register,
+                LibSite::default(),
+                &(),
+            );
+            let s = register.s16(1);
+            assert_eq!(s.map(|s| &s.as_ref()[..2]), res, "ins.{flag}");
+            assert!(s.map(|s| s.len() == u16::MAX).unwrap_or(true), "ins.{flag}");
+            assert!(!register.st0, "ins.{flag}");
+        }
+
+        // Missing source
+        let mut register = CoreRegs::default();
+        put_s16(&mut register, 1, Some(b"banana"));
+        put_a(&mut register, RegA::A16, Reg32::Reg0, 0);
+        BytesOp::Ins(FailOnLen, Reg32::Reg0, 0.into(), 1.into()).exec(
+            &mut register,
+            LibSite::default(),
+            &(),
+        );
+        assert_eq!(register.s16(1), None);
+        assert!(!register.st0);
+    }
+
+    #[test]
+    fn bytes_del_test() {
+        use DeleteFlag::*;
+
+        let s = b"apple_banana";
+        // (flag, offset1, offset2, flag1, flag2, result, st0)
+        #[allow(clippy::type_complexity)]
+        let cases: &[(DeleteFlag, u8, u16, bool, bool, Option<&[u8]>, bool)] = &[
+            (None, 5, 12, true, true, Some(b"apple"), true),
+            (None, 5, 0, true, true, Some(b"_banana"), true),
+            (Zero, 5, 6, true, true, Some(b"applebanana"), true),
+            (Cut, 3, 3, true, true, Some(s), true),
+            // offset_start > src_len
+            (None, 13, 20, true, true, Option::None, false),
+            (None, 13, 20, false, false, Option::None, true),
+            (Zero, 13, 20, true, false, Some(b""), false),
+            (Zero, 13, 20, false, true, Some(b""), true),
+            (Cut, 14, 20, true, true, Option::None, false),
+            (Extend, 14, 20, false, false, Option::None, true),
+            // offset_end > src_len && offset_start <= src_len
+            (None, 5, 20, true, true, Option::None, false),
+            (Zero, 5, 20, false, false, Option::None, true),
+            (Cut, 5, 20, true, false, Some(b"_banana"), true),
+            (Cut, 12, 20, false, true, Some(b""), false),
+            (Extend, 5, 20, false, true, Some(b"_banana\0\0\0\0\0\0\0\0"), false),
+        ];
+
+        for (flag, offset1, offset2, flag1, flag2, res, st0) in cases {
+            let mut register = CoreRegs::default();
+            put_s16(&mut register, 0, Some(s));
+            put_a(&mut register, RegA::A8, Reg32::Reg0, *offset1 as u16);
+            put_a(&mut register, RegA::A16, Reg32::Reg1, *offset2);
+            BytesOp::Del(
+                *flag,
+                RegA2::A8,
+                Reg32::Reg0,
+                RegA2::A16,
+                Reg32::Reg1,
+                *flag1,
+                *flag2,
+                0.into(),
+                1.into(),
+            )
+            .exec(&mut register, LibSite::default(), &());
+            let case = format!("del.{flag} {offset1}..{offset2}");
+            assert_eq!(register.s16(1).map(ByteStr::to_vec).as_deref(), *res, "{case}");
+            assert_eq!(register.st0, *st0, "{case}");
+        }
+    }
+
     #[test]
     #[cfg(feature = "secp256k1")]
     fn secp256k1_add_test() {
@@ -1891,6 +2733,37 @@ mod tests {
         assert!(register.st0);
     }
 
+    #[test]
+    #[cfg(feature = "secp256k1")]
+    fn secp256k1_mul_invalid_test() {
+        let mut register = CoreRegs::default();
+        let lib_site = LibSite::default();
+        PutOp::PutR(RegR::R256, Reg32::Reg0, MaybeNumber::from(2u8).into()).exec(
+            &mut register,
+            lib_site,
+            &(),
+        );
+        PutOp::PutR(RegR::R512, Reg32::Reg0, MaybeNumber::from(1u8).into()).exec(
+            &mut register,
+            lib_site,
+            &(),
+        );
+        PutOp::PutR(RegR::R512, Reg32::Reg1, MaybeNumber::from(1u8).into()).exec(
+            &mut register,
+            lib_site,
+            &(),
+        );
+        // `r512[0]` does not hold a valid curve point: the operation must not halt the program
+        let step = Secp256k1Op::Mul(RegBlockAR::R, Reg32::Reg0, Reg32::Reg0, Reg32::Reg1).exec(
+            &mut register,
+            lib_site,
+            &(),
+        );
+        assert_eq!(step, ExecStep::Next);
+        assert!(!register.st0);
+        assert_eq!(register.get_n(RegR::R512, Reg32::Reg1), MaybeNumber::none());
+    }
+
     #[test]
     #[cfg(feature = "secp256k1")]
     fn secp256k1_neg_test() {
@@ -1940,7 +2813,6 @@ mod tests {
         assert!(register.st0);
     }
 
-    /* TODO: Enable after curve25519 re-implementation
     #[test]
     #[cfg(feature = "curve25519")]
     fn curve25519_mul_test() {
@@ -2021,6 +2893,40 @@ mod tests {
     #[test]
     #[cfg(feature = "curve25519")]
     fn curve25519_add_overflow_test() {
+        let mut register = CoreRegs::default();
+        let lib_site = LibSite::default();
+        PutOp::PutR(RegR::R256, Reg32::Reg1, MaybeNumber::from(1u8).into()).exec(
+            &mut register,
+            lib_site,
+            &(),
+        );
+        Curve25519Op::Gen(Reg32::Reg1, Reg8::Reg1).exec(&mut register, lib_site, &());
+        Curve25519Op::Neg(Reg32::Reg1, Reg8::Reg2).exec(&mut register, lib_site, &());
+        assert!(register.st0);
+        // G + -G
+        Curve25519Op::Add(Reg32::Reg1, Reg32::Reg2, Reg32::Reg3, false).exec(
+            &mut register,
+            lib_site,
+            &(),
+        );
+        assert!(!register.st0);
+        assert!(register.get_n(RegR::R256, Reg32::Reg3).is_none());
+        CmpOp::StInv.exec(&mut register, lib_site, &());
+        Curve25519Op::Add(Reg32::Reg1, Reg32::Reg2, Reg32::Reg3, true).exec(
+            &mut register,
+            lib_site,
+            &(),
+        );
+        assert!(register.st0);
+        // Identity point in compressed form
+        let mut identity = [0u8; 32];
+        identity[0] = 1;
+        assert_eq!(register.get_n(RegR::R256, Reg32::Reg3).unwrap().as_ref(), &identity);
+    }
+
+    #[test]
+    #[cfg(feature = "curve25519")]
+    fn curve25519_invalid_test() {
         let mut register = CoreRegs::default();
         let lib_site = LibSite::default();
         let l_plus_two_bytes: [u8; 32] = [
@@ -2034,33 +2940,54 @@ mod tests {
             MaybeNumber::from(Number::from_slice(l_plus_two_bytes)).into(),
         )
         .exec(&mut register, lib_site, &());
-        PutOp::PutR(RegR::R256, Reg32::Reg1, MaybeNumber::from(1u8).into()).exec(
+        // Non-canonical scalar
+        Curve25519Op::Gen(Reg32::Reg0, Reg8::Reg1).exec(&mut register, lib_site, &());
+        assert!(!register.st0);
+        assert!(register.get_n(RegR::R256, Reg32::Reg1).is_none());
+        CmpOp::StInv.exec(&mut register, lib_site, &());
+        // Uninitialized source register
+        Curve25519Op::Neg(Reg32::Reg5, Reg8::Reg1).exec(&mut register, lib_site, &());
+        assert!(!register.st0);
+        CmpOp::StInv.exec(&mut register, lib_site, &());
+        // Not a curve point
+        PutOp::PutR(RegR::R256, Reg32::Reg2, MaybeNumber::from(2u8).into()).exec(
             &mut register,
             lib_site,
             &(),
         );
-        PutOp::PutR(RegR::R256, Reg32::Reg2, MaybeNumber::from(3u8).into()).exec(
+        Curve25519Op::Neg(Reg32::Reg2, Reg8::Reg3).exec(&mut register, lib_site, &());
+        assert!(!register.st0);
+        assert!(register.get_n(RegR::R256, Reg32::Reg3).is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "curve25519")]
+    fn curve25519_mul_a_test() {
+        let mut register = CoreRegs::default();
+        let lib_site = LibSite::default();
+        PutOp::PutR(RegR::R256, Reg32::Reg0, MaybeNumber::from(1u8).into()).exec(
             &mut register,
             lib_site,
             &(),
         );
-        Curve25519Op::Gen(Reg32::Reg0, Reg8::Reg7).exec(&mut register, lib_site, &());
-        Curve25519Op::Gen(Reg32::Reg1, Reg8::Reg1).exec(&mut register, lib_site, &());
-        Curve25519Op::Gen(Reg32::Reg2, Reg8::Reg2).exec(&mut register, lib_site, &());
-        Curve25519Op::Add(Reg32::Reg7, Reg32::Reg1, Reg32::Reg3, false).exec(
+        PutOp::PutA(RegA::A256, Reg32::Reg0, MaybeNumber::from(7u8).into()).exec(
             &mut register,
             lib_site,
             &(),
         );
-        assert!(!register.st0);
-        ControlFlowOp::Succ.exec(&mut register, lib_site, &());
-        Curve25519Op::Add(Reg32::Reg0, Reg32::Reg1, Reg32::Reg3, true).exec(
+        PutOp::PutR(RegR::R256, Reg32::Reg2, MaybeNumber::from(7u8).into()).exec(
             &mut register,
             lib_site,
             &(),
         );
-        assert!(register.st0);
-        CmpOp::EqR(NoneEqFlag::NonEqual, RegR::R256, Reg32::Reg2, Reg32::Reg3).exec(
+        Curve25519Op::Gen(Reg32::Reg0, Reg8::Reg0).exec(&mut register, lib_site, &());
+        Curve25519Op::Mul(RegBlockAR::A, Reg32::Reg0, Reg32::Reg0, Reg32::Reg1).exec(
+            &mut register,
+            lib_site,
+            &(),
+        );
+        Curve25519Op::Gen(Reg32::Reg2, Reg8::Reg2).exec(&mut register, lib_site, &());
+        CmpOp::EqR(NoneEqFlag::NonEqual, RegR::R256, Reg32::Reg1, Reg32::Reg2).exec(
             &mut register,
             lib_site,
             &(),
@@ -2087,7 +3014,7 @@ mod tests {
             &(),
         );
         assert!(!register.st0);
-        ControlFlowOp::Succ.exec(&mut register, lib_site, &());
+        register.st0 = true;
         assert!(register.st0);
         CmpOp::EqR(NoneEqFlag::NonEqual, RegR::R256, Reg32::Reg0, Reg32::Reg2).exec(
             &mut register,
@@ -2120,5 +3047,63 @@ mod tests {
         );
         assert!(register.st0);
     }
-     */
+
+    #[test]
+    fn exec_fn_a64_edge_cases() {
+        use crate::data::Step;
+
+        let unsigned = |wrap| IntFlags {
+            signed: false,
+            wrap,
+        };
+        let (a8, a16, a64) = (RegA::A8, RegA::A16, RegA::A64);
+        let (r0, r1, r2) = (Reg32::Reg0, Reg32::Reg1, Reg32::Reg2);
+        // Instruction, checked register, its expected value and the expected `st0` value
+        let arithm: [(ArithmeticOp, RegA, Reg32, Option<u64>, bool); 12] = [
+            (ArithmeticOp::AddA(unsigned(false), a8, r0, r1), a8, r1, None, false),
+            (ArithmeticOp::AddA(unsigned(true), a8, r0, r1), a8, r1, Some(44), true),
+            (ArithmeticOp::AddA(unsigned(false), a16, r0, r1), a16, r1, Some(300), true),
+            (ArithmeticOp::AddA(unsigned(false), a8, r0, r2), a8, r2, None, false),
+            (ArithmeticOp::SubA(unsigned(true), a8, r1, r0), a8, r0, None, false),
+            (ArithmeticOp::SubA(unsigned(false), a8, r0, r1), a8, r1, Some(100), true),
+            (ArithmeticOp::MulA(unsigned(false), a8, r0, r1), a8, r1, None, false),
+            (ArithmeticOp::MulA(unsigned(true), a8, r0, r1), a8, r1, Some(32), true),
+            (ArithmeticOp::MulA(unsigned(true), a64, r0, r1), a64, r1, Some(u64::MAX - 1), true),
+            (ArithmeticOp::Stp(a8, r0, Step::with(55)), a8, r0, Some(255), true),
+            (ArithmeticOp::Stp(a8, r0, Step::with(56)), a8, r0, None, false),
+            (ArithmeticOp::Stp(a8, r1, Step::with(-101)), a8, r1, None, false),
+        ];
+        let cmp: [(CmpOp, RegA, Reg32, Option<u64>, bool); 5] = [
+            (CmpOp::LtA(SignFlag::Unsigned, a8, r1, r0), a8, r1, Some(100), true),
+            (CmpOp::GtA(SignFlag::Unsigned, a8, r1, r0), a8, r1, Some(100), false),
+            (CmpOp::GtA(SignFlag::Unsigned, a64, r0, r1), a64, r0, Some(u64::MAX), true),
+            (CmpOp::EqA(NoneEqFlag::Equal, a8, r2, r2), a8, r2, None, true),
+            (CmpOp::EqA(NoneEqFlag::NonEqual, a8, r2, r2), a8, r2, None, false),
+        ];
+        let cases = arithm
+            .into_iter()
+            .map(|(op, reg, idx, val, st0)| {
+                (Instr::<ReservedOp>::Arithmetic(op), reg, idx, val, st0)
+            })
+            .chain(
+                cmp.into_iter()
+                    .map(|(op, reg, idx, val, st0)| (Instr::Cmp(op), reg, idx, val, st0)),
+            );
+        for (instr, reg, idx, val, st0) in cases {
+            let mut regs = CoreRegs::default();
+            regs.set_a8(r0, 200);
+            regs.set_a8(r1, 100);
+            regs.set_a16(r0, 200);
+            regs.set_a16(r1, 100);
+            regs.set_a64(r0, u64::MAX);
+            regs.set_a64(r1, 2);
+            let mut fn_regs = regs.clone();
+
+            let site = LibSite::default();
+            assert_eq!(instr.exec(&mut regs, site, &()), ExecStep::Next);
+            assert_eq!((instr.exec_fn())(&instr, &mut fn_regs, site, &()), ExecStep::Next);
+            assert_eq!((regs.a_u64(reg, idx), regs.st0), (val, st0), "{instr}");
+            assert_eq!((fn_regs.a_u64(reg, idx), fn_regs.st0), (val, st0), "{instr}");
+        }
+    }
 }
@@ -879,11 +879,11 @@ impl FromStr for InsertFlag {
         if !filtered.is_empty() {
             return Err(ParseFlagError::UnknownFlags("insert operation", filtered));
         }
-        if filtered.len() > 1 {
-            return Err(ParseFlagError::DuplicatedFlags("insert operation", filtered));
+        if s.len() > 1 {
+            return Err(ParseFlagError::DuplicatedFlags("insert operation", s.to_owned()));
         }
 
-        Ok(match filtered.as_bytes()[0].into() {
+        Ok(match s.as_bytes()[0].into() {
             'l' => InsertFlag::FailOnLen,
             'o' => InsertFlag::FailOnOffset,
             'f' => InsertFlag::FailOnOffsetLen,
@@ -979,11 +979,11 @@ impl FromStr for DeleteFlag {
         if !filtered.is_empty() {
             return Err(ParseFlagError::UnknownFlags("delete operation", filtered));
         }
-        if filtered.len() > 1 {
-            return Err(ParseFlagError::DuplicatedFlags("delete operation", filtered));
+        if s.len() > 1 {
+            return Err(ParseFlagError::DuplicatedFlags("delete operation", s.to_owned()));
         }
 
-        Ok(match filtered.as_bytes()[0].into() {
+        Ok(match s.as_bytes()[0].into() {
             'n' => DeleteFlag::None,
             'z' => DeleteFlag::Zero,
             'c' => DeleteFlag::Cut,
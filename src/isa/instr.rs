@@ -23,14 +23,17 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#[cfg(feature = "alloc")]
+use alloc::borrow::ToOwned;
 #[cfg(feature = "alloc")]
 use alloc::boxed::Box;
+use core::str::FromStr;
 
 use super::{
     DeleteFlag, FloatEqFlag, InsertFlag, InstructionSet, IntFlags, MergeFlag, RoundingFlag,
     SignFlag, SplitFlag,
 };
-use crate::data::{ByteStr, MaybeNumber, Step};
+use crate::data::{ByteStr, LiteralParseError, MaybeNumber, Step};
 use crate::isa::{ExtendFlag, NoneEqFlag};
 use crate::library::LibSite;
 use crate::reg::{Reg16, Reg32, Reg8, RegA, RegA2, RegAF, RegAR, RegBlockAR, RegF, RegR, RegS};
@@ -40,6 +43,17 @@ use crate::reg::{Reg16, Reg32, Reg8, RegA, RegA2, RegAF, RegAR, RegBlockAR, RegF
 #[display("rsrv:{0:02X}")]
 pub struct ReservedOp(/** Reserved instruction op code value */ pub(super) u8);
 
+impl FromStr for ReservedOp {
+    type Err = LiteralParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let code = s
+            .strip_prefix("rsrv:")
+            .ok_or_else(|| LiteralParseError::UnknownLiteral(s.to_owned()))?;
+        Ok(ReservedOp(u8::from_str_radix(code, 16)?))
+    }
+}
+
 /// Full set of instructions
 #[derive(Clone, PartialEq, Eq, Hash, Debug, Display)]
 #[display(inner)]
@@ -105,6 +119,7 @@ where Extension: InstructionSet
     // 0x11_***_***
     /// No-operation instruction.
     // #[value = 0b11_111_111]
+    #[display("nop")]
     Nop,
 }
 
@@ -687,7 +702,8 @@ pub enum BytesOp {
 
     /// Count number of occurrences of one string within another putting result to `a16[0]`,
     ///
-    /// If the first or the second string is `None`, sets `st0` to `false` and `a16[0]` to `None`.
+    /// If the first or the second string is `None`, or the matching fragment is empty, sets `st0`
+    /// to `false` and `a16[0]` to `None`.
     #[display("find    a16[0],{0},{1}")]
     Find(/** `s` register with string */ RegS, /** `s` register with matching fragment */ RegS),
 
@@ -793,7 +809,7 @@ pub enum BytesOp {
     /// </pre>
     ///
     /// In all of these cases `st0` is set to `false`. Otherwise, `st0` value is not modified.
-    #[display("ins.{0}   {1},a16{2},{2}")]
+    #[display("ins.{0}   {2},a16{1},{3}")]
     Ins(
         InsertFlag,
         /** `a16` register index with offset value for insert location */ Reg32,
@@ -927,11 +943,17 @@ pub enum Secp256k1Op {
     Neg(/** Register hilding EC point to negate */ Reg32, /** Destination register */ Reg8),
 }
 
-/// Operations on Curve25519 elliptic curve
+/// Operations on Curve25519 elliptic curve in Edwards form (Ed25519).
+///
+/// Curve points are kept in `r256` registers in their compressed Edwards Y form; scalars are
+/// little-endian numbers which must be less than the curve group order. If a source register
+/// is in the undefined state, does not contain a valid curve point or a canonical scalar, the
+/// destination register is set to `None` and `st0` is set to `false`. Otherwise, `st0` value is
+/// not modified.
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Display)]
 pub enum Curve25519Op {
     /// Generates new elliptic curve point value saved into destination
-    /// register in `r512` set using scalar value from the source `r256`
+    /// register in `r256` set using scalar value from the source `r256`
     /// register
     #[display("edgen   r256{0},r256{1}")]
     Gen(
@@ -948,8 +970,10 @@ pub enum Curve25519Op {
         /** Destination `r` register index */ Reg32,
     ),
 
-    /// Adds two elliptic curve points
-    #[display("edadd   r512{0},r256{1},r256{2},{3}")]
+    /// Adds two elliptic curve points. If the sum is the identity point (i.e. the points are
+    /// negations of each other) and overflows are not allowed, the destination register is set to
+    /// `None` and `st0` is set to `false`.
+    #[display("edadd   r256{0},r256{1},r256{2},{3}")]
     Add(
         /** Source 1 */ Reg32,
         /** Source 2 */ Reg32,
@@ -959,5 +983,5 @@ pub enum Curve25519Op {
 
     /// Negates elliptic curve point
     #[display("edneg   r256{0},r256{1}")]
-    Neg(/** Register hilding EC point to negate */ Reg32, /** Destination register */ Reg8),
+    Neg(/** Register holding EC point to negate */ Reg32, /** Destination register */ Reg8),
 }
@@ -25,6 +25,10 @@
 
 /// Macro compiler for AluVM assembler.
 ///
+/// Produces a list of instructions, which can be assembled into a library with
+/// [`Lib::assemble`](crate::library::Lib::assemble). Jumps take code offsets; use
+/// [`aluasm_labels!`] to refer to symbolic labels instead.
+///
 /// # Example
 ///
 /// ```
@@ -62,102 +66,191 @@ macro_rules! aluasm {
     } }};
 }
 
+/// Macro compiler for AluVM assembler supporting symbolic labels.
+///
+/// Works as [`aluasm!`], but produces a list of [`AsmItem`](crate::isa::asm::AsmItem)s instead
+/// of instructions. Labels are defined with `name:` before an instruction and are referenced by
+/// `jmp`, `jif` and `routine` as `:name`; they are resolved into code offsets when the items are
+/// assembled with [`Lib::assemble`](crate::library::Lib::assemble).
+///
+/// # Example
+///
+/// ```
+/// # use aluvm::aluasm_labels;
+/// # use aluvm::Vm;
+/// # use aluvm::library::{Lib, LibSite};
+/// # use aluvm::isa::Instr;
+///
+/// let code = aluasm_labels! {
+///         put     a16[0],0                        ;
+///         put     a16[1],10                       ;
+/// start:
+///         inc     a16[0]                          ;
+///         lt.u    a16[0],a16[1]                   ;
+///         jif     :start                          ;
+///         ret                                     ;
+/// };
+///
+/// let lib = Lib::assemble(&code).unwrap();
+/// let mut vm = Vm::<Instr>::new();
+/// vm.exec(LibSite::with(0, lib.id()), |_| Some(&lib), &());
+/// assert_eq!(vm.registers.a16(aluvm::reg::Reg32::Reg0), Some(10));
+/// ```
+#[macro_export]
+macro_rules! aluasm_labels {
+    ($( $tt:tt )+) => {{ #[allow(unused_imports)] {
+        use ::aluvm::isa::ReservedOp;
+        $crate::aluasm_labels_isa! { ReservedOp => $( $tt )+ }
+    } }};
+}
+
 #[doc(hidden)]
 #[macro_export]
 macro_rules! aluasm_isa {
     ($isa:ty => $( $tt:tt )+) => {{
-        use ::std::boxed::Box;
-
-        use ::aluvm::isa::{
-            ArithmeticOp, BitwiseOp, BytesOp, CmpOp, ControlFlowOp, DigestOp, ExtendFlag, FloatEqFlag, Instr, IntFlags,
-            MergeFlag, MoveOp, PutOp, RoundingFlag, Secp256k1Op, SignFlag, NoneEqFlag
-        };
-        use ::aluvm::reg::{
-            Reg16, Reg32, Reg8, RegA, RegA2, RegAR, RegBlockAFR, RegBlockAR, RegF, RegR, RegS,
-            NumericRegister,
-        };
-        use ::aluvm::library::LibSite;
-        use ::aluvm::data::{ByteStr, Number, MaybeNumber, Step};
+        $crate::aluasm_imports!();
 
         let mut code: Vec<Instr<$isa>> = vec![];
         #[allow(unreachable_code)] {
-            $crate::aluasm_inner! { code => $( $tt )+ }
+            $crate::aluasm_inner! { instr code => $( $tt )+ }
+        }
+        code
+    }}
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! aluasm_labels_isa {
+    ($isa:ty => $( $tt:tt )+) => {{
+        use ::std::string::String;
+        use ::aluvm::isa::asm::{AsmItem, Jump};
+        $crate::aluasm_imports!();
+
+        let mut code: Vec<AsmItem<Instr<$isa>>> = vec![];
+        #[allow(unreachable_code)] {
+            $crate::aluasm_inner! { item code => $( $tt )+ }
         }
         code
     }}
 }
 
+#[doc(hidden)]
+#[macro_export]
+macro_rules! aluasm_imports {
+    () => {
+        use ::aluvm::data::{ByteStr, MaybeNumber, Number, Step};
+        use ::aluvm::isa::{
+            ArithmeticOp, BitwiseOp, BytesOp, CmpOp, ControlFlowOp, DigestOp, ExtendFlag,
+            FloatEqFlag, Instr, IntFlags, MergeFlag, MoveOp, NoneEqFlag, PutOp, RoundingFlag,
+            Secp256k1Op, SignFlag,
+        };
+        use ::aluvm::library::LibSite;
+        use ::aluvm::reg::{
+            NumericRegister, Reg16, Reg32, Reg8, RegA, RegA2, RegAR, RegBlockAFR, RegBlockAR, RegF,
+            RegR, RegS,
+        };
+        use ::std::boxed::Box;
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! aluasm_item {
+    (instr $instr:expr) => {
+        $instr
+    };
+    (item $instr:expr) => {
+        AsmItem::Instr($instr)
+    };
+}
+
 #[doc(hidden)]
 #[macro_export]
 macro_rules! aluasm_inner {
-    { $code:ident => } => { };
-    { $code:ident => $op:ident ; $($tt:tt)* } => {
-        $code.push($crate::instr!{ $op });
-        $crate::aluasm_inner! { $code => $( $tt )* }
-    };
-    { $code:ident => $op:ident $arg:literal @ $lib:ident ; $($tt:tt)* } => {
-        $code.push($crate::instr!{ $op $arg @ $lib });
-        $crate::aluasm_inner! { $code => $( $tt )* }
-    };
-    { $code:ident => $op:ident $arg:literal @ $lib:literal ; $($tt:tt)* } => {
-        $code.push($crate::instr!{ $op $arg @ $lib });
-        $crate::aluasm_inner! { $code => $( $tt )* }
-    };
-    { $code:ident => $op:ident $arg:ident @ $lib:ident ; $($tt:tt)* } => {
-        $code.push($crate::instr!{ $op $arg @ $lib });
-        $crate::aluasm_inner! { $code => $( $tt )* }
-    };
-    { $code:ident => $op:ident $( $arg:literal ),+ ; $($tt:tt)* } => {
-        $code.push($crate::instr!{ $op $( $arg ),+ });
-        $crate::aluasm_inner! { $code => $( $tt )* }
-    };
-    { $code:ident => $op:ident $( $arg:ident ),+ ; $($tt:tt)* } => {
-        $code.push($crate::instr!{ $op $( $arg ),+ });
-        $crate::aluasm_inner! { $code => $( $tt )* }
-    };
-    { $code:ident => $op:ident . $flag:ident $( $arg:ident ),+ ; $($tt:tt)* } => {
-        $code.push($crate::instr!{ $op . $flag $( $arg ),+ });
-        $crate::aluasm_inner! { $code => $( $tt )* }
-    };
-    { $code:ident => $op:ident $( $arg:ident [ $idx:literal ] ),+ ; $($tt:tt)* } => {
-        $code.push($crate::instr!{ $op $( $arg [ $idx ]  ),+ });
-        $crate::aluasm_inner! { $code => $( $tt )* }
-    };
-    { $code:ident => $op:ident . $flag:ident $( $arg:ident [ $idx:literal ] ),+ ; $($tt:tt)* } => {
-        $code.push($crate::instr!{ $op . $flag $( $arg [ $idx ]  ),+ });
-        $crate::aluasm_inner! { $code => $( $tt )* }
-    };
-    { $code:ident => $op:ident $arglit:literal, $( $arg:ident [ $idx:literal ] ),+ ; $($tt:tt)* } => {
-        $code.push($crate::instr!{ $op $arglit, $( $arg [ $idx ] ),+ });
-        $crate::aluasm_inner! { $code => $( $tt )* }
-    };
-    { $code:ident => $op:ident $arglit:ident, $( $arg:ident [ $idx:literal ] ),+ ; $($tt:tt)* } => {
-        $code.push($crate::instr!{ $op $arglit, $( $arg [ $idx ] ),+ });
-        $crate::aluasm_inner! { $code => $( $tt )* }
-    };
-    { $code:ident => $op:ident . $flag:ident $arglit:literal, $( $arg:ident [ $idx:literal ] ),+ ; $($tt:tt)* } => {
-        $code.push($crate::instr!{ $op . $flag $arglit, $( $arg [ $idx ] ),+ });
-        $crate::aluasm_inner! { $code => $( $tt )* }
-    };
-    { $code:ident => $op:ident $arglit1:literal, $arglit2:literal, $arg:ident [ $idx:literal ] ; $($tt:tt)* } => {
-        $code.push($crate::instr!{ $op $arglit1, $arglit2, $arg [ $idx ] });
-        $crate::aluasm_inner! { $code => $( $tt )* }
-    };
-    { $code:ident => $op:ident . $flag:ident $arglit1:literal, $arglit2:literal $arg:ident [ $idx:literal ] ; $($tt:tt)* } => {
-        $code.push($crate::instr!{ $op . $flag $arglit1, $arglit2, $arg [ $idx ] });
-        $crate::aluasm_inner! { $code => $( $tt )* }
-    };
-    { $code:ident => $op:ident $arg:ident [ $idx:literal ] , $arglit:literal ; $($tt:tt)* } => {
-        $code.push($crate::instr!{ $op $arg [ $idx ] , $arglit });
-        $crate::aluasm_inner! { $code => $( $tt )* }
-    };
-    { $code:ident => $op:ident $arg:ident [ $idx:literal ] , $arglit:ident ; $($tt:tt)* } => {
-        $code.push($crate::instr!{ $op $arg [ $idx ] , $arglit });
-        $crate::aluasm_inner! { $code => $( $tt )* }
-    };
-    { $code:ident => $op:ident . $flag:ident $arg:ident [ $idx:literal ], $arglit:expr ; $($tt:tt)* } => {
-        $code.push($crate::instr!{ $op . $flag $arg [ $idx ], $arglit });
-        $crate::aluasm_inner! { $code => $( $tt )* }
+    { $mode:ident $code:ident => } => { };
+    { item $code:ident => jmp : $label:ident ; $($tt:tt)* } => {
+        $code.push(AsmItem::Jump(Jump::Jmp, String::from(stringify!($label))));
+        $crate::aluasm_inner! { item $code => $( $tt )* }
+    };
+    { item $code:ident => jif : $label:ident ; $($tt:tt)* } => {
+        $code.push(AsmItem::Jump(Jump::Jif, String::from(stringify!($label))));
+        $crate::aluasm_inner! { item $code => $( $tt )* }
+    };
+    { item $code:ident => routine : $label:ident ; $($tt:tt)* } => {
+        $code.push(AsmItem::Jump(Jump::Routine, String::from(stringify!($label))));
+        $crate::aluasm_inner! { item $code => $( $tt )* }
+    };
+    { item $code:ident => $label:ident : $($tt:tt)* } => {
+        $code.push(AsmItem::Label(String::from(stringify!($label))));
+        $crate::aluasm_inner! { item $code => $( $tt )* }
+    };
+    { $mode:ident $code:ident => $op:ident ; $($tt:tt)* } => {
+        $code.push($crate::aluasm_item!($mode $crate::instr!{ $op }));
+        $crate::aluasm_inner! { $mode $code => $( $tt )* }
+    };
+    { $mode:ident $code:ident => $op:ident $arg:literal @ $lib:ident ; $($tt:tt)* } => {
+        $code.push($crate::aluasm_item!($mode $crate::instr!{ $op $arg @ $lib }));
+        $crate::aluasm_inner! { $mode $code => $( $tt )* }
+    };
+    { $mode:ident $code:ident => $op:ident $arg:literal @ $lib:literal ; $($tt:tt)* } => {
+        $code.push($crate::aluasm_item!($mode $crate::instr!{ $op $arg @ $lib }));
+        $crate::aluasm_inner! { $mode $code => $( $tt )* }
+    };
+    { $mode:ident $code:ident => $op:ident $arg:ident @ $lib:ident ; $($tt:tt)* } => {
+        $code.push($crate::aluasm_item!($mode $crate::instr!{ $op $arg @ $lib }));
+        $crate::aluasm_inner! { $mode $code => $( $tt )* }
+    };
+    { $mode:ident $code:ident => $op:ident $( $arg:literal ),+ ; $($tt:tt)* } => {
+        $code.push($crate::aluasm_item!($mode $crate::instr!{ $op $( $arg ),+ }));
+        $crate::aluasm_inner! { $mode $code => $( $tt )* }
+    };
+    { $mode:ident $code:ident => $op:ident $( $arg:ident ),+ ; $($tt:tt)* } => {
+        $code.push($crate::aluasm_item!($mode $crate::instr!{ $op $( $arg ),+ }));
+        $crate::aluasm_inner! { $mode $code => $( $tt )* }
+    };
+    { $mode:ident $code:ident => $op:ident . $flag:ident $( $arg:ident ),+ ; $($tt:tt)* } => {
+        $code.push($crate::aluasm_item!($mode $crate::instr!{ $op . $flag $( $arg ),+ }));
+        $crate::aluasm_inner! { $mode $code => $( $tt )* }
+    };
+    { $mode:ident $code:ident => $op:ident $( $arg:ident [ $idx:literal ] ),+ ; $($tt:tt)* } => {
+        $code.push($crate::aluasm_item!($mode $crate::instr!{ $op $( $arg [ $idx ]  ),+ }));
+        $crate::aluasm_inner! { $mode $code => $( $tt )* }
+    };
+    { $mode:ident $code:ident => $op:ident . $flag:ident $( $arg:ident [ $idx:literal ] ),+ ; $($tt:tt)* } => {
+        $code.push($crate::aluasm_item!($mode $crate::instr!{ $op . $flag $( $arg [ $idx ]  ),+ }));
+        $crate::aluasm_inner! { $mode $code => $( $tt )* }
+    };
+    { $mode:ident $code:ident => $op:ident $arglit:literal, $( $arg:ident [ $idx:literal ] ),+ ; $($tt:tt)* } => {
+        $code.push($crate::aluasm_item!($mode $crate::instr!{ $op $arglit, $( $arg [ $idx ] ),+ }));
+        $crate::aluasm_inner! { $mode $code => $( $tt )* }
+    };
+    { $mode:ident $code:ident => $op:ident $arglit:ident, $( $arg:ident [ $idx:literal ] ),+ ; $($tt:tt)* } => {
+        $code.push($crate::aluasm_item!($mode $crate::instr!{ $op $arglit, $( $arg [ $idx ] ),+ }));
+        $crate::aluasm_inner! { $mode $code => $( $tt )* }
+    };
+    { $mode:ident $code:ident => $op:ident . $flag:ident $arglit:literal, $( $arg:ident [ $idx:literal ] ),+ ; $($tt:tt)* } => {
+        $code.push($crate::aluasm_item!($mode $crate::instr!{ $op . $flag $arglit, $( $arg [ $idx ] ),+ }));
+        $crate::aluasm_inner! { $mode $code => $( $tt )* }
+    };
+    { $mode:ident $code:ident => $op:ident $arglit1:literal, $arglit2:literal, $arg:ident [ $idx:literal ] ; $($tt:tt)* } => {
+        $code.push($crate::aluasm_item!($mode $crate::instr!{ $op $arglit1, $arglit2, $arg [ $idx ] }));
+        $crate::aluasm_inner! { $mode $code => $( $tt )* }
+    };
+    { $mode:ident $code:ident => $op:ident . $flag:ident $arglit1:literal, $arglit2:literal $arg:ident [ $idx:literal ] ; $($tt:tt)* } => {
+        $code.push($crate::aluasm_item!($mode $crate::instr!{ $op . $flag $arglit1, $arglit2, $arg [ $idx ] }));
+        $crate::aluasm_inner! { $mode $code => $( $tt )* }
+    };
+    { $mode:ident $code:ident => $op:ident $arg:ident [ $idx:literal ] , $arglit:literal ; $($tt:tt)* } => {
+        $code.push($crate::aluasm_item!($mode $crate::instr!{ $op $arg [ $idx ] , $arglit }));
+        $crate::aluasm_inner! { $mode $code => $( $tt )* }
+    };
+    { $mode:ident $code:ident => $op:ident $arg:ident [ $idx:literal ] , $arglit:ident ; $($tt:tt)* } => {
+        $code.push($crate::aluasm_item!($mode $crate::instr!{ $op $arg [ $idx ] , $arglit }));
+        $crate::aluasm_inner! { $mode $code => $( $tt )* }
+    };
+    { $mode:ident $code:ident => $op:ident . $flag:ident $arg:ident [ $idx:literal ], $arglit:expr ; $($tt:tt)* } => {
+        $code.push($crate::aluasm_item!($mode $crate::instr!{ $op . $flag $arg [ $idx ], $arglit }));
+        $crate::aluasm_inner! { $mode $code => $( $tt )* }
     };
 }
 
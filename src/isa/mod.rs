@@ -27,14 +27,19 @@
 
 #[macro_use]
 mod macros;
+pub mod asm;
+pub mod disasm;
 mod bytecode;
+mod cost;
 mod exec;
 mod flags;
 mod instr;
 pub mod opcodes;
 
 pub use bytecode::{Bytecode, BytecodeError};
-pub use exec::{ExecStep, InstructionSet};
+pub(crate) use bytecode::{CALL_LEN, ROUTINE_LEN};
+pub use cost::{CostModel, DefaultCost, SizedCost};
+pub use exec::{ExecFn, ExecStep, HaltReason, InstructionSet};
 pub use flags::{
     DeleteFlag, ExtendFlag, Flag, FloatEqFlag, InsertFlag, IntFlags, MergeFlag, NoneEqFlag,
     ParseFlagError, RoundingFlag, SignFlag, SplitFlag,
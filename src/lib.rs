@@ -138,9 +138,7 @@
 
 #![allow(clippy::bool_assert_comparison)]
 
-// TODO(#6) Complete string operations
 // TODO(#7) Complete assembly compiler for string operations
-// TODO(#8) Implement operations on Edwards curves
 
 #[cfg(not(any(feature = "alloc", feature = "std")))]
 compile_error!("either `alloc` or `std` feature must be used");
@@ -157,21 +155,28 @@ extern crate strict_encoding;
 extern crate serde_crate as serde;
 extern crate core;
 
+pub mod abi;
 pub mod data;
+pub mod debugger;
 #[macro_use]
 pub mod isa;
 pub mod library;
+pub mod observer;
 pub mod reg;
 #[cfg(feature = "stl")]
 pub mod stl;
+#[cfg(feature = "std")]
+pub mod trace;
+mod batch;
 mod vm;
 
+pub use batch::{Batch, Job, JobOutcome};
 pub use isa::Isa;
 #[cfg(feature = "ascii-armor")]
 pub use library::LibArmorError;
 #[doc(hidden)]
 pub use paste::paste;
-pub use vm::Vm;
+pub use vm::{ExecReport, LibPolicy, Vm, VmSnapshot};
 
 /// Struct types library name.
 pub const LIB_NAME_ALUVM: &str = "AluVM";
@@ -31,6 +31,11 @@ pub const CODE_SEGMENT_MAX_LEN: usize = 0xFFFF;
 
 pub const DATA_SEGMENT_MAX_LEN: usize = 0xFFFF;
 
+/// Maximum number of bytes which [`LoadedLib`](super::LoadedLib) may keep in memory for the
+/// pre-decoded instructions of a single library. Instructions past this limit are decoded each
+/// time they are executed.
+pub const LOADED_CACHE_MAX_SIZE: usize = 0x40_0000;
+
 /// Maximum number of libraries that may be referenced (used by) any other library; i.e. limit for
 /// the number of records inside program segment.
 pub const LIBS_SEGMENT_MAX_COUNT: usize = 0xFF;
@@ -26,6 +26,7 @@
 use core::convert::TryInto;
 #[cfg(feature = "std")]
 use core::fmt::{self, Debug, Display, Formatter};
+use core::mem;
 
 use amplify::num::{u1, u2, u24, u3, u4, u5, u6, u7};
 
@@ -48,6 +49,7 @@ where
     byte_pos: u16,
     data: D,
     libs: &'a LibSeg,
+    data_loaded: usize,
 }
 
 #[cfg(feature = "std")]
@@ -104,6 +106,7 @@ where
             bit_pos: u3::MIN,
             data: D::default(),
             libs,
+            data_loaded: 0,
         }
     }
 }
@@ -130,12 +133,17 @@ where
             bit_pos: u3::MIN,
             data,
             libs,
+            data_loaded: 0,
         }
     }
 
     /// Returns the current offset of the cursor
     pub const fn offset(&self) -> (u16, u3) { (self.byte_pos, self.bit_pos) }
 
+    /// Returns number of bytes of the values loaded from the data segment by the cursor, counting
+    /// numbers with their in-memory size.
+    pub(crate) const fn data_loaded(&self) -> usize { self.data_loaded }
+
     /// Converts writer into data segment
     #[inline]
     pub fn into_data_segment(self) -> D { self.data }
@@ -146,6 +154,18 @@ where
     fn read(&mut self, bit_count: u5) -> Result<u32, CodeEofError> {
         let mut ret = 0u32;
         let mut cnt = bit_count.to_u8();
+        // Fast path for reading whole bytes at a byte-aligned position
+        if self.bit_pos.to_u8() == 0 && cnt % 8 == 0 {
+            let start = self.byte_pos as usize;
+            let end = start + cnt as usize / 8;
+            if let Some(bytes) = self.as_ref().get(start..end) {
+                for (no, byte) in bytes.iter().enumerate() {
+                    ret |= (*byte as u32) << (no * 8);
+                }
+                self.byte_pos = end as u16;
+                return Ok(ret);
+            }
+        }
         while cnt > 0 {
             if self.is_eof() {
                 return Err(CodeEofError);
@@ -204,7 +224,7 @@ where
     fn write(&mut self, value: u32, bit_count: u5) -> Result<(), CodeEofError> {
         let mut cnt = bit_count.to_u8();
         let value = ((value as u64) << (self.bit_pos.to_u8())).to_le_bytes();
-        let n_bytes = (cnt + self.bit_pos.to_u8() + 7) / 8;
+        let n_bytes = (cnt + self.bit_pos.to_u8()).div_ceil(8);
         for i in 0..n_bytes {
             if self.is_eof() {
                 return Err(CodeEofError);
@@ -351,9 +371,10 @@ where
     fn read_data(&mut self) -> Result<(&[u8], bool), CodeEofError> {
         let offset = self.read_u16()? as usize;
         let end = offset + self.read_u16()? as usize;
-        let max = DATA_SEGMENT_MAX_LEN;
-        let st0 = end > self.data.as_ref().len();
-        let data = &self.data.as_ref()[offset.min(max)..end.min(max)];
+        let len = self.data.as_ref().len();
+        let st0 = end > len;
+        let data = &self.data.as_ref()[offset.min(len)..end.min(len)];
+        self.data_loaded += data.len();
         Ok((data, st0))
     }
 
@@ -363,6 +384,7 @@ where
         if end > self.data.as_ref().len() {
             return Err(CodeEofError);
         }
+        self.data_loaded += mem::size_of::<Number>();
         Ok(Number::with(&self.data.as_ref()[offset..end], reg.layout())
             .expect("read_number is broken"))
     }
@@ -507,6 +529,11 @@ mod tests {
         let bytes = 0b11101011_11110000_01110111;
         let mut cursor = Cursor::<_, ByteStr>::new(u32::to_le_bytes(bytes), &libseg);
         assert_eq!(cursor.read(u5::with(24)).unwrap(), bytes);
+
+        let mut cursor = Cursor::<_, ByteStr>::new([0x01, 0x02, 0x03], &libseg);
+        assert_eq!(cursor.read_u16().unwrap(), 0x0201);
+        assert_eq!(cursor.read_u8().unwrap(), 0x03);
+        assert!(cursor.is_eof());
     }
 
     #[test]
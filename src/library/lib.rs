@@ -23,6 +23,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#[cfg(feature = "alloc")]
+use alloc::collections::{BTreeMap, BTreeSet};
 #[cfg(feature = "alloc")]
 use alloc::string::{String, ToString};
 #[cfg(feature = "alloc")]
@@ -43,11 +45,16 @@ use strict_encoding::{StrictDeserialize, StrictSerialize};
 pub use self::_armor::LibArmorError;
 use super::{Cursor, Read, WriteError};
 use crate::data::ByteStr;
+use crate::isa::asm::AsmCode;
 #[cfg(feature = "std")]
-use crate::isa::{Bytecode, Instr};
-use crate::isa::{BytecodeError, ExecStep, InstructionSet};
-use crate::library::segs::IsaSeg;
+use crate::isa::Instr;
+use crate::isa::{
+    Bytecode, BytecodeError, CostModel, DefaultCost, ExecFn, ExecStep, HaltReason, InstructionSet,
+};
+use crate::library::constants::CODE_SEGMENT_MAX_LEN;
+use crate::library::segs::{IsaName, IsaSeg};
 use crate::library::{CodeEofError, LibSeg, SegmentError};
+use crate::observer::{DefaultObserver, Observer};
 use crate::reg::CoreRegs;
 use crate::LIB_NAME_ALUVM;
 
@@ -246,6 +253,23 @@ pub enum AssemblerError {
     /// Error assembling library segment
     #[from]
     LibSegOverflow(confinement::Error),
+
+    /// Label defined by the code item with the provided index is already defined by a previous
+    /// item
+    #[display("label defined by the code item #{0} is already defined")]
+    DuplicateLabel(usize),
+
+    /// Jump at the code item with the provided index refers to a label which is not defined
+    #[display("jump at the code item #{0} refers to an undefined label")]
+    UndefinedLabel(usize),
+
+    /// Jump at the code item with the provided index refers to a label which is not followed by
+    /// any instruction
+    #[display(
+        "jump at the code item #{0} refers to a label at the end of the code, where execution \
+         can't continue"
+    )]
+    LabelAtEnd(usize),
 }
 
 #[cfg(feature = "std")]
@@ -254,10 +278,46 @@ impl ::std::error::Error for AssemblerError {
         match self {
             AssemblerError::Bytecode(err) => Some(err),
             AssemblerError::LibSegOverflow(err) => Some(err),
+            AssemblerError::DuplicateLabel(_)
+            | AssemblerError::UndefinedLabel(_)
+            | AssemblerError::LabelAtEnd(_) => None,
         }
     }
 }
 
+/// Kinds of issues found by [`Lib::verify`] in the library bytecode
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Display)]
+#[display(doc_comments)]
+pub enum FindingKind {
+    /// code segment ends with a truncated instruction.
+    Truncated,
+
+    /// instruction can't be decoded (like when it references data outside of the data segment).
+    Undecodable,
+
+    /// jump to offset {0:#06X}, which is inside an instruction.
+    MisalignedJump(u16),
+
+    /// jump to offset {0:#06X}, which is not inside the code segment.
+    JumpOutOfCode(u16),
+
+    /// call into a library which is absent from the libraries segment.
+    UnknownLib,
+
+    /// instruction belongs to ISA extension {0}, which is not declared by the library.
+    UndeclaredIsa(IsaName),
+}
+
+/// Issue found by [`Lib::verify`] in the instruction located at a specific code offset
+#[derive(Clone, PartialEq, Eq, Hash, Debug, Display)]
+#[display("@x{pos:06X}: {kind}")]
+pub struct Finding {
+    /// Offset of the instruction in the code segment
+    pub pos: u16,
+    /// Description of the issue
+    pub kind: FindingKind,
+}
+
 impl Lib {
     /// Constructs library from raw data split into segments
     pub fn with(
@@ -277,24 +337,58 @@ impl Lib {
         })
     }
 
-    /// Assembles library from the provided instructions by encoding them into bytecode
-    pub fn assemble<Isa>(code: &[Isa]) -> Result<Lib, AssemblerError>
-    where Isa: InstructionSet {
-        let call_sites = code.iter().filter_map(|instr| instr.call_site()).map(|site| site.lib);
+    /// Assembles library from the provided instructions by encoding them into bytecode.
+    ///
+    /// The code may be either a list of instructions or a list of [`AsmItem`]s, which may also
+    /// contain labels. The assembly is done in two passes: the first one encodes instructions and
+    /// records label offsets, using placeholder offsets for jumps to labels; the second one
+    /// patches the jumps with the resolved label offsets.
+    ///
+    /// [`AsmItem`]: crate::isa::asm::AsmItem
+    pub fn assemble<Code>(code: &[Code]) -> Result<Lib, AssemblerError>
+    where Code: AsmCode {
+        let call_sites = code
+            .iter()
+            .filter_map(Code::instr)
+            .filter_map(|instr| instr.call_site())
+            .map(|site| site.lib);
         let libs_segment = LibSeg::try_from_iter(call_sites)?;
 
-        let mut code_segment = ByteStr::default();
-        let mut writer = Cursor::<_, ByteStr>::new(&mut code_segment.bytes[..], &libs_segment);
-        for instr in code.iter() {
-            instr.encode(&mut writer)?;
+        let mut code_segment = vec![0u8; CODE_SEGMENT_MAX_LEN];
+        let mut writer = Cursor::<_, ByteStr>::new(&mut code_segment[..], &libs_segment);
+        let mut labels = BTreeMap::new();
+        let mut jumps = Vec::new();
+        for (idx, item) in code.iter().enumerate() {
+            if let Some(label) = item.label() {
+                if labels.insert(label, writer.pos()).is_some() {
+                    return Err(AssemblerError::DuplicateLabel(idx));
+                }
+            }
+            if let Some((jump, label)) = item.jump() {
+                jumps.push((idx, writer.pos(), jump, label));
+                jump.to_op(0).encode(&mut writer)?;
+            }
+            if let Some(instr) = item.instr() {
+                instr.encode(&mut writer)?;
+            }
         }
         let pos = writer.pos();
+
+        // Jump operations have a fixed size, so patching them does not move other code
+        for (idx, at, jump, label) in jumps {
+            let target = *labels.get(label).ok_or(AssemblerError::UndefinedLabel(idx))?;
+            if target >= pos {
+                return Err(AssemblerError::LabelAtEnd(idx));
+            }
+            writer.seek(at).expect("jump offset lies within the written code");
+            jump.to_op(target).encode(&mut writer)?;
+        }
         let data_segment = SmallBlob::from_checked(writer.into_data_segment().to_vec());
-        code_segment.adjust_len(pos);
-        let code_segment = SmallBlob::from_checked(code_segment.to_vec());
+        code_segment.truncate(pos as usize);
+        let code_segment = SmallBlob::from_checked(code_segment);
 
         Ok(Lib {
-            isae: Isa::isa_ids(),
+            isae: Code::Isa::isa_ids(),
             libs: libs_segment,
             code: code_segment,
             data: data_segment,
@@ -312,7 +406,87 @@ impl Lib {
         Ok(code)
     }
 
+    /// Verifies library bytecode by decoding the whole code segment. Checks that:
+    /// - the code segment does not end with a truncated instruction;
+    /// - all instructions can be decoded;
+    /// - all jumps target the start of an instruction;
+    /// - all calls into external libraries reference libraries present in the libraries segment;
+    /// - all instructions belong to the ISA extensions declared by the library.
+    ///
+    /// # Errors
+    ///
+    /// Returns list of all found issues, ordered by their code offset.
+    pub fn verify<Isa>(&self) -> Result<(), Vec<Finding>>
+    where Isa: InstructionSet {
+        let mut findings = Vec::new();
+        let mut offsets = BTreeSet::new();
+        let mut jumps = Vec::new();
+        let mut reader = Cursor::with(&self.code, &self.data, &self.libs);
+        while !reader.is_eof() {
+            let pos = reader.pos();
+            let Ok(instr) = Isa::decode(&mut reader) else {
+                let kind = match reader.is_eof() {
+                    true => FindingKind::Truncated,
+                    false => FindingKind::Undecodable,
+                };
+                findings.push(Finding { pos, kind });
+                break;
+            };
+            offsets.insert(pos);
+            if let Some(target) = instr.jump_target() {
+                jumps.push((pos, target));
+            }
+            if let Some(site) = instr.call_site() {
+                if self.libs.index(site.lib).is_none() {
+                    findings.push(Finding {
+                        pos,
+                        kind: FindingKind::UnknownLib,
+                    });
+                }
+            }
+            for isa in instr.isa_ext() {
+                if !self.isae.contains(&isa) {
+                    findings.push(Finding {
+                        pos,
+                        kind: FindingKind::UndeclaredIsa(isa),
+                    });
+                }
+            }
+        }
+
+        let end = self.code.len() as u16;
+        for (pos, target) in jumps {
+            // Execution can't continue from the end of the code segment, so jumping there fails
+            let kind = if target >= end {
+                FindingKind::JumpOutOfCode(target)
+            } else if !offsets.contains(&target) {
+                FindingKind::MisalignedJump(target)
+            } else {
+                continue;
+            };
+            findings.push(Finding { pos, kind });
+        }
+        findings.sort_by_key(|finding| finding.pos);
+
+        if findings.is_empty() {
+            Ok(())
+        } else {
+            Err(findings)
+        }
+    }
+
+    /// Decodes a single instruction located at the `pos` offset of the code segment.
+    pub fn decode_at<Isa>(&self, pos: u16) -> Result<Isa, CodeEofError>
+    where Isa: InstructionSet {
+        let mut reader = Cursor::with(&self.code, &self.data, &self.libs);
+        reader.seek(pos)?;
+        Isa::decode(&mut reader)
+    }
+
     /// Disassembles library into a set of instructions and offsets and prints it to the writer.
+    ///
+    /// The output is a listing which can't be assembled back; use
+    /// [`disasm::disassemble`](crate::isa::disasm::disassemble) to get assembly text with labels.
     #[cfg(feature = "std")]
     pub fn print_disassemble<Isa>(
         &self,
@@ -362,7 +536,9 @@ impl Lib {
     ///
     /// # Returns
     ///
-    /// Location for the external code jump, if any
+    /// Location for the external code jump, if any. Both halting and suspending the execution by
+    /// an instruction yielding to the host result in `None`; use [`Lib::run`] to distinguish
+    /// between them.
     pub fn exec<Isa>(
         &self,
         entrypoint: u16,
@@ -372,116 +548,346 @@ impl Lib {
     where
         Isa: InstructionSet,
     {
-        #[cfg(feature = "log")]
-        let (m, w, d, g, r, y, z) = (
-            "\x1B[0;35m",
-            "\x1B[1;1m",
-            "\x1B[0;37;2m",
-            "\x1B[0;32m",
-            "\x1B[0;31m",
-            "\x1B[0;33m",
-            "\x1B[0m",
-        );
-
-        let mut cursor = Cursor::with(&self.code, &self.data, &self.libs);
-        let lib_id = self.id();
+        let observer = &mut DefaultObserver::default();
+        match self.run::<Isa>(entrypoint, registers, context, &DefaultCost, observer) {
+            LibExit::Call(site) => Some(site),
+            LibExit::Halt(..) | LibExit::Yield(..) => None,
+        }
+    }
 
-        #[cfg(feature = "log")]
-        let lib_mnemonic = lib_id.to_baid64_mnemonic();
-        #[cfg(feature = "log")]
-        let lib_ref = lib_mnemonic.split_at(5).0;
+    /// Executes library code starting at entrypoint, reporting the reason for which the execution
+    /// of this library has ended. Instruction complexity is accounted using the provided cost
+    /// schedule; each execution step is reported to the observer.
+    pub fn run<Isa>(
+        &self,
+        entrypoint: u16,
+        registers: &mut CoreRegs,
+        context: &Isa::Context<'_>,
+        cost: &impl CostModel<Isa>,
+        observer: &mut impl Observer<Isa>,
+    ) -> LibExit
+    where
+        Isa: InstructionSet,
+    {
+        self.run_with_id(self.id(), entrypoint, registers, context, cost, observer)
+    }
 
-        if cursor.seek(entrypoint).is_err() {
-            registers.st0 = false;
-            #[cfg(feature = "log")]
-            eprintln!("jump to non-existing offset; halting, {d}st0{z} is set to {r}false{z}");
-            return None;
-        }
+    /// Executes a single instruction of the library code located at `pos` offset.
+    ///
+    /// # Returns
+    ///
+    /// [`LibExit::Call`] with the location of the next instruction to execute (in this or other
+    /// library), [`LibExit::Halt`] if the execution has halted, or [`LibExit::Yield`] if the
+    /// execution is suspended by the instruction.
+    pub fn step<Isa>(
+        &self,
+        pos: u16,
+        registers: &mut CoreRegs,
+        context: &Isa::Context<'_>,
+        cost: &impl CostModel<Isa>,
+        observer: &mut impl Observer<Isa>,
+    ) -> LibExit
+    where
+        Isa: InstructionSet,
+    {
+        self.step_with_id(self.id(), pos, registers, context, cost, observer)
+    }
+}
 
-        #[cfg(feature = "log")]
-        let mut st0 = registers.st0;
+impl AsRef<Lib> for Lib {
+    #[inline]
+    fn as_ref(&self) -> &Lib { self }
+}
 
-        while !cursor.is_eof() {
-            let pos = cursor.pos();
+/// Library code which can be executed by the virtual machine: either a [`Lib`], or a
+/// [`LoadedLib`](super::LoadedLib) keeping its instructions pre-decoded.
+pub trait ExecLib<Isa>: AsRef<Lib>
+where Isa: InstructionSet
+{
+    /// Returns id of the library. Computes the library code hash unless the id is cached.
+    #[inline]
+    fn lib_id(&self) -> LibId { self.as_ref().id() }
 
-            let instr = Isa::decode(&mut cursor).ok()?;
+    /// Same as [`Lib::run`], but using already known library id, which saves on computing the
+    /// library code hash. The id must match the id of this library.
+    fn run_with_id(
+        &self,
+        lib_id: LibId,
+        entrypoint: u16,
+        registers: &mut CoreRegs,
+        context: &Isa::Context<'_>,
+        cost: &impl CostModel<Isa>,
+        observer: &mut impl Observer<Isa>,
+    ) -> LibExit;
 
-            #[cfg(feature = "log")]
-            {
-                eprint!("{m}{}@x{pos:06X}:{z} {: <32}; ", lib_ref, instr.to_string());
-                for reg in instr.src_regs() {
-                    let val = registers.get(reg);
-                    eprint!("{d}{reg}={z}{w}{val}{z} ");
-                }
-            }
+    /// Same as [`Lib::step`], but using already known library id, which saves on computing the
+    /// library code hash. The id must match the id of this library.
+    fn step_with_id(
+        &self,
+        lib_id: LibId,
+        pos: u16,
+        registers: &mut CoreRegs,
+        context: &Isa::Context<'_>,
+        cost: &impl CostModel<Isa>,
+        observer: &mut impl Observer<Isa>,
+    ) -> LibExit;
 
-            let next = instr.exec(registers, LibSite::with(pos, lib_id), context);
+    /// Returns instruction located at the `pos` offset of the code segment.
+    fn instr_at(&self, pos: u16) -> Result<Isa, CodeEofError>;
+}
 
-            #[cfg(feature = "log")]
-            {
-                eprint!("-> ");
-                for reg in instr.dst_regs() {
-                    let val = registers.get(reg);
-                    eprint!("{g}{reg}={y}{val}{z} ");
+impl<Isa> ExecLib<Isa> for Lib
+where Isa: InstructionSet
+{
+    fn run_with_id(
+        &self,
+        lib_id: LibId,
+        entrypoint: u16,
+        registers: &mut CoreRegs,
+        context: &Isa::Context<'_>,
+        cost: &impl CostModel<Isa>,
+        observer: &mut impl Observer<Isa>,
+    ) -> LibExit {
+        let code_len = self.code.len();
+        let mut cursor = Cursor::with(&self.code, &self.data, &self.libs);
+        let exit = if cursor.seek(entrypoint).is_err() {
+            registers.st0 = false;
+            LibExit::Halt(LibSite::with(entrypoint, lib_id), HaltReason::InvalidJump)
+        } else {
+            loop {
+                if cursor.is_eof() {
+                    break LibExit::Halt(
+                        LibSite::with(cursor.pos(), lib_id),
+                        HaltReason::EndOfCode,
+                    );
                 }
-                if st0 != registers.st0 {
-                    let c = if registers.st0 { g } else { r };
-                    eprint!(" {d}st0={z}{c}{}{z} ", registers.st0);
+                if let Some(exit) =
+                    exec_instr(&mut cursor, code_len, lib_id, registers, context, cost, observer)
+                {
+                    break exit;
                 }
-
-                st0 = registers.st0;
             }
+        };
+        notify_exit(exit, registers, observer);
+        exit
+    }
 
-            if !registers.acc_complexity(instr) {
-                #[cfg(feature = "log")]
-                eprintln!("complexity overflow");
-                return None;
-            }
-            match next {
-                ExecStep::Stop => {
-                    #[cfg(feature = "log")]
-                    {
-                        let c = if registers.st0 { g } else { r };
-                        eprintln!("execution stopped; {d}st0={z}{c}{}{z}", registers.st0);
-                    }
-                    return None;
-                }
-                ExecStep::Fail => {
-                    registers.st0 = false;
-                    assert_eq!(registers.st0, false);
-                    #[cfg(feature = "log")]
-                    eprintln!("halting, {d}st0{z} is set to {r}false{z}");
-                    return None;
-                }
-                ExecStep::Next => {
-                    #[cfg(feature = "log")]
-                    eprintln!();
-                    continue;
-                }
-                ExecStep::Jump(pos) => {
-                    #[cfg(feature = "log")]
-                    eprintln!("{}", pos);
-                    if cursor.seek(pos).is_err() {
-                        registers.st0 = false;
-                        #[cfg(feature = "log")]
-                        eprintln!(
-                            "jump to non-existing offset; halting, {d}st0{z} is set to {r}false{z}"
-                        );
-                        return None;
-                    }
-                }
-                ExecStep::Call(site) => {
-                    #[cfg(feature = "log")]
-                    eprintln!("{}", site);
-                    return Some(site);
+    fn step_with_id(
+        &self,
+        lib_id: LibId,
+        pos: u16,
+        registers: &mut CoreRegs,
+        context: &Isa::Context<'_>,
+        cost: &impl CostModel<Isa>,
+        observer: &mut impl Observer<Isa>,
+    ) -> LibExit {
+        let code_len = self.code.len();
+        let mut cursor = Cursor::with(&self.code, &self.data, &self.libs);
+        let exit = if cursor.seek(pos).is_err() {
+            registers.st0 = false;
+            LibExit::Halt(LibSite::with(pos, lib_id), HaltReason::InvalidJump)
+        } else {
+            match exec_instr(&mut cursor, code_len, lib_id, registers, context, cost, observer) {
+                Some(exit) => exit,
+                None if cursor.is_eof() => {
+                    LibExit::Halt(LibSite::with(cursor.pos(), lib_id), HaltReason::EndOfCode)
                 }
+                None => LibExit::Call(LibSite::with(cursor.pos(), lib_id)),
             }
+        };
+        notify_exit(exit, registers, observer);
+        exit
+    }
+
+    #[inline]
+    fn instr_at(&self, pos: u16) -> Result<Isa, CodeEofError> { self.decode_at(pos) }
+}
+
+impl<Isa, L> ExecLib<Isa> for &L
+where
+    Isa: InstructionSet,
+    L: ExecLib<Isa>,
+{
+    #[inline]
+    fn run_with_id(
+        &self,
+        lib_id: LibId,
+        entrypoint: u16,
+        registers: &mut CoreRegs,
+        context: &Isa::Context<'_>,
+        cost: &impl CostModel<Isa>,
+        observer: &mut impl Observer<Isa>,
+    ) -> LibExit {
+        (*self).run_with_id(lib_id, entrypoint, registers, context, cost, observer)
+    }
+
+    #[inline]
+    fn step_with_id(
+        &self,
+        lib_id: LibId,
+        pos: u16,
+        registers: &mut CoreRegs,
+        context: &Isa::Context<'_>,
+        cost: &impl CostModel<Isa>,
+        observer: &mut impl Observer<Isa>,
+    ) -> LibExit {
+        (*self).step_with_id(lib_id, pos, registers, context, cost, observer)
+    }
+
+    #[inline]
+    fn instr_at(&self, pos: u16) -> Result<Isa, CodeEofError> { (*self).instr_at(pos) }
+}
+
+/// Reports the reason for leaving the library code to the observer.
+pub(super) fn notify_exit<Isa>(
+    exit: LibExit,
+    registers: &CoreRegs,
+    observer: &mut impl Observer<Isa>,
+) where
+    Isa: InstructionSet,
+{
+    match exit {
+        LibExit::Halt(site, reason) => observer.on_halt(site, reason, registers),
+        LibExit::Yield(site, request) => observer.on_yield(site, request, registers),
+        LibExit::Call(_) => {}
+    }
+}
+
+/// Decodes and executes a single instruction at the cursor position, moving the cursor to the
+/// next instruction which has to be executed within the same library.
+///
+/// # Returns
+///
+/// `None` if the execution must continue from the new cursor position; otherwise the reason for
+/// leaving the library code.
+fn exec_instr<Isa>(
+    cursor: &mut Cursor<&SmallBlob, &SmallBlob>,
+    code_len: usize,
+    lib_id: LibId,
+    registers: &mut CoreRegs,
+    context: &Isa::Context<'_>,
+    cost: &impl CostModel<Isa>,
+    observer: &mut impl Observer<Isa>,
+) -> Option<LibExit>
+where
+    Isa: InstructionSet,
+{
+    let site = LibSite::with(cursor.pos(), lib_id);
+
+    let Ok(instr) = Isa::decode(cursor) else {
+        registers.st0 = false;
+        return Some(LibExit::Halt(site, HaltReason::DecodeError));
+    };
+
+    let next_pos = cursor.pos();
+    match exec_decoded(
+        &instr,
+        Isa::exec,
+        site,
+        next_pos,
+        code_len,
+        registers,
+        context,
+        cost,
+        observer,
+    ) {
+        Ok(None) => None,
+        Ok(Some(pos)) => {
+            cursor.seek(pos).expect("jump target is checked against the code length");
+            None
         }
+        Err(exit) => Some(exit),
+    }
+}
+
+/// Executes a single already decoded instruction located at `site` with `exec` function, which
+/// must have the same effect as [`InstructionSet::exec`]. The instruction is followed by the next
+/// instruction at `next_pos` offset of the code segment having `code_len` bytes.
+///
+/// # Returns
+///
+/// `Ok(None)` if the execution must continue from the next instruction, `Ok(Some(pos))` if the
+/// execution must continue from the valid `pos` offset within the same library; otherwise the
+/// reason for leaving the library code.
+#[allow(clippy::too_many_arguments)]
+pub(super) fn exec_decoded<Isa>(
+    instr: &Isa,
+    exec: ExecFn<Isa>,
+    site: LibSite,
+    next_pos: u16,
+    code_len: usize,
+    registers: &mut CoreRegs,
+    context: &Isa::Context<'_>,
+    cost: &impl CostModel<Isa>,
+    observer: &mut impl Observer<Isa>,
+) -> Result<Option<u16>, LibExit>
+where
+    Isa: InstructionSet,
+{
+    observer.before_instr(site, instr, registers);
+
+    let st0 = registers.st0;
+    let depth = registers.call_depth();
+    let instr_cost = cost.cost(instr, registers);
+    let next = exec(instr, registers, site, context);
+    let within_limit = registers.acc_cost(instr_cost);
 
-        None
+    observer.after_instr(site, instr, registers, st0, next);
+
+    let target = match next {
+        ExecStep::Jump(pos) => Some(LibSite::with(pos, site.lib)),
+        ExecStep::Call(target) => Some(target),
+        ExecStep::Stop
+        | ExecStep::Fail
+        | ExecStep::Halt(_)
+        | ExecStep::Next
+        | ExecStep::Yield(_) => None,
+    };
+    match (target, registers.call_depth().cmp(&depth)) {
+        (Some(target), Ordering::Greater) => observer.on_call(site, target),
+        (Some(target), Ordering::Less) => observer.on_return(site, target),
+        _ => {}
+    }
+
+    if !within_limit {
+        observer.on_complexity_overflow(site, registers);
+        return Err(LibExit::Halt(site, HaltReason::ComplexityLimit));
+    }
+    match next {
+        ExecStep::Stop => Err(LibExit::Halt(site, HaltReason::Stop)),
+        ExecStep::Fail => {
+            registers.st0 = false;
+            Err(LibExit::Halt(site, HaltReason::Fail))
+        }
+        ExecStep::Halt(reason) => {
+            registers.st0 = false;
+            Err(LibExit::Halt(site, reason))
+        }
+        ExecStep::Next => Ok(None),
+        ExecStep::Jump(pos) if pos as usize >= code_len => {
+            registers.st0 = false;
+            Err(LibExit::Halt(site, HaltReason::InvalidJump))
+        }
+        ExecStep::Jump(pos) => Ok(Some(pos)),
+        ExecStep::Call(site) => Err(LibExit::Call(site)),
+        ExecStep::Yield(request) => Err(LibExit::Yield(LibSite::with(next_pos, site.lib), request)),
     }
 }
 
+/// Result of the execution of a library code by [`Lib::run`]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum LibExit {
+    /// Execution must continue at the provided location in other (or the same) library
+    Call(LibSite),
+
+    /// Execution has halted at the provided location for the given reason
+    Halt(LibSite, HaltReason),
+
+    /// Execution is suspended by an instruction with a host request; it may be resumed from the
+    /// provided location
+    Yield(LibSite, u16),
+}
+
 /// Location within a library
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default, Display)]
 #[derive(StrictType, StrictDecode)]
@@ -503,9 +909,237 @@ impl LibSite {
     pub fn with(pos: u16, lib: LibId) -> LibSite { LibSite { lib, pos } }
 }
 
+/// Errors parsing [`LibSite`] from a string
+#[derive(Debug, Display, From)]
+#[cfg_attr(feature = "std", derive(Error))]
+#[display(doc_comments)]
+pub enum LibSiteParseError {
+    /// library site `{0}` must have `<offset> @ <library id>` form.
+    Format(String),
+
+    /// invalid library site offset `{0}`.
+    Offset(String),
+
+    /// invalid library id. Details: {0}
+    #[from]
+    LibId(Baid64ParseError),
+}
+
+impl FromStr for LibSite {
+    type Err = LibSiteParseError;
+
+    /// Parses library site from `<offset> @ <library id>` string, where the offset is either a
+    /// decimal or a `0x`-prefixed hexadecimal number.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (pos, lib) =
+            s.split_once('@').ok_or_else(|| LibSiteParseError::Format(s.to_string()))?;
+        let (pos, lib) = (pos.trim(), lib.trim());
+        let offset = match pos.strip_prefix("0x").or_else(|| pos.strip_prefix("0X")) {
+            Some(hex) => u16::from_str_radix(hex, 16),
+            None => u16::from_str(pos),
+        };
+        let pos = offset.map_err(|_| LibSiteParseError::Offset(pos.to_string()))?;
+        Ok(LibSite::with(pos, LibId::from_str(lib)?))
+    }
+}
+
 #[cfg(test)]
 mod test {
+    use core::ops::RangeInclusive;
+
     use super::*;
+    use crate::data::Number;
+    use crate::isa::{
+        ArithmeticOp, ControlFlowOp, DigestOp, Instr, ReservedOp, CALL_LEN, ROUTINE_LEN,
+    };
+    use crate::library::Write;
+    use crate::reg::{NumericRegister, Reg, Reg16, Reg32, RegA, RegAFR, RegF, RegR, RegS};
+
+    type Isa = Instr<ReservedOp>;
+
+    fn findings(lib: &Lib) -> Vec<(u16, FindingKind)> {
+        lib.verify::<Isa>()
+            .unwrap_err()
+            .into_iter()
+            .map(|finding| (finding.pos, finding.kind))
+            .collect()
+    }
+
+    /// Deterministic xorshift generator, so the failures are reproducible.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next_u64(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn next_u8(&mut self) -> u8 { self.next_u64() as u8 }
+
+        fn bytes(&mut self, len: usize) -> Vec<u8> { (0..len).map(|_| self.next_u8()).collect() }
+
+        /// Produces either a small value (useful as an offset or a shift) or a fully random one.
+        fn number(&mut self, reg: impl NumericRegister) -> Option<Number> {
+            let mut buf = vec![0u8; reg.bytes() as usize];
+            match self.next_u8() % 4 {
+                0 => return None,
+                1 => buf[0] = self.next_u8() % 80,
+                _ => buf.iter_mut().for_each(|byte| *byte = self.next_u8()),
+            }
+            Number::with(buf, reg.layout())
+        }
+
+        fn regs(&mut self) -> CoreRegs {
+            let mut regs = CoreRegs::default();
+            for idx in Reg32::ALL {
+                for reg in RegA::ALL {
+                    regs.set_n(reg, idx, self.number(reg));
+                }
+                for reg in RegF::ALL {
+                    regs.set_n(reg, idx, self.number(reg));
+                }
+                for reg in RegR::ALL {
+                    regs.set_n(reg, idx, self.number(reg));
+                }
+            }
+            for idx in 0..16u8 {
+                if self.next_u8() % 4 != 0 {
+                    let len = self.next_u8() % 64;
+                    regs.set_s16(idx, ByteStr::with(self.bytes(len as usize)));
+                }
+            }
+            regs
+        }
+    }
+
+    /// Compares all general-purpose and string registers; formatting all registers for the
+    /// comparison is much slower than executing
+    fn assert_same_regs(left: &CoreRegs, right: &CoreRegs, msg: impl Display) {
+        assert_eq!(left.st0, right.st0, "{msg}");
+        assert_eq!(left.call_depth(), right.call_depth(), "{msg}");
+        for reg in RegA::ALL
+            .map(RegAFR::A)
+            .into_iter()
+            .chain(RegF::ALL.map(RegAFR::F))
+            .chain(RegR::ALL.map(RegAFR::R))
+        {
+            for idx in Reg32::ALL {
+                assert_eq!(left.get_n(reg, idx), right.get_n(reg, idx), "{msg}");
+            }
+        }
+        for idx in 0..16u8 {
+            assert_eq!(left.s16(idx), right.s16(idx), "{msg}");
+        }
+    }
+
+    /// Whether the instruction fails whichever register state it is executed on. Curve operations
+    /// with their feature disabled are decoded as extension codes.
+    fn always_fails(instr: &Isa) -> bool {
+        matches!(
+            instr,
+            Instr::ReservedInstruction(_)
+                | Instr::ExtensionCodes(_)
+                | Instr::Arithmetic(
+                    ArithmeticOp::AddF(_, RegF::F512, _, _)
+                        | ArithmeticOp::SubF(_, RegF::F512, _, _)
+                        | ArithmeticOp::MulF(_, RegF::F512, _, _)
+                        | ArithmeticOp::DivF(_, RegF::F512, _, _)
+                )
+        )
+    }
+
+    /// Decodes and executes every opcode followed by random operands and random register state;
+    /// must never panic whichever features are enabled, must fail on reserved and unsupported
+    /// operations and must be deterministic.
+    #[test]
+    fn exec_arbitrary_bytecode() {
+        let mut rng = Rng(0x0123_4567_89AB_CDEF);
+        let isae = Instr::<ReservedOp>::isa_ids().to_string();
+        // Filling in and cloning registers is expensive, so we pick states from a pre-generated
+        // pool and run several programs over each of them
+        let pool = (0..64).map(|_| rng.regs()).collect::<Vec<_>>();
+        // Logging formats all touched registers on each step, which is orders of magnitude slower
+        let runs = if cfg!(feature = "log") { 2 } else { 16 };
+        for opcode in 0..=u8::MAX {
+            for _ in 0..4 {
+                let mut regs = pool[rng.next_u8() as usize % pool.len()].clone();
+                for _ in 0..runs {
+                    let mut code = vec![opcode];
+                    code.extend(rng.bytes(32));
+                    let data = rng.bytes(256);
+                    let lib = Lib::with(&isae, code, data, none!()).unwrap();
+                    lib.exec::<Isa>(0, &mut regs, &());
+
+                    let Ok(instr) = lib.decode_at::<Isa>(0) else {
+                        continue;
+                    };
+                    if always_fails(&instr) {
+                        let lib = Lib::assemble(core::slice::from_ref(&instr)).unwrap();
+                        regs.st0 = true;
+                        lib.exec::<Isa>(0, &mut regs, &());
+                        assert!(!regs.st0, "{instr}");
+                    }
+                }
+            }
+
+            let mut code = vec![opcode];
+            code.extend(rng.bytes(32));
+            let lib = Lib::with(&isae, code, rng.bytes(256), none!()).unwrap();
+            let mut regs = pool[rng.next_u8() as usize % pool.len()].clone();
+            let mut replay = regs.clone();
+            let exit = lib.exec::<Isa>(0, &mut regs, &());
+            assert_eq!(lib.exec::<Isa>(0, &mut replay, &()), exit, "opcode {opcode:#04x}");
+            assert_same_regs(&replay, &regs, format_args!("opcode {opcode:#04x}"));
+        }
+    }
+
+    /// Executes every opcode followed by random operands with both [`InstructionSet::exec`] and
+    /// the function selected by [`InstructionSet::exec_fn`]; they must have the same effect.
+    #[test]
+    fn exec_fn_same_as_exec() {
+        let mut rng = Rng(0xFEDC_BA98_7654_3210);
+        let isae = Instr::<ReservedOp>::isa_ids().to_string();
+        let pool = (0..64).map(|_| rng.regs()).collect::<Vec<_>>();
+        for opcode in 0..=u8::MAX {
+            for _ in 0..32 {
+                let mut code = vec![opcode];
+                code.extend(rng.bytes(32));
+                let lib = Lib::with(&isae, code, rng.bytes(256), none!()).unwrap();
+                let Ok(instr) = lib.decode_at::<Isa>(0) else {
+                    continue;
+                };
+                let site = LibSite::with(0, lib.id());
+                let mut regs = pool[rng.next_u8() as usize % pool.len()].clone();
+                let mut fn_regs = regs.clone();
+                let step = instr.exec(&mut regs, site, &());
+                assert_eq!((instr.exec_fn())(&instr, &mut fn_regs, site, &()), step, "{instr}");
+                assert_same_regs(&fn_regs, &regs, instr);
+            }
+        }
+    }
+
+    #[test]
+    fn nop_encoding() {
+        let lib = Lib::assemble::<Instr>(&[Instr::Nop]).unwrap();
+        assert_eq!(lib.code_segment(), &[0x01]);
+
+        let isae = Instr::<ReservedOp>::isa_ids().to_string();
+        let lib = Lib::with(&isae, vec![0xFF, 0xFF], none!(), none!()).unwrap();
+        assert_eq!(lib.decode_at::<Instr>(1), Ok(Instr::Nop));
+        assert_eq!(lib.disassemble::<Instr>(), Ok(vec![Instr::Nop, Instr::Nop]));
+    }
+
+    #[test]
+    fn call_encoding() {
+        let callee = Lib::assemble::<Instr>(&[Instr::Nop]).unwrap();
+        let lib = Lib::assemble::<Instr>(&[Instr::ControlFlow(ControlFlowOp::Routine(0))]).unwrap();
+        assert_eq!(lib.code_segment().len(), ROUTINE_LEN as usize);
+        let call = ControlFlowOp::Call(LibSite::with(0, callee.id()));
+        let lib = Lib::assemble::<Instr>(&[Instr::ControlFlow(call)]).unwrap();
+        assert_eq!(lib.code_segment().len(), CALL_LEN as usize);
+    }
 
     #[test]
     fn lib_id_display() {
@@ -541,4 +1175,116 @@ mod test {
 
         assert_eq!(id, LibId::from_str("650XHPmhWpXWR5RUz4B5jXjeDqcyrHXpdZxYaX9gfO4").unwrap());
     }
+
+    #[test]
+    fn verify_valid() {
+        let code: [Isa; 3] = [
+            Instr::ControlFlow(ControlFlowOp::Jif(6)),
+            Instr::ControlFlow(ControlFlowOp::Jmp(0)),
+            Instr::ControlFlow(ControlFlowOp::Fail),
+        ];
+        let lib = Lib::assemble(&code).unwrap();
+        assert_eq!(lib.verify::<Isa>(), Ok(()));
+    }
+
+    #[test]
+    fn verify_truncated() {
+        let lib = Lib::assemble::<Isa>(&[
+            Instr::ControlFlow(ControlFlowOp::Ret),
+            Instr::ControlFlow(ControlFlowOp::Jmp(0)),
+        ])
+        .unwrap();
+        let mut code = lib.code.to_vec();
+        code.pop();
+        let lib = Lib::with(&lib.isae_segment(), code, vec![], none!()).unwrap();
+        assert_eq!(findings(&lib), vec![(1, FindingKind::Truncated)]);
+    }
+
+    #[test]
+    fn verify_jumps() {
+        let code: [Isa; 3] = [
+            Instr::ControlFlow(ControlFlowOp::Jmp(1)),
+            Instr::ControlFlow(ControlFlowOp::Routine(100)),
+            Instr::ControlFlow(ControlFlowOp::Jif(9)),
+        ];
+        let lib = Lib::assemble(&code).unwrap();
+        assert_eq!(findings(&lib), vec![
+            (0, FindingKind::MisalignedJump(1)),
+            (3, FindingKind::JumpOutOfCode(100)),
+            (6, FindingKind::JumpOutOfCode(9)),
+        ]);
+    }
+
+    /// Extension instruction loading `a8` value from the data segment, which fails to decode if
+    /// the value is outside of the data segment
+    #[derive(Copy, Clone, PartialEq, Eq, Debug, Display)]
+    #[display("load")]
+    struct LoadOp;
+
+    impl Bytecode for LoadOp {
+        fn instr_range() -> RangeInclusive<u8> { 0b11_000_000..=0b11_000_000 }
+
+        fn instr_byte(&self) -> u8 { 0b11_000_000 }
+
+        fn encode_args<W>(&self, writer: &mut W) -> Result<(), BytecodeError>
+        where W: Write {
+            writer.write_u16(0u16)?;
+            Ok(())
+        }
+
+        fn decode<R>(reader: &mut R) -> Result<Self, CodeEofError>
+        where R: Read {
+            reader.read_u8()?;
+            reader.read_number(RegA::A8)?;
+            Ok(LoadOp)
+        }
+    }
+
+    impl InstructionSet for LoadOp {
+        type Context<'ctx> = ();
+
+        fn isa_ids() -> IsaSeg { IsaSeg::default() }
+
+        fn src_regs(&self) -> BTreeSet<Reg> { none!() }
+
+        fn dst_regs(&self) -> BTreeSet<Reg> { none!() }
+
+        fn complexity(&self) -> u64 { 1 }
+
+        fn exec(&self, _: &mut CoreRegs, _: LibSite, _: &()) -> ExecStep { ExecStep::Next }
+    }
+
+    #[test]
+    fn verify_undecodable() {
+        let code = [Instr::ExtensionCodes(LoadOp), Instr::ControlFlow(ControlFlowOp::Ret)];
+        let lib = Lib::assemble(&code).unwrap();
+        assert_eq!(lib.verify::<Instr<LoadOp>>().unwrap_err(), vec![Finding {
+            pos: 0,
+            kind: FindingKind::Undecodable
+        }]);
+    }
+
+    #[test]
+    fn verify_unknown_lib() {
+        let site = LibSite::with(0, LibId::with("ALU", b"", b"", &none!()));
+        let lib = Lib::assemble::<Isa>(&[Instr::ControlFlow(ControlFlowOp::Call(site))]).unwrap();
+        assert_eq!(lib.verify::<Isa>(), Ok(()));
+        let lib = Lib::with(&lib.isae_segment(), lib.code.to_vec(), vec![], none!()).unwrap();
+        assert_eq!(findings(&lib), vec![(0, FindingKind::UnknownLib)]);
+    }
+
+    #[test]
+    fn verify_undeclared_isa() {
+        let code: [Isa; 2] = [
+            Instr::ControlFlow(ControlFlowOp::Ret),
+            Instr::Digest(DigestOp::Sha256(RegS::default(), Reg16::Reg0)),
+        ];
+        let lib = Lib::assemble(&code).unwrap();
+        assert_eq!(lib.verify::<Isa>(), Ok(()));
+        let lib = Lib::with("ALU", lib.code.to_vec(), vec![], none!()).unwrap();
+        assert_eq!(findings(&lib), vec![(
+            1,
+            FindingKind::UndeclaredIsa(IsaName::from_str("BPDIGEST").unwrap())
+        )]);
+    }
 }
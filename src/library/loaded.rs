@@ -0,0 +1,350 @@
+// Reference rust implementation of AluVM (arithmetic logic unit virtual machine).
+// To find more on AluVM please check <https://aluvm.org>
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2021-2024 by
+//     Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2021-2022 LNP/BP Standards Association. All rights reserved.
+// Copyright (C) 2023-2024 UBIDECO Labs,
+//     Institute for Distributed and Cognitive Computing, Switzerland.
+//     All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Libraries prepared for the repeated execution
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::mem;
+
+use super::constants::LOADED_CACHE_MAX_SIZE;
+use super::lib::{exec_decoded, notify_exit};
+use super::{CodeEofError, Cursor, ExecLib, Lib, LibExit, LibId, LibSite, Read};
+use crate::isa::{CostModel, ExecFn, HaltReason, InstructionSet};
+use crate::observer::Observer;
+use crate::reg::CoreRegs;
+
+/// Marks offsets of the code segment which have no pre-decoded instruction.
+const NOT_DECODED: u16 = u16::MAX;
+
+/// Library prepared for the repeated execution. Keeps the library id and the instructions decoded
+/// by a single pass over the code segment, such that neither the library code hash nor the
+/// bytecode decoding is recomputed when the library is run again, jumps within itself or is
+/// returned to from other libraries.
+///
+/// Instructions which are not reached by the pass (i.e. located in the middle of other
+/// instructions or after a bytecode which can't be decoded), or which don't fit into
+/// [`LOADED_CACHE_MAX_SIZE`], are decoded each time they are executed.
+///
+/// Each pre-decoded instruction keeps the function executing it, selected once with
+/// [`InstructionSet::exec_fn`], such that the interpreter calls it directly instead of matching
+/// the instruction against the whole instruction set on each execution.
+#[derive(Clone, Debug)]
+pub struct LoadedLib<Isa>
+where Isa: InstructionSet
+{
+    lib: Lib,
+    id: LibId,
+    /// Pre-decoded instructions with the offsets of the instructions following them and the
+    /// functions executing them.
+    instrs: Vec<(Isa, u16, ExecFn<Isa>)>,
+    /// Index in `instrs` for each offset of the code segment.
+    index: Vec<u16>,
+}
+
+// All other fields are derived from the library; execution functions can't be reliably compared.
+impl<Isa> PartialEq for LoadedLib<Isa>
+where Isa: InstructionSet
+{
+    fn eq(&self, other: &Self) -> bool { self.lib == other.lib }
+}
+
+impl<Isa> Eq for LoadedLib<Isa> where Isa: InstructionSet {}
+
+impl<Isa> From<Lib> for LoadedLib<Isa>
+where Isa: InstructionSet
+{
+    fn from(lib: Lib) -> Self { LoadedLib::new(lib) }
+}
+
+impl<Isa> AsRef<Lib> for LoadedLib<Isa>
+where Isa: InstructionSet
+{
+    #[inline]
+    fn as_ref(&self) -> &Lib { &self.lib }
+}
+
+impl<Isa> LoadedLib<Isa>
+where Isa: InstructionSet
+{
+    /// Prepares library for the execution by computing its id and decoding its instructions
+    /// from the start of the code segment.
+    pub fn new(lib: Lib) -> Self {
+        let id = lib.id();
+        let mut instrs = Vec::new();
+        let mut index = vec![NOT_DECODED; lib.code.len()];
+        let mut cursor = Cursor::with(&lib.code, &lib.data, &lib.libs);
+        while !cursor.is_eof() {
+            let pos = cursor.pos();
+            let Ok(instr) = Isa::decode(&mut cursor) else {
+                break;
+            };
+            let size = (instrs.len() + 1) * mem::size_of::<(Isa, u16, ExecFn<Isa>)>()
+                + cursor.data_loaded();
+            if cursor.pos() <= pos || size > LOADED_CACHE_MAX_SIZE {
+                break;
+            }
+            index[pos as usize] = instrs.len() as u16;
+            let exec = instr.exec_fn();
+            instrs.push((instr, cursor.pos(), exec));
+        }
+        LoadedLib {
+            lib,
+            id,
+            instrs,
+            index,
+        }
+    }
+
+    /// Returns id of the library.
+    #[inline]
+    pub fn id(&self) -> LibId { self.id }
+
+    /// Returns the library.
+    #[inline]
+    pub fn lib(&self) -> &Lib { &self.lib }
+
+    /// Releases the library, dropping the decoded instructions.
+    #[inline]
+    pub fn into_lib(self) -> Lib { self.lib }
+
+    /// Same as [`Lib::run`], but using cached library id and pre-decoded instructions.
+    pub fn run(
+        &self,
+        entrypoint: u16,
+        registers: &mut CoreRegs,
+        context: &Isa::Context<'_>,
+        cost: &impl CostModel<Isa>,
+        observer: &mut impl Observer<Isa>,
+    ) -> LibExit {
+        self.run_with_id(self.id, entrypoint, registers, context, cost, observer)
+    }
+
+    /// Same as [`Lib::step`], but using cached library id and pre-decoded instructions.
+    pub fn step(
+        &self,
+        pos: u16,
+        registers: &mut CoreRegs,
+        context: &Isa::Context<'_>,
+        cost: &impl CostModel<Isa>,
+        observer: &mut impl Observer<Isa>,
+    ) -> LibExit {
+        self.step_with_id(self.id, pos, registers, context, cost, observer)
+    }
+
+    /// Decodes instruction at `pos` offset, returning it together with the offset of the
+    /// following instruction.
+    fn decode_at(&self, pos: u16) -> Option<(Isa, u16)> {
+        let mut cursor = Cursor::with(&self.lib.code, &self.lib.data, &self.lib.libs);
+        cursor.seek(pos).ok()?;
+        let instr = Isa::decode(&mut cursor).ok()?;
+        Some((instr, cursor.pos()))
+    }
+
+    /// Executes instruction at `pos` offset, which must be within the code segment.
+    fn exec_at(
+        &self,
+        lib_id: LibId,
+        pos: u16,
+        registers: &mut CoreRegs,
+        context: &Isa::Context<'_>,
+        cost: &impl CostModel<Isa>,
+        observer: &mut impl Observer<Isa>,
+    ) -> Result<u16, LibExit> {
+        let site = LibSite::with(pos, lib_id);
+        let decoded;
+        let (instr, next_pos, exec) = match self.index[pos as usize] {
+            NOT_DECODED => {
+                let Some(instr) = self.decode_at(pos) else {
+                    registers.st0 = false;
+                    return Err(LibExit::Halt(site, HaltReason::DecodeError));
+                };
+                decoded = instr;
+                (&decoded.0, decoded.1, Isa::exec as ExecFn<Isa>)
+            }
+            idx => {
+                let (instr, next_pos, exec) = &self.instrs[idx as usize];
+                (instr, *next_pos, *exec)
+            }
+        };
+        let code_len = self.index.len();
+        exec_decoded(instr, exec, site, next_pos, code_len, registers, context, cost, observer)
+            .map(|jump| jump.unwrap_or(next_pos))
+    }
+}
+
+impl<Isa> ExecLib<Isa> for LoadedLib<Isa>
+where Isa: InstructionSet
+{
+    #[inline]
+    fn lib_id(&self) -> LibId { self.id }
+
+    fn run_with_id(
+        &self,
+        lib_id: LibId,
+        entrypoint: u16,
+        registers: &mut CoreRegs,
+        context: &Isa::Context<'_>,
+        cost: &impl CostModel<Isa>,
+        observer: &mut impl Observer<Isa>,
+    ) -> LibExit {
+        let mut pos = entrypoint;
+        let exit = if pos as usize >= self.index.len() {
+            registers.st0 = false;
+            LibExit::Halt(LibSite::with(pos, lib_id), HaltReason::InvalidJump)
+        } else {
+            loop {
+                if pos as usize >= self.index.len() {
+                    break LibExit::Halt(LibSite::with(pos, lib_id), HaltReason::EndOfCode);
+                }
+                match self.exec_at(lib_id, pos, registers, context, cost, observer) {
+                    Ok(next) => pos = next,
+                    Err(exit) => break exit,
+                }
+            }
+        };
+        notify_exit(exit, registers, observer);
+        exit
+    }
+
+    fn step_with_id(
+        &self,
+        lib_id: LibId,
+        pos: u16,
+        registers: &mut CoreRegs,
+        context: &Isa::Context<'_>,
+        cost: &impl CostModel<Isa>,
+        observer: &mut impl Observer<Isa>,
+    ) -> LibExit {
+        let exit = if pos as usize >= self.index.len() {
+            registers.st0 = false;
+            LibExit::Halt(LibSite::with(pos, lib_id), HaltReason::InvalidJump)
+        } else {
+            match self.exec_at(lib_id, pos, registers, context, cost, observer) {
+                Ok(next) if next as usize >= self.index.len() => {
+                    LibExit::Halt(LibSite::with(next, lib_id), HaltReason::EndOfCode)
+                }
+                Ok(next) => LibExit::Call(LibSite::with(next, lib_id)),
+                Err(exit) => exit,
+            }
+        };
+        notify_exit(exit, registers, observer);
+        exit
+    }
+
+    #[inline]
+    fn instr_at(&self, pos: u16) -> Result<Isa, CodeEofError> { self.lib.decode_at(pos) }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::collections::BTreeMap;
+    use alloc::string::ToString;
+
+    use super::*;
+    use crate::isa::opcodes::INSTR_PUT;
+    use crate::isa::{ControlFlowOp, DefaultCost, Instr};
+    use crate::library::constants::{CODE_SEGMENT_MAX_LEN, DATA_SEGMENT_MAX_LEN};
+    use crate::{ExecReport, Vm};
+
+    fn lib(code: &[ControlFlowOp]) -> Lib {
+        Lib::assemble(&code.iter().cloned().map(Instr::ControlFlow).collect::<Vec<Instr>>())
+            .unwrap()
+    }
+
+    fn run_both(entry: LibSite, libs: &[&Lib]) -> ExecReport {
+        let plain = libs.iter().map(|lib| (lib.id(), *lib)).collect::<BTreeMap<_, _>>();
+        let loaded = libs
+            .iter()
+            .map(|lib| (lib.id(), LoadedLib::<Instr>::new((*lib).clone())))
+            .collect::<BTreeMap<_, _>>();
+
+        let mut vm = Vm::<Instr>::new();
+        let report = vm.run(entry, &plain, &());
+        let mut loaded_vm = Vm::<Instr>::new();
+        assert_eq!(loaded_vm.run(entry, &loaded, &()), report);
+        assert_eq!(format!("{:?}", loaded_vm.registers), format!("{:?}", vm.registers));
+        report
+    }
+
+    #[test]
+    fn same_as_lib() {
+        let callee = lib(&[ControlFlowOp::Ret]);
+        let callee_id = callee.id();
+        let caller =
+            lib(&[ControlFlowOp::Call(LibSite::with(0, callee_id)), ControlFlowOp::Jif(0)]);
+        let report = run_both(LibSite::with(0, caller.id()), &[&caller, &callee]);
+        assert_eq!(report.halt, HaltReason::CycleLimit);
+
+        let report = run_both(LibSite::with(0, caller.id()), &[&caller]);
+        assert_eq!(report.halt, HaltReason::LibNotFound(callee_id));
+
+        let jumps = lib(&[ControlFlowOp::Jmp(1), ControlFlowOp::Jmp(0x10)]);
+        run_both(LibSite::with(0, jumps.id()), &[&jumps]);
+        run_both(LibSite::with(3, jumps.id()), &[&jumps]);
+        let report = run_both(LibSite::with(6, jumps.id()), &[&jumps]);
+        assert_eq!(report.halt, HaltReason::InvalidJump);
+    }
+
+    #[test]
+    fn step() {
+        let code = [ControlFlowOp::Routine(4), ControlFlowOp::Ret, ControlFlowOp::Test];
+        let loaded = LoadedLib::<Instr>::new(lib(&code));
+        let id = loaded.id();
+        assert_eq!(id, loaded.lib().id());
+        assert_eq!(loaded.instr_at(3), Ok(Instr::ControlFlow(ControlFlowOp::Ret)));
+
+        let mut regs = CoreRegs::new();
+        assert_eq!(
+            loaded.step(0, &mut regs, &(), &DefaultCost, &mut ()),
+            LibExit::Call(LibSite::with(4, id))
+        );
+        assert_eq!(
+            loaded.step(4, &mut regs, &(), &DefaultCost, &mut ()),
+            LibExit::Halt(LibSite::with(5, id), HaltReason::EndOfCode)
+        );
+        let mut regs = CoreRegs::new();
+        assert_eq!(
+            loaded.run(3, &mut regs, &(), &DefaultCost, &mut ()),
+            LibExit::Halt(LibSite::with(3, id), HaltReason::Stop)
+        );
+    }
+
+    #[test]
+    fn cache_limit() {
+        // Each `put` instruction loads the whole data segment into a byte string
+        let put = [INSTR_PUT, 0x00, 0x00, 0x00, 0xFF, 0xFF];
+        let code = put.repeat(CODE_SEGMENT_MAX_LEN / put.len());
+        let data = vec![0x30; DATA_SEGMENT_MAX_LEN];
+        let lib = Lib::with(&<Instr>::isa_ids().to_string(), code, data, none!()).unwrap();
+        let loaded = LoadedLib::<Instr>::new(lib.clone());
+        let size = mem::size_of::<(Instr, u16, ExecFn<Instr>)>() + DATA_SEGMENT_MAX_LEN;
+        assert_eq!(loaded.instrs.len(), LOADED_CACHE_MAX_SIZE / size);
+
+        let report = run_both(LibSite::with(0, lib.id()), &[&lib]);
+        assert_eq!(report.halt, HaltReason::EndOfCode);
+        run_both(LibSite::with(1, lib.id()), &[&lib]);
+    }
+}
@@ -28,12 +28,20 @@
 pub mod constants;
 mod cursor;
 mod lib;
+mod loaded;
+mod resolver;
 mod rw;
 mod segs;
 
 pub use cursor::Cursor;
 #[cfg(feature = "ascii-armor")]
 pub use lib::LibArmorError;
-pub use lib::{AssemblerError, Lib, LibId, LibSite};
+pub use lib::{
+    AssemblerError, ExecLib, Finding, FindingKind, Lib, LibExit, LibId, LibSite, LibSiteParseError,
+};
+pub use loaded::LoadedLib;
+#[cfg(feature = "std")]
+pub use resolver::LibDir;
+pub use resolver::{Chain, ChainHandle, LibCache, LibResolver, ResolveError, StoredLib};
 pub use rw::{CodeEofError, Read, Write, WriteError};
 pub use segs::{IsaName, IsaSeg, IsaSegError, LibSeg, SegmentError};
@@ -0,0 +1,507 @@
+// Reference rust implementation of AluVM (arithmetic logic unit virtual machine).
+// To find more on AluVM please check <https://aluvm.org>
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2021-2024 by
+//     Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2021-2022 LNP/BP Standards Association. All rights reserved.
+// Copyright (C) 2023-2024 UBIDECO Labs,
+//     Institute for Distributed and Cognitive Computing, Switzerland.
+//     All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Resolvers providing libraries required for the program execution
+
+#[cfg(feature = "alloc")]
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::rc::Rc;
+use alloc::sync::Arc;
+use core::ops::Deref;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+#[cfg(feature = "std")]
+use std::path::PathBuf;
+#[cfg(feature = "std")]
+use std::sync::Mutex;
+
+#[cfg(feature = "std")]
+use strict_encoding::DeserializeError;
+
+use super::{Lib, LibId, LoadedLib};
+use crate::isa::InstructionSet;
+
+/// Errors resolving library by its id
+#[derive(Clone, Eq, PartialEq, Debug, Display)]
+#[cfg_attr(feature = "std", derive(Error))]
+#[display(doc_comments)]
+pub enum ResolveError {
+    /// library {0} is not known.
+    NotFound(LibId),
+
+    /// library {expected} was requested, but library {actual} was provided instead.
+    IdMismatch {
+        /// Id of the requested library
+        expected: LibId,
+        /// Id of the provided library
+        actual: LibId,
+    },
+
+    /// unable to load library {0}: {1}
+    #[cfg(feature = "std")]
+    Load(LibId, DeserializeError),
+}
+
+impl ResolveError {
+    /// Returns id of the library which can't be resolved.
+    pub fn lib_id(&self) -> LibId {
+        match self {
+            ResolveError::NotFound(id) => *id,
+            ResolveError::IdMismatch { expected, .. } => *expected,
+            #[cfg(feature = "std")]
+            ResolveError::Load(id, _) => *id,
+        }
+    }
+}
+
+/// Provider of the libraries required for the program execution.
+///
+/// Implemented by closures returning library references, by library maps, by [`LibDir`]
+/// directory store and by [`Chain`] of two resolvers.
+pub trait LibResolver {
+    /// Type of the resolved libraries: either [`Lib`], or [`LoadedLib`] with pre-decoded
+    /// instructions.
+    type Lib: AsRef<Lib>;
+
+    /// Handle to the resolved library, like `&Lib`, `Arc<Lib>` or `Cow<Lib>`.
+    type Handle<'a>: Deref<Target = Self::Lib>
+    where Self: 'a;
+
+    /// Resolves library with the provided id. The id of the returned library must match the
+    /// requested one; otherwise the virtual machine halts as if the library was not found.
+    fn resolve(&self, id: LibId) -> Result<Self::Handle<'_>, ResolveError>;
+
+    /// Checks that the library with the provided id and all libraries it depends on, directly or
+    /// transitively, can be resolved.
+    ///
+    /// # Returns
+    ///
+    /// Ids of all resolved libraries, including the `root` one, or the first resolution error.
+    fn check_deps(&self, root: LibId) -> Result<BTreeSet<LibId>, ResolveError> {
+        let mut resolved = bset![root];
+        let mut queue = vec![root];
+        while let Some(id) = queue.pop() {
+            let lib = self.resolve(id)?;
+            for dep in &(*lib).as_ref().libs {
+                if resolved.insert(*dep) {
+                    queue.push(*dep);
+                }
+            }
+        }
+        Ok(resolved)
+    }
+
+    /// Constructs resolver which uses `other` resolver for libraries which are not found by this
+    /// resolver.
+    fn chain<R: LibResolver<Lib = Self::Lib>>(self, other: R) -> Chain<Self, R>
+    where Self: Sized {
+        Chain(self, other)
+    }
+}
+
+/// Library kept by a resolver, either directly or behind a pointer, like `Lib`, `Arc<Lib>` or
+/// `Box<LoadedLib<Isa>>`.
+pub trait StoredLib {
+    /// Type of the stored library.
+    type Lib: AsRef<Lib>;
+
+    /// Returns reference to the stored library.
+    fn stored_lib(&self) -> &Self::Lib;
+}
+
+impl StoredLib for Lib {
+    type Lib = Lib;
+
+    #[inline]
+    fn stored_lib(&self) -> &Self::Lib { self }
+}
+
+impl<Isa> StoredLib for LoadedLib<Isa>
+where Isa: InstructionSet
+{
+    type Lib = LoadedLib<Isa>;
+
+    #[inline]
+    fn stored_lib(&self) -> &Self::Lib { self }
+}
+
+impl<L: StoredLib> StoredLib for &L {
+    type Lib = L::Lib;
+
+    #[inline]
+    fn stored_lib(&self) -> &Self::Lib { (*self).stored_lib() }
+}
+
+impl<L: StoredLib> StoredLib for Box<L> {
+    type Lib = L::Lib;
+
+    #[inline]
+    fn stored_lib(&self) -> &Self::Lib { self.as_ref().stored_lib() }
+}
+
+impl<L: StoredLib> StoredLib for Rc<L> {
+    type Lib = L::Lib;
+
+    #[inline]
+    fn stored_lib(&self) -> &Self::Lib { self.as_ref().stored_lib() }
+}
+
+impl<L: StoredLib> StoredLib for Arc<L> {
+    type Lib = L::Lib;
+
+    #[inline]
+    fn stored_lib(&self) -> &Self::Lib { self.as_ref().stored_lib() }
+}
+
+impl<'lib, F> LibResolver for F
+where F: Fn(LibId) -> Option<&'lib Lib>
+{
+    type Lib = Lib;
+    type Handle<'a>
+        = &'lib Lib
+    where Self: 'a;
+
+    fn resolve(&self, id: LibId) -> Result<Self::Handle<'_>, ResolveError> {
+        self(id).ok_or(ResolveError::NotFound(id))
+    }
+}
+
+impl<L> LibResolver for BTreeMap<LibId, L>
+where L: StoredLib
+{
+    type Lib = L::Lib;
+    type Handle<'a>
+        = &'a L::Lib
+    where Self: 'a;
+
+    fn resolve(&self, id: LibId) -> Result<Self::Handle<'_>, ResolveError> {
+        self.get(&id).map(L::stored_lib).ok_or(ResolveError::NotFound(id))
+    }
+}
+
+impl<'map, L> LibResolver for &'map BTreeMap<LibId, L>
+where L: StoredLib
+{
+    type Lib = L::Lib;
+    type Handle<'a>
+        = &'map L::Lib
+    where Self: 'a;
+
+    fn resolve(&self, id: LibId) -> Result<Self::Handle<'_>, ResolveError> {
+        self.get(&id).map(L::stored_lib).ok_or(ResolveError::NotFound(id))
+    }
+}
+
+#[cfg(feature = "std")]
+impl<L> LibResolver for HashMap<LibId, L>
+where L: StoredLib
+{
+    type Lib = L::Lib;
+    type Handle<'a>
+        = &'a L::Lib
+    where Self: 'a;
+
+    fn resolve(&self, id: LibId) -> Result<Self::Handle<'_>, ResolveError> {
+        self.get(&id).map(L::stored_lib).ok_or(ResolveError::NotFound(id))
+    }
+}
+
+#[cfg(feature = "std")]
+impl<'map, L> LibResolver for &'map HashMap<LibId, L>
+where L: StoredLib
+{
+    type Lib = L::Lib;
+    type Handle<'a>
+        = &'map L::Lib
+    where Self: 'a;
+
+    fn resolve(&self, id: LibId) -> Result<Self::Handle<'_>, ResolveError> {
+        self.get(&id).map(L::stored_lib).ok_or(ResolveError::NotFound(id))
+    }
+}
+
+/// Read-only cache of libraries with pre-decoded instructions, which may be shared between many
+/// virtual machines, including ones running in different threads.
+#[derive(Clone, Debug)]
+pub struct LibCache<Isa: InstructionSet>(BTreeMap<LibId, LoadedLib<Isa>>);
+
+impl<Isa: InstructionSet> Default for LibCache<Isa> {
+    fn default() -> Self { LibCache(empty!()) }
+}
+
+impl<Isa: InstructionSet> LibCache<Isa> {
+    /// Constructs empty cache.
+    #[inline]
+    pub fn new() -> Self { Self::default() }
+
+    /// Decodes library instructions and adds the library to the cache, returning its id.
+    pub fn insert(&mut self, lib: Lib) -> LibId {
+        let lib = LoadedLib::new(lib);
+        let id = lib.id();
+        self.0.insert(id, lib);
+        id
+    }
+
+    /// Returns library with the provided id, if it is present in the cache.
+    #[inline]
+    pub fn get(&self, id: LibId) -> Option<&LoadedLib<Isa>> { self.0.get(&id) }
+
+    /// Returns number of libraries in the cache.
+    #[inline]
+    pub fn len(&self) -> usize { self.0.len() }
+
+    /// Detects whether the cache contains no libraries.
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.0.is_empty() }
+}
+
+impl<Isa: InstructionSet> FromIterator<Lib> for LibCache<Isa> {
+    fn from_iter<T: IntoIterator<Item = Lib>>(iter: T) -> Self {
+        let mut cache = Self::new();
+        cache.extend(iter);
+        cache
+    }
+}
+
+impl<Isa: InstructionSet> Extend<Lib> for LibCache<Isa> {
+    fn extend<T: IntoIterator<Item = Lib>>(&mut self, iter: T) {
+        for lib in iter {
+            self.insert(lib);
+        }
+    }
+}
+
+impl<Isa: InstructionSet> LibResolver for LibCache<Isa> {
+    type Lib = LoadedLib<Isa>;
+    type Handle<'a>
+        = &'a LoadedLib<Isa>
+    where Self: 'a;
+
+    fn resolve(&self, id: LibId) -> Result<Self::Handle<'_>, ResolveError> {
+        self.get(id).ok_or(ResolveError::NotFound(id))
+    }
+}
+
+/// Resolver trying to resolve library with the first resolver and, if it is not found, with the
+/// second one. Constructed with [`LibResolver::chain`].
+#[derive(Clone, Debug)]
+pub struct Chain<A, B>(pub A, pub B);
+
+/// Handle to a library resolved by [`Chain`] resolver.
+#[derive(Clone, Debug)]
+pub enum ChainHandle<A, B> {
+    /// Library resolved by the first resolver
+    First(A),
+    /// Library resolved by the second resolver
+    Second(B),
+}
+
+impl<A, B> Deref for ChainHandle<A, B>
+where
+    A: Deref,
+    B: Deref<Target = A::Target>,
+{
+    type Target = A::Target;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            ChainHandle::First(lib) => lib,
+            ChainHandle::Second(lib) => lib,
+        }
+    }
+}
+
+impl<A, B> LibResolver for Chain<A, B>
+where
+    A: LibResolver,
+    B: LibResolver<Lib = A::Lib>,
+{
+    type Lib = A::Lib;
+    type Handle<'a>
+        = ChainHandle<A::Handle<'a>, B::Handle<'a>>
+    where Self: 'a;
+
+    fn resolve(&self, id: LibId) -> Result<Self::Handle<'_>, ResolveError> {
+        match self.0.resolve(id) {
+            Ok(lib) => Ok(ChainHandle::First(lib)),
+            Err(ResolveError::NotFound(_)) => self.1.resolve(id).map(ChainHandle::Second),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+/// Directory store loading libraries from strict-encoded files named by the hex-encoded library
+/// id with `.alu` extension. Libraries are loaded lazily on the first request and cached in
+/// memory.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct LibDir {
+    path: PathBuf,
+    cache: Mutex<BTreeMap<LibId, Arc<Lib>>>,
+}
+
+#[cfg(feature = "std")]
+impl LibDir {
+    /// File extension used for library files.
+    pub const EXTENSION: &'static str = "alu";
+
+    /// Constructs resolver loading libraries from the provided directory.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        LibDir {
+            path: path.into(),
+            cache: empty!(),
+        }
+    }
+
+    /// Returns path to the file containing library with the provided id.
+    pub fn lib_path(&self, id: LibId) -> PathBuf {
+        use amplify::hex::ToHex;
+        self.path.join(id.to_byte_array().to_hex()).with_extension(Self::EXTENSION)
+    }
+}
+
+#[cfg(feature = "std")]
+impl LibResolver for LibDir {
+    type Lib = Lib;
+    type Handle<'a> = Arc<Lib>;
+
+    fn resolve(&self, id: LibId) -> Result<Self::Handle<'_>, ResolveError> {
+        use amplify::confinement::U24;
+        use strict_encoding::StrictDeserialize;
+
+        let mut cache = self.cache.lock().unwrap_or_else(|err| err.into_inner());
+        if let Some(lib) = cache.get(&id) {
+            return Ok(lib.clone());
+        }
+        let path = self.lib_path(id);
+        if !path.is_file() {
+            return Err(ResolveError::NotFound(id));
+        }
+        let lib = Lib::strict_deserialize_from_file::<U24>(path)
+            .map_err(|err| ResolveError::Load(id, err))?;
+        let actual = lib.id();
+        if actual != id {
+            return Err(ResolveError::IdMismatch {
+                expected: id,
+                actual,
+            });
+        }
+        let lib = Arc::new(lib);
+        cache.insert(id, lib.clone());
+        Ok(lib)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use super::*;
+    use crate::isa::{ControlFlowOp, HaltReason, Instr};
+    use crate::library::LibSite;
+    use crate::Vm;
+
+    fn lib(code: &[ControlFlowOp]) -> Lib {
+        Lib::assemble(&code.iter().cloned().map(Instr::ControlFlow).collect::<Vec<Instr>>())
+            .unwrap()
+    }
+
+    #[test]
+    fn chain() {
+        let callee = lib(&[ControlFlowOp::Ret]);
+        let caller =
+            lib(&[ControlFlowOp::Call(LibSite::with(0, callee.id())), ControlFlowOp::Test]);
+        let callee_id = callee.id();
+        let caller_id = caller.id();
+
+        let first = bmap! { caller_id => caller };
+        let second = HashMap::from([(callee_id, Arc::new(callee))]);
+        assert_eq!(first.resolve(callee_id).unwrap_err(), ResolveError::NotFound(callee_id));
+
+        let resolver = (&first).chain(&second);
+        assert_eq!(resolver.resolve(callee_id).unwrap().id(), callee_id);
+        let mut vm = Vm::<Instr>::new();
+        let report = vm.run(LibSite::with(0, caller_id), resolver, &());
+        assert_eq!(report.halt, HaltReason::EndOfCode);
+        assert_eq!(report.site, LibSite::with(5, caller_id));
+        assert_eq!(report.cycles, 1);
+        assert!(report.status);
+    }
+
+    #[test]
+    fn check_deps() {
+        let leaf = lib(&[ControlFlowOp::Ret]);
+        let middle = lib(&[ControlFlowOp::Call(LibSite::with(0, leaf.id()))]);
+        let root = lib(&[
+            ControlFlowOp::Call(LibSite::with(0, middle.id())),
+            ControlFlowOp::Call(LibSite::with(0, leaf.id())),
+        ]);
+        let ids = bset![root.id(), middle.id(), leaf.id()];
+
+        let mut libs = bmap! { root.id() => root.clone(), middle.id() => middle.clone() };
+        assert_eq!(libs.check_deps(root.id()).unwrap_err(), ResolveError::NotFound(leaf.id()));
+        libs.insert(leaf.id(), leaf);
+        assert_eq!(libs.check_deps(root.id()).unwrap(), ids);
+    }
+
+    #[test]
+    fn id_mismatch() {
+        let lib = lib(&[ControlFlowOp::Test]);
+        let id = LibId::from([0u8; 32]);
+        let mut vm = Vm::<Instr>::new();
+        let report = vm.run(LibSite::with(0, id), |_: LibId| Some(&lib), &());
+        assert_eq!(report.halt, HaltReason::LibNotFound(id));
+        assert_eq!(report.cycles, 0);
+        assert!(!report.status);
+
+        let libs = bmap! { id => LoadedLib::<Instr>::new(lib) };
+        let report = vm.run(LibSite::with(0, id), &libs, &());
+        assert_eq!(report.halt, HaltReason::LibNotFound(id));
+    }
+
+    #[test]
+    fn dir() {
+        use strict_encoding::StrictSerialize;
+
+        let lib = lib(&[ControlFlowOp::Test]);
+        let id = lib.id();
+        let path = std::env::temp_dir().join(format!("aluvm-resolver-{}", std::process::id()));
+        std::fs::create_dir_all(&path).unwrap();
+        let store = LibDir::new(&path);
+        assert_eq!(store.resolve(id).unwrap_err(), ResolveError::NotFound(id));
+
+        lib.strict_serialize_to_file::<{ amplify::confinement::U24 }>(store.lib_path(id)).unwrap();
+        assert_eq!(*store.resolve(id).unwrap(), lib);
+
+        let other = LibId::from([0u8; 32]);
+        std::fs::copy(store.lib_path(id), store.lib_path(other)).unwrap();
+        assert_eq!(store.resolve(other).unwrap_err(), ResolveError::IdMismatch {
+            expected: other,
+            actual: id
+        });
+
+        std::fs::remove_dir_all(&path).unwrap();
+        assert_eq!(*store.resolve(id).unwrap(), lib);
+    }
+}
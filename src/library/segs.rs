@@ -203,7 +203,7 @@ pub struct LibSeg(Confined<BTreeSet<LibId>, 0, LIBS_SEGMENT_MAX_COUNT>);
 impl LibSeg {
     /// Returns iterator over unique libraries iterated in the deterministic (lexicographic) order
     #[inline]
-    pub fn iter(&self) -> ::alloc::collections::btree_set::Iter<LibId> { self.into_iter() }
+    pub fn iter(&self) -> ::alloc::collections::btree_set::Iter<'_, LibId> { self.into_iter() }
 }
 
 impl<'a> IntoIterator for &'a LibSeg {
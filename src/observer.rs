@@ -0,0 +1,259 @@
+// Reference rust implementation of AluVM (arithmetic logic unit virtual machine).
+// To find more on AluVM please check <https://aluvm.org>
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2021-2024 by
+//     Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2021-2022 LNP/BP Standards Association. All rights reserved.
+// Copyright (C) 2023-2024 UBIDECO Labs,
+//     Institute for Distributed and Cognitive Computing, Switzerland.
+//     All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Hooks for observing program execution
+
+#[cfg(feature = "std")]
+use alloc::string::{String, ToString};
+
+use crate::isa::{ExecStep, HaltReason, InstructionSet};
+#[cfg(feature = "std")]
+use crate::library::LibId;
+use crate::library::LibSite;
+use crate::reg::CoreRegs;
+
+/// Observer of the program execution, called by the VM on each execution step.
+///
+/// All methods have no-op default implementations, so an observer needs to implement only the
+/// events it is interested in. `()` is a no-op observer.
+pub trait Observer<Isa>
+where Isa: InstructionSet
+{
+    /// Called before the instruction located at `site` is executed. Values of the instruction
+    /// source registers ([`InstructionSet::src_regs`]) can be read from `regs`.
+    #[allow(unused_variables)]
+    fn before_instr(&mut self, site: LibSite, instr: &Isa, regs: &CoreRegs) {}
+
+    /// Called after the instruction located at `site` is executed and its complexity is
+    /// accounted in `ca0` register. Values of the instruction destination registers
+    /// ([`InstructionSet::dst_regs`]) can be read from `regs`; `st0` is the value of `st0` register
+    /// before the instruction execution and `next` is the movement requested by the instruction.
+    #[allow(unused_variables)]
+    fn after_instr(
+        &mut self,
+        site: LibSite,
+        instr: &Isa,
+        regs: &CoreRegs,
+        st0: bool,
+        next: ExecStep,
+    ) {
+    }
+
+    /// Called when the instruction located at `site` has called a subroutine or an external
+    /// library code located at `target`.
+    #[allow(unused_variables)]
+    fn on_call(&mut self, site: LibSite, target: LibSite) {}
+
+    /// Called when the instruction located at `site` has returned execution to `target`.
+    #[allow(unused_variables)]
+    fn on_return(&mut self, site: LibSite, target: LibSite) {}
+
+    /// Called when the instruction located at `site` has made accumulated complexity to reach
+    /// the complexity limit. Followed by [`Observer::on_halt`] call.
+    #[allow(unused_variables)]
+    fn on_complexity_overflow(&mut self, site: LibSite, regs: &CoreRegs) {}
+
+    /// Called when the program execution halts at `site`.
+    #[allow(unused_variables)]
+    fn on_halt(&mut self, site: LibSite, reason: HaltReason, regs: &CoreRegs) {}
+
+    /// Called when the program execution is suspended with a host `request`; `site` is the
+    /// location from which the execution will be resumed.
+    #[allow(unused_variables)]
+    fn on_yield(&mut self, site: LibSite, request: u16, regs: &CoreRegs) {}
+}
+
+impl<Isa> Observer<Isa> for () where Isa: InstructionSet {}
+
+/// Observer printing colored execution trace to the standard error output.
+///
+/// Used by default by [`crate::Vm::exec`] and [`crate::library::Lib::exec`] when the crate is
+/// compiled with `log` feature.
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, Default)]
+pub struct StderrLog {
+    lib: Option<(LibId, String)>,
+}
+
+#[cfg(feature = "std")]
+impl StderrLog {
+    const M: &'static str = "\x1B[0;35m";
+    const W: &'static str = "\x1B[1;1m";
+    const D: &'static str = "\x1B[0;37;2m";
+    const G: &'static str = "\x1B[0;32m";
+    const R: &'static str = "\x1B[0;31m";
+    const Y: &'static str = "\x1B[0;33m";
+    const Z: &'static str = "\x1B[0m";
+
+    /// Creates new logger.
+    pub fn new() -> Self { StderrLog::default() }
+
+    fn lib_ref(&mut self, id: LibId) -> &str {
+        use baid64::DisplayBaid64;
+
+        if !matches!(self.lib, Some((cached, _)) if cached == id) {
+            let mnemonic = id.to_baid64_mnemonic();
+            self.lib = Some((id, mnemonic.split_at(5).0.to_string()));
+        }
+        self.lib.as_ref().map(|(_, name)| name.as_str()).unwrap_or_default()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<Isa> Observer<Isa> for StderrLog
+where Isa: InstructionSet
+{
+    fn before_instr(&mut self, site: LibSite, instr: &Isa, regs: &CoreRegs) {
+        let (m, w, d, z) = (Self::M, Self::W, Self::D, Self::Z);
+        let pos = site.pos;
+        eprint!("{m}{}@x{pos:06X}:{z} {: <32}; ", self.lib_ref(site.lib), instr.to_string());
+        instr.for_each_src_reg(|reg| {
+            let val = regs.get(reg);
+            eprint!("{d}{reg}={z}{w}{val}{z} ");
+        });
+    }
+
+    fn after_instr(&mut self, _: LibSite, instr: &Isa, regs: &CoreRegs, st0: bool, next: ExecStep) {
+        let (d, g, r, y, z) = (Self::D, Self::G, Self::R, Self::Y, Self::Z);
+        eprint!("-> ");
+        instr.for_each_dst_reg(|reg| {
+            let val = regs.get(reg);
+            eprint!("{g}{reg}={y}{val}{z} ");
+        });
+        if st0 != regs.status() {
+            let c = if regs.status() { g } else { r };
+            eprint!(" {d}st0={z}{c}{}{z} ", regs.status());
+        }
+        match next {
+            ExecStep::Jump(pos) => eprint!("{pos}"),
+            ExecStep::Call(site) => eprint!("{site}"),
+            ExecStep::Yield(request) => eprint!("yield {request}"),
+            ExecStep::Stop | ExecStep::Fail | ExecStep::Halt(_) | ExecStep::Next => {}
+        }
+        eprintln!();
+    }
+
+    fn on_complexity_overflow(&mut self, _: LibSite, _: &CoreRegs) {
+        eprintln!("complexity overflow");
+    }
+
+    fn on_halt(&mut self, _: LibSite, reason: HaltReason, regs: &CoreRegs) {
+        let (d, g, r, z) = (Self::D, Self::G, Self::R, Self::Z);
+        let c = if regs.status() { g } else { r };
+        match reason {
+            HaltReason::Stop | HaltReason::EndOfCode => {
+                eprintln!("execution stopped; {d}st0={z}{c}{}{z}", regs.status())
+            }
+            reason => eprintln!("halting: {reason} {d}st0={z}{c}{}{z}", regs.status()),
+        }
+    }
+
+    fn on_yield(&mut self, site: LibSite, request: u16, _: &CoreRegs) {
+        eprintln!("execution suspended with request {request}; resuming at {site}");
+    }
+}
+
+/// Observer used by default: [`StderrLog`] if the crate is compiled with `log` feature, or no-op
+/// observer otherwise.
+#[cfg(feature = "log")]
+pub type DefaultObserver = StderrLog;
+
+/// Observer used by default: [`StderrLog`] if the crate is compiled with `log` feature, or no-op
+/// observer otherwise.
+#[cfg(not(feature = "log"))]
+pub type DefaultObserver = ();
+
+#[cfg(test)]
+mod test {
+    use alloc::string::String;
+    use alloc::vec::Vec;
+
+    use super::*;
+    use crate::isa::{ControlFlowOp, Instr};
+    use crate::library::{Lib, LibId};
+    use crate::Vm;
+
+    #[derive(Default)]
+    struct Recorder(Vec<String>);
+
+    impl Observer<Instr> for Recorder {
+        fn before_instr(&mut self, site: LibSite, instr: &Instr, _: &CoreRegs) {
+            self.0.push(format!("{} {instr}", site.pos));
+        }
+
+        fn after_instr(&mut self, _: LibSite, _: &Instr, regs: &CoreRegs, st0: bool, _: ExecStep) {
+            if st0 != regs.status() {
+                self.0.push(format!("st0={}", regs.status()));
+            }
+        }
+
+        fn on_call(&mut self, site: LibSite, target: LibSite) {
+            self.0.push(format!("call {}->{}", site.pos, target.pos));
+        }
+
+        fn on_return(&mut self, site: LibSite, target: LibSite) {
+            self.0.push(format!("ret {}->{}", site.pos, target.pos));
+        }
+
+        fn on_halt(&mut self, site: LibSite, reason: HaltReason, _: &CoreRegs) {
+            self.0.push(format!("halt {} {reason:?}", site.pos));
+        }
+    }
+
+    #[test]
+    fn trace_events() {
+        let code: [Instr; 3] = [ControlFlowOp::Routine(4), ControlFlowOp::Fail, ControlFlowOp::Ret]
+            .map(Instr::ControlFlow);
+        let lib = Lib::assemble(&code).unwrap();
+        let mut vm = Vm::<Instr>::new();
+        let mut recorder = Recorder::default();
+        vm.run_observed(LibSite::with(0, lib.id()), |_: LibId| Some(&lib), &(), &mut recorder);
+        assert_eq!(recorder.0, [
+            "0 routine 0x0004",
+            "call 0->4",
+            "4 ret",
+            "ret 4->3",
+            "3 fail",
+            "halt 3 Fail"
+        ]);
+    }
+
+    #[test]
+    fn trace_lib_call() {
+        let callee = Lib::assemble::<Instr>(&[Instr::ControlFlow(ControlFlowOp::Ret)]).unwrap();
+        let caller = Lib::assemble::<Instr>(&[
+            Instr::ControlFlow(ControlFlowOp::Call(LibSite::with(0, callee.id()))),
+            Instr::ControlFlow(ControlFlowOp::Test),
+        ])
+        .unwrap();
+        let resolver = |id: LibId| [&caller, &callee].into_iter().find(|lib| lib.id() == id);
+        let mut vm = Vm::<Instr>::new();
+        let mut recorder = Recorder::default();
+        vm.run_observed(LibSite::with(0, caller.id()), resolver, &(), &mut recorder);
+        let events = recorder.0.iter().filter(|e| e.starts_with("call") || e.starts_with("ret"));
+        assert_eq!(events.collect::<Vec<_>>(), ["call 0->0", "ret 0->4"]);
+        assert_eq!(recorder.0.last().unwrap(), "halt 5 EndOfCode");
+    }
+}
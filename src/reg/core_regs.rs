@@ -36,9 +36,9 @@ use amplify::num::apfloat::{ieee, Float};
 use amplify::num::{u1024, u256, u512};
 use half::bf16;
 
-use super::{Reg, Reg32, RegA, RegAFR, RegF, RegR, RegS};
+use super::{NumericRegister, Reg, Reg32, RegA, RegAFR, RegF, RegR, RegS};
 use crate::data::{ByteStr, MaybeNumber, Number, RegValue};
-use crate::isa::InstructionSet;
+use crate::isa::{HaltReason, InstructionSet};
 use crate::library::LibSite;
 
 /// Maximal size of call stack.
@@ -46,6 +46,152 @@ use crate::library::LibSite;
 /// Equals to 2^16 (limited by `cy0` and `cp0` bit size)
 pub const CALL_STACK_SIZE: usize = 1 << 16;
 
+/// Resource limits applied to the program execution
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct ExecLimits {
+    /// Maximal accumulated complexity of the executed instructions (value for `cl0` register);
+    /// unlimited if `None`
+    pub complexity: Option<u64>,
+
+    /// Maximal number of jumps and calls (limit for `cy0` register)
+    pub cycles: u16,
+
+    /// Maximal depth of the call stack (limit for `cp0` register)
+    pub call_depth: u16,
+}
+
+impl Default for ExecLimits {
+    fn default() -> Self {
+        ExecLimits {
+            complexity: None,
+            cycles: u16::MAX,
+            call_depth: u16::MAX,
+        }
+    }
+}
+
+/// Bank of 32 registers of a large size, which allocates its memory only once some of its
+/// registers gets assigned a value.
+#[derive(Clone)]
+pub(crate) struct LazyBank<T: Copy>(Option<Box<[Option<T>; 32]>>);
+
+impl<T: Copy> Default for LazyBank<T> {
+    #[inline]
+    fn default() -> Self { LazyBank(None) }
+}
+
+impl<T: Copy> LazyBank<T> {
+    /// Returns value of a register with a given index, if the register is initialized.
+    #[inline]
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.0.as_ref().and_then(|bank| bank[index].as_ref())
+    }
+
+    /// Returns mutable reference to a value of a register with a given index, if the register is
+    /// initialized.
+    #[inline]
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.0.as_mut().and_then(|bank| bank[index].as_mut())
+    }
+
+    /// Sets value of a register with a given index, allocating the bank if it was not allocated
+    /// before and the value is not `None`.
+    pub fn set(&mut self, index: usize, value: Option<T>) {
+        match &mut self.0 {
+            Some(bank) => bank[index] = value,
+            None if value.is_some() => self.0.insert(Box::new([None; 32]))[index] = value,
+            None => {}
+        }
+    }
+}
+
+/// Bank of 32 registers of the same type, either stored inline or allocated lazily.
+trait Bank<T: Copy> {
+    fn value(&self, index: usize) -> Option<T>;
+    fn replace(&mut self, index: usize, value: Option<T>) -> Option<T>;
+    fn to_array(&self) -> [Option<T>; 32];
+    fn set_array(&mut self, bank: [Option<T>; 32]);
+}
+
+impl<T: Copy> Bank<T> for [Option<T>; 32] {
+    #[inline]
+    fn value(&self, index: usize) -> Option<T> { self[index] }
+
+    #[inline]
+    fn replace(&mut self, index: usize, value: Option<T>) -> Option<T> {
+        core::mem::replace(&mut self[index], value)
+    }
+
+    #[inline]
+    fn to_array(&self) -> [Option<T>; 32] { *self }
+
+    #[inline]
+    fn set_array(&mut self, bank: [Option<T>; 32]) { *self = bank }
+}
+
+impl<T: Copy> Bank<T> for LazyBank<T> {
+    #[inline]
+    fn value(&self, index: usize) -> Option<T> { self.get(index).copied() }
+
+    fn replace(&mut self, index: usize, value: Option<T>) -> Option<T> {
+        let prev = self.value(index);
+        self.set(index, value);
+        prev
+    }
+
+    fn to_array(&self) -> [Option<T>; 32] { self.0.as_deref().copied().unwrap_or([None; 32]) }
+
+    fn set_array(&mut self, bank: [Option<T>; 32]) {
+        self.0 = bank.iter().any(Option::is_some).then(|| Box::new(bank));
+    }
+}
+
+/// Converts float value into the form in which it is kept by the registers: `NaN` can't be stored
+/// in a register and `-0` is stored as `+0`, same as with [`CoreRegs::set_n`].
+fn normalize_float<T>(val: T) -> Option<T>
+where T: Into<MaybeNumber> + From<Number> {
+    Option::<Number>::from(val.into()).map(T::from)
+}
+
+/// Generates typed accessors for register banks. Values of the banks having a normalization
+/// function are normalized before being stored.
+macro_rules! accessors {
+    ($($bank:ident: $ty:ty $(=> $norm:ident)?),+ $(,)?) => {
+        $crate::paste! { $(
+            #[doc = "Get value from `" $bank "` register."]
+            #[inline]
+            pub fn $bank(&self, idx: impl Into<Reg32>) -> Option<$ty> {
+                self.$bank.value(idx.into().to_usize())
+            }
+
+            #[doc = "Sets `" $bank "` register to a given value. Returns previous register value."]
+            #[inline]
+            pub fn [<set_ $bank>](&mut self, idx: impl Into<Reg32>, val: $ty) -> Option<$ty> {
+                let val = Some(val) $(.and_then($norm))?;
+                self.$bank.replace(idx.into().to_usize(), val)
+            }
+
+            #[doc = "Clears `" $bank "` register (sets its value to `None`). Returns previous \
+                     register value."]
+            #[inline]
+            pub fn [<clr_ $bank>](&mut self, idx: impl Into<Reg32>) -> Option<$ty> {
+                self.$bank.replace(idx.into().to_usize(), None)
+            }
+
+            #[doc = "Returns values of all `" $bank "` registers, indexed by the register number."]
+            #[inline]
+            pub fn [<$bank _bank>](&self) -> [Option<$ty>; 32] { self.$bank.to_array() }
+
+            #[doc = "Sets values of all `" $bank "` registers, indexed by the register number."]
+            #[inline]
+            pub fn [<set_ $bank _bank>](&mut self, bank: [Option<$ty>; 32]) {
+                $( let bank = bank.map(|val| val.and_then($norm)); )?
+                self.$bank.set_array(bank)
+            }
+        )+ }
+    };
+}
+
 /// Structure keeping state of all registers in a single microprosessor/VM core
 #[derive(Clone)]
 pub struct CoreRegs {
@@ -57,7 +203,7 @@ pub struct CoreRegs {
     pub(crate) a128: [Option<u128>; 32],
     pub(crate) a256: [Option<u256>; 32],
     pub(crate) a512: [Option<u512>; 32],
-    pub(crate) a1024: Box<[Option<u1024>; 32]>,
+    pub(crate) a1024: LazyBank<u1024>,
 
     // Arithmetic float registers
     pub(crate) f16b: [Option<bf16>; 32],
@@ -75,10 +221,10 @@ pub struct CoreRegs {
     pub(crate) r160: [Option<[u8; 20]>; 32],
     pub(crate) r256: [Option<[u8; 32]>; 32],
     pub(crate) r512: [Option<[u8; 64]>; 32],
-    pub(crate) r1024: Box<[Option<[u8; 128]>; 32]>,
-    pub(crate) r2048: Box<[Option<[u8; 256]>; 32]>,
-    pub(crate) r4096: Box<[Option<[u8; 512]>; 32]>,
-    pub(crate) r8192: Box<[Option<[u8; 1024]>; 32]>,
+    pub(crate) r1024: LazyBank<[u8; 128]>,
+    pub(crate) r2048: LazyBank<[u8; 256]>,
+    pub(crate) r4096: LazyBank<[u8; 512]>,
+    pub(crate) r8192: LazyBank<[u8; 1024]>,
 
     /// String and bytestring registers
     pub(crate) s16: Box<[Option<ByteStr>; 16]>,
@@ -87,9 +233,9 @@ pub struct CoreRegs {
     /// overflowing operations. Initialized with `true`.
     pub(crate) st0: bool,
 
-    /// Counts number of jumps (possible cycles). The number of jumps is limited by 2^16 per
-    /// script.
-    cy0: u16,
+    /// Counts number of jumps (possible cycles). The number of jumps is limited by
+    /// [`CoreRegs::cy_limit`], which can't exceed 2^16-1 per script.
+    pub(crate) cy0: u16,
 
     /// Complexity accumulator / counter.
     ///
@@ -100,24 +246,30 @@ pub struct CoreRegs {
     ///
     /// - [`CoreRegs::cy0`] register
     /// - [`CoreRegs::cl0`] register
-    ca0: u64,
+    pub(crate) ca0: u64,
 
     /// Complexity limit
     ///
     /// If this register has a value set, once [`CoreRegs::ca0`] will reach this value the VM will
     /// stop program execution setting `st0` to `false`.
-    cl0: Option<u64>,
+    pub(crate) cl0: Option<u64>,
 
-    /// Call stack
+    /// Call stack, growing with each call up to [`CoreRegs::cp_limit`] sites
     ///
     /// # See also
     ///
     /// - [`CALL_STACK_SIZE`] constant
     /// - [`CoreRegs::cp0`] register
-    cs0: Vec<LibSite>,
+    pub(crate) cs0: Vec<LibSite>,
 
     /// Defines "top" of the call stack
-    cp0: u16,
+    pub(crate) cp0: u16,
+
+    /// Maximal number of jumps (limit for [`CoreRegs::cy0`]).
+    pub(crate) cy_limit: u16,
+
+    /// Maximal depth of the call stack (limit for [`CoreRegs::cp0`]).
+    pub(crate) cp_limit: u16,
 }
 
 impl Default for CoreRegs {
@@ -157,8 +309,10 @@ impl Default for CoreRegs {
             cy0: 0,
             ca0: 0,
             cl0: None,
-            cs0: vec![LibSite::default(); CALL_STACK_SIZE],
+            cs0: Vec::new(),
             cp0: 0,
+            cy_limit: u16::MAX,
+            cp_limit: u16::MAX,
         }
     }
 }
@@ -171,97 +325,122 @@ impl CoreRegs {
     #[inline]
     pub fn new() -> CoreRegs { CoreRegs::default() }
 
-    pub(crate) fn jmp(&mut self) -> Result<(), ()> {
-        self.cy0
-            .checked_add(1)
-            .map(|cy| self.cy0 = cy)
-            .ok_or_else(|| {
-                self.st0 = false;
-            })
-            .map(|_| ())
+    /// Initializes register state in the same way as [`CoreRegs::new`], applying provided
+    /// resource limits.
+    pub fn with_limits(limits: ExecLimits) -> CoreRegs {
+        let mut regs = CoreRegs::default();
+        regs.set_limits(limits);
+        regs
     }
 
-    pub(crate) fn call(&mut self, site: LibSite) -> Result<(), ()> {
-        self.cy0
-            .checked_add(1)
-            .map(|cy| self.cy0 = cy)
-            .ok_or_else(|| {
-                self.st0 = false;
-            })
-            .map(|_| {
-                self.cs0[self.cp0 as usize] = site;
-            })
-            .and_then(|_| {
-                self.cp0
-                    .checked_add(1)
-                    .ok_or_else(|| {
-                        self.st0 = false;
-                    })
-                    .map(|_| ())
-            })
-    }
-
-    pub(crate) fn ret(&mut self) -> Option<LibSite> {
-        if self.cp0 == 0 {
-            None
-        } else {
-            self.cs0[self.cp0 as usize] = LibSite::default();
-            self.cp0 -= 1;
-            Some(self.cs0[self.cp0 as usize])
+    /// Returns resource limits applied to the program execution.
+    pub fn limits(&self) -> ExecLimits {
+        ExecLimits {
+            complexity: self.cl0,
+            cycles: self.cy_limit,
+            call_depth: self.cp_limit,
         }
     }
 
-    /// Get value from `a8` register.
-    pub fn a8(&self, idx: impl Into<Reg32>) -> Option<u8> { self.a8[idx.into().to_usize()] }
-    /// Get value from `a16` register.
-    pub fn a16(&self, idx: impl Into<Reg32>) -> Option<u16> { self.a16[idx.into().to_usize()] }
-    /// Get value from `a32` register.
-    pub fn a32(&self, idx: impl Into<Reg32>) -> Option<u32> { self.a32[idx.into().to_usize()] }
-    /// Get value from `a64` register.
-    pub fn a64(&self, idx: impl Into<Reg32>) -> Option<u64> { self.a64[idx.into().to_usize()] }
-    /// Get value from `a128` register.
-    pub fn a128(&self, idx: impl Into<Reg32>) -> Option<u128> { self.a128[idx.into().to_usize()] }
-
-    /// Sets `a8` register to a given value. Returns previous register value.
-    pub fn set_a8(&mut self, idx: impl Into<Reg32>, val: u8) -> Option<u8> {
-        self.a8[idx.into().to_usize()].replace(val)
-    }
-    /// Sets `a16` register to a given value. Returns previous register value.
-    pub fn set_a16(&mut self, idx: impl Into<Reg32>, val: u16) -> Option<u16> {
-        self.a16[idx.into().to_usize()].replace(val)
-    }
-    /// Sets `a32` register to a given value. Returns previous register value.
-    pub fn set_a32(&mut self, idx: impl Into<Reg32>, val: u32) -> Option<u32> {
-        self.a32[idx.into().to_usize()].replace(val)
+    /// Sets resource limits for the program execution. Does not reset the counters, so the limits
+    /// apply to the resources already consumed.
+    pub fn set_limits(&mut self, limits: ExecLimits) {
+        self.cl0 = limits.complexity;
+        self.cy_limit = limits.cycles;
+        self.cp_limit = limits.call_depth;
     }
-    /// Sets `a64` register to a given value. Returns previous register value.
-    pub fn set_a64(&mut self, idx: impl Into<Reg32>, val: u64) -> Option<u64> {
-        self.a64[idx.into().to_usize()].replace(val)
-    }
-    /// Sets `a128` register to a given value. Returns previous register value.
-    pub fn set_a128(&mut self, idx: impl Into<Reg32>, val: u128) -> Option<u128> {
-        self.a128[idx.into().to_usize()].replace(val)
+
+    pub(crate) fn jmp(&mut self) -> Result<(), HaltReason> {
+        if self.cy0 >= self.cy_limit {
+            self.st0 = false;
+            return Err(HaltReason::CycleLimit);
+        }
+        self.cy0 += 1;
+        Ok(())
     }
 
-    /// Clears `a8` register (sets its value to `None`). Returns previous register value.
-    pub fn clr_a8(&mut self, idx: impl Into<Reg32>) -> Option<u8> {
-        self.a8[idx.into().to_usize()].take()
+    pub(crate) fn call(&mut self, site: LibSite) -> Result<(), HaltReason> {
+        self.jmp()?;
+        if self.cp0 >= self.cp_limit {
+            self.st0 = false;
+            return Err(HaltReason::CallStackOverflow);
+        }
+        self.cs0.push(site);
+        self.cp0 += 1;
+        Ok(())
     }
-    /// Clears `a16` register (sets its value to `None`). Returns previous register value.
-    pub fn clr_a16(&mut self, idx: impl Into<Reg32>) -> Option<u16> {
-        self.a16[idx.into().to_usize()].take()
+
+    pub(crate) fn ret(&mut self) -> Option<LibSite> {
+        let site = self.cs0.pop()?;
+        self.cp0 -= 1;
+        Some(site)
     }
-    /// Clears `a32` register (sets its value to `None`). Returns previous register value.
-    pub fn clr_a32(&mut self, idx: impl Into<Reg32>) -> Option<u32> {
-        self.a32[idx.into().to_usize()].take()
+
+    accessors! {
+        a8: u8,
+        a16: u16,
+        a32: u32,
+        a64: u64,
+        a128: u128,
+        a256: u256,
+        a512: u512,
+        a1024: u1024,
+
+        f16b: bf16 => normalize_float,
+        f16: ieee::Half => normalize_float,
+        f32: ieee::Single => normalize_float,
+        f64: ieee::Double => normalize_float,
+        f80: ieee::X87DoubleExtended => normalize_float,
+        f128: ieee::Quad => normalize_float,
+        f256: ieee::Oct => normalize_float,
+
+        r128: [u8; 16],
+        r160: [u8; 20],
+        r256: [u8; 32],
+        r512: [u8; 64],
+        r1024: [u8; 128],
+        r2048: [u8; 256],
+        r4096: [u8; 512],
+        r8192: [u8; 1024],
     }
-    /// Clears `a64` register (sets its value to `None`). Returns previous register value.
-    pub fn clr_a64(&mut self, idx: impl Into<Reg32>) -> Option<u64> {
-        self.a64[idx.into().to_usize()].take()
+
+    /// Returns value of `a8`-`a64` register extended to 64 bits, without converting it into a
+    /// [`Number`]. Returns `None` if the register is not set or is wider than 64 bits.
+    #[inline]
+    pub(crate) fn a_u64(&self, reg: RegA, idx: Reg32) -> Option<u64> {
+        let idx = idx.to_usize();
+        match reg {
+            RegA::A8 => self.a8.value(idx).map(u64::from),
+            RegA::A16 => self.a16.value(idx).map(u64::from),
+            RegA::A32 => self.a32.value(idx).map(u64::from),
+            RegA::A64 => self.a64.value(idx),
+            RegA::A128 | RegA::A256 | RegA::A512 | RegA::A1024 => None,
+        }
     }
-    /// Clears `a128` register (sets its value to `None`). Returns previous register value.
-    pub fn clr_a128(&mut self, idx: impl Into<Reg32>) -> Option<u128> {
-        self.a128[idx.into().to_usize()].take()
+
+    /// Sets `a8`-`a64` register to a value, which must fit into the register bit size. Returns
+    /// whether the register has been set to some value, like [`CoreRegs::set_n`] does.
+    #[inline]
+    pub(crate) fn set_a_u64(&mut self, reg: RegA, idx: Reg32, val: Option<u64>) -> bool {
+        let index = idx.to_usize();
+        match reg {
+            RegA::A8 => {
+                self.a8.replace(index, val.map(|val| val as u8));
+            }
+            RegA::A16 => {
+                self.a16.replace(index, val.map(|val| val as u16));
+            }
+            RegA::A32 => {
+                self.a32.replace(index, val.map(|val| val as u32));
+            }
+            RegA::A64 => {
+                self.a64.replace(index, val);
+            }
+            RegA::A128 | RegA::A256 | RegA::A512 | RegA::A1024 => {
+                return self.set_n(reg, idx, val.map(Number::from));
+            }
+        }
+        val.is_some()
     }
 
     /// Gets `s16` register value.
@@ -310,7 +489,7 @@ impl CoreRegs {
                     RegA::A128 => self.a128[index].map(Number::from),
                     RegA::A256 => self.a256[index].map(Number::from),
                     RegA::A512 => self.a512[index].map(Number::from),
-                    RegA::A1024 => self.a1024[index].map(Number::from),
+                    RegA::A1024 => self.a1024.get(index).copied().map(Number::from),
                 };
                 n.into()
             }
@@ -321,10 +500,10 @@ impl CoreRegs {
                     RegR::R160 => self.r160[index].map(Number::from),
                     RegR::R256 => self.r256[index].map(Number::from),
                     RegR::R512 => self.r512[index].map(Number::from),
-                    RegR::R1024 => self.r1024[index].map(Number::from),
-                    RegR::R2048 => self.r2048[index].map(Number::from),
-                    RegR::R4096 => self.r4096[index].map(Number::from),
-                    RegR::R8192 => self.r8192[index].map(Number::from),
+                    RegR::R1024 => self.r1024.get(index).copied().map(Number::from),
+                    RegR::R2048 => self.r2048.get(index).copied().map(Number::from),
+                    RegR::R4096 => self.r4096.get(index).copied().map(Number::from),
+                    RegR::R8192 => self.r8192.get(index).copied().map(Number::from),
                 };
                 n.into()
             }
@@ -345,6 +524,31 @@ impl CoreRegs {
         }
     }
 
+    /// Retrieves numeric register value bit-wise, including float values which are not numbers
+    /// (like NaNs), which are reported by [`CoreRegs::get_n`] as undefined.
+    #[cfg(feature = "std")]
+    pub(crate) fn get_n_bits(
+        &self,
+        reg: impl Into<RegAFR>,
+        index: impl Into<Reg32>,
+    ) -> MaybeNumber {
+        let reg = reg.into();
+        let index = index.into();
+        let idx = index.to_usize();
+        let n = match reg {
+            RegAFR::A(_) | RegAFR::R(_) => return self.get_n(reg, index),
+            RegAFR::F(RegF::F16B) => self.f16b[idx].map(|v| Number::from(v.to_bits())),
+            RegAFR::F(RegF::F16) => self.f16[idx].map(|v| Number::from(v.to_bits())),
+            RegAFR::F(RegF::F32) => self.f32[idx].map(|v| Number::from(v.to_bits())),
+            RegAFR::F(RegF::F64) => self.f64[idx].map(|v| Number::from(v.to_bits())),
+            RegAFR::F(RegF::F80) => self.f80[idx].map(|v| Number::from(v.to_bits())),
+            RegAFR::F(RegF::F128) => self.f128[idx].map(|v| Number::from(v.to_bits())),
+            RegAFR::F(RegF::F256) => self.f256[idx].map(|v| Number::from(v.to_bits())),
+            RegAFR::F(RegF::F512) => self.f512[idx].map(Number::from),
+        };
+        n.and_then(|n| Number::with(&n[..reg.bytes()], reg.layout())).into()
+    }
+
     /// Retrieves mutable reference to R-register value
     pub fn get_r_mut(
         &mut self,
@@ -357,10 +561,10 @@ impl CoreRegs {
             RegR::R160 => self.r160[index].as_mut().map(|x| x.as_mut_slice()),
             RegR::R256 => self.r256[index].as_mut().map(|x| x.as_mut_slice()),
             RegR::R512 => self.r512[index].as_mut().map(|x| x.as_mut_slice()),
-            RegR::R1024 => self.r1024[index].as_mut().map(|x| x.as_mut_slice()),
-            RegR::R2048 => self.r2048[index].as_mut().map(|x| x.as_mut_slice()),
-            RegR::R4096 => self.r4096[index].as_mut().map(|x| x.as_mut_slice()),
-            RegR::R8192 => self.r8192[index].as_mut().map(|x| x.as_mut_slice()),
+            RegR::R1024 => self.r1024.get_mut(index).map(|x| x.as_mut_slice()),
+            RegR::R2048 => self.r2048.get_mut(index).map(|x| x.as_mut_slice()),
+            RegR::R4096 => self.r4096.get_mut(index).map(|x| x.as_mut_slice()),
+            RegR::R8192 => self.r8192.get_mut(index).map(|x| x.as_mut_slice()),
         }
     }
 
@@ -405,9 +609,19 @@ impl CoreRegs {
         index: impl Into<Reg32>,
         value: impl Into<MaybeNumber>,
     ) -> bool {
+        let reg = reg.into();
         let index = index.into() as usize;
         let value: Option<Number> = value.into().into();
-        match reg.into() {
+        let value = value.map(|val| {
+            if val.min_bit_len() <= reg.bits() {
+                return val;
+            }
+            let len = reg.bytes();
+            let mut fit = Number::zero(reg.layout());
+            fit[..len].copy_from_slice(&val[..len]);
+            fit
+        });
+        match reg {
             RegAFR::A(a) => match a {
                 RegA::A8 => self.a8[index] = value.map(Number::into),
                 RegA::A16 => self.a16[index] = value.map(Number::into),
@@ -416,17 +630,17 @@ impl CoreRegs {
                 RegA::A128 => self.a128[index] = value.map(Number::into),
                 RegA::A256 => self.a256[index] = value.map(Number::into),
                 RegA::A512 => self.a512[index] = value.map(Number::into),
-                RegA::A1024 => self.a1024[index] = value.map(Number::into),
+                RegA::A1024 => self.a1024.set(index, value.map(Number::into)),
             },
             RegAFR::R(r) => match r {
                 RegR::R128 => self.r128[index] = value.map(Number::into),
                 RegR::R160 => self.r160[index] = value.map(Number::into),
                 RegR::R256 => self.r256[index] = value.map(Number::into),
                 RegR::R512 => self.r512[index] = value.map(Number::into),
-                RegR::R1024 => self.r1024[index] = value.map(Number::into),
-                RegR::R2048 => self.r2048[index] = value.map(Number::into),
-                RegR::R4096 => self.r4096[index] = value.map(Number::into),
-                RegR::R8192 => self.r8192[index] = value.map(Number::into),
+                RegR::R1024 => self.r1024.set(index, value.map(Number::into)),
+                RegR::R2048 => self.r2048.set(index, value.map(Number::into)),
+                RegR::R4096 => self.r4096.set(index, value.map(Number::into)),
+                RegR::R8192 => self.r8192.set(index, value.map(Number::into)),
             },
             RegAFR::F(f) => match f {
                 RegF::F16B => self.f16b[index] = value.map(Number::into),
@@ -520,7 +734,20 @@ impl CoreRegs {
     /// this limit
     #[inline]
     pub fn acc_complexity(&mut self, instr: impl InstructionSet) -> bool {
-        self.ca0 = self.ca0.saturating_add(instr.complexity());
+        self.acc_cost(instr.complexity())
+    }
+
+    /// Accumulates instruction cost, computed with some [`crate::isa::CostModel`], into `ca0`.
+    ///
+    /// Sets `st0` to `false` if the complexity limit is reached or exceeded. Otherwise, does not
+    /// modify `st0` value.
+    ///
+    /// # Returns
+    ///
+    /// `false` if `cl0` register has value and the accumulated complexity has reached or exceeded
+    /// this limit
+    pub fn acc_cost(&mut self, cost: u64) -> bool {
+        self.ca0 = self.ca0.saturating_add(cost);
         if let Some(limit) = self.cl0 {
             if self.ca0 >= limit {
                 self.st0 = false;
@@ -537,6 +764,40 @@ impl CoreRegs {
     #[inline]
     pub fn status(&self) -> bool { self.st0 }
 
+    /// Returns value of `cy0` register, counting the number of performed jumps and calls.
+    #[inline]
+    pub fn cycles(&self) -> u16 { self.cy0 }
+
+    /// Returns value of `ca0` register, accumulating complexity of the executed instructions.
+    #[inline]
+    pub fn complexity(&self) -> u64 { self.ca0 }
+
+    /// Returns value of `cp0` register, i.e. the current depth of the call stack.
+    #[inline]
+    pub fn call_depth(&self) -> u16 { self.cp0 }
+
+    /// Returns return addresses stored in the call stack (`cs0` register), starting from the
+    /// outermost call.
+    #[inline]
+    pub fn call_stack(&self) -> &[LibSite] { &self.cs0[..self.cp0 as usize] }
+
+    /// Returns complexity which may be still accumulated before reaching the complexity limit, or
+    /// `None` if the complexity is not limited.
+    #[inline]
+    pub fn remaining_complexity(&self) -> Option<u64> {
+        self.cl0.map(|limit| limit.saturating_sub(self.ca0))
+    }
+
+    /// Returns number of jumps and calls which may be still performed before reaching the cycle
+    /// limit.
+    #[inline]
+    pub fn remaining_cycles(&self) -> u16 { self.cy_limit.saturating_sub(self.cy0) }
+
+    /// Returns number of nested calls which may be still performed before reaching the call depth
+    /// limit.
+    #[inline]
+    pub fn remaining_call_depth(&self) -> u16 { self.cp_limit.saturating_sub(self.cp0) }
+
     /// Set `st0` value to `false`.
     pub fn set_failure(&mut self) -> bool {
         let status = self.st0;
@@ -561,8 +822,8 @@ impl Debug for CoreRegs {
         write!(f, "{}cl0{}={}{} ", reg, eq, val, cl)?;
         write!(f, "{}cp0{}={}{} ", reg, eq, val, self.cp0)?;
         write!(f, "\n\t\t{}cs0{}={}", reg, eq, val)?;
-        for p in 0..=self.cp0 {
-            write!(f, "{}\n\t\t   ", self.cs0[p as usize])?;
+        for site in self.call_stack().iter().chain([&LibSite::default()]) {
+            write!(f, "{}\n\t\t   ", site)?;
         }
 
         write!(f, "\n{}A-REG:{}\t", sect, reset)?;
@@ -648,7 +909,7 @@ impl Debug for CoreRegs {
             }
         }
         for i in 0..32 {
-            if let Some(v) = self.a1024[i] {
+            if let Some(v) = self.a1024.get(i).copied() {
                 let v = Number::from(v);
                 write!(
                     f,
@@ -800,7 +1061,7 @@ impl Debug for CoreRegs {
             }
         }
         for i in 0..32 {
-            if let Some(v) = &self.r1024[i] {
+            if let Some(v) = self.r1024.get(i) {
                 write!(
                     f,
                     "{}r1024{}[{}{:02}{}]={}{}{}h\n\t\t",
@@ -816,7 +1077,7 @@ impl Debug for CoreRegs {
             }
         }
         for i in 0..32 {
-            if let Some(v) = &self.r2048[i] {
+            if let Some(v) = self.r2048.get(i) {
                 write!(
                     f,
                     "{}r2048{}[{}{:02}{}]={}{}{}h\n\t\t",
@@ -832,7 +1093,7 @@ impl Debug for CoreRegs {
             }
         }
         for i in 0..32 {
-            if let Some(v) = &self.r4096[i] {
+            if let Some(v) = self.r4096.get(i) {
                 write!(
                     f,
                     "{}r4096{}[{}{:02}{}]={}{}{}h\n\t\t",
@@ -848,7 +1109,7 @@ impl Debug for CoreRegs {
             }
         }
         for i in 0..32 {
-            if let Some(v) = &self.r8192[i] {
+            if let Some(v) = self.r8192.get(i) {
                 write!(
                     f,
                     "{}r8192{}[{}{:02}{}]={}{}{}h\n\t\t",
@@ -874,6 +1135,41 @@ impl Debug for CoreRegs {
     }
 }
 
+#[cfg(feature = "serde")]
+mod _serde {
+    use amplify::hex::{FromHex, ToHex};
+    use serde_crate::de::Error;
+    use serde_crate::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::CoreRegs;
+    use crate::data::encoding;
+
+    impl Serialize for CoreRegs {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where S: Serializer {
+            let data = encoding::Encode::serialize(self);
+            if serializer.is_human_readable() {
+                data.to_hex().serialize(serializer)
+            } else {
+                data.serialize(serializer)
+            }
+        }
+    }
+
+    impl<'de> Deserialize<'de> for CoreRegs {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where D: Deserializer<'de> {
+            let data = if deserializer.is_human_readable() {
+                let hex = String::deserialize(deserializer)?;
+                Vec::<u8>::from_hex(&hex).map_err(D::Error::custom)?
+            } else {
+                Vec::<u8>::deserialize(deserializer)?
+            };
+            <CoreRegs as encoding::Decode>::deserialize(data).map_err(D::Error::custom)
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use amplify::num::u4;
@@ -907,6 +1203,88 @@ mod test {
             regs.set_s16(u4::with(idx), ByteStr::with(format!("string index {idx}")));
         }
 
+        #[cfg(feature = "std")]
         eprintln!("{regs:#?}");
     }
+
+    #[test]
+    fn lazy_alloc() {
+        let mut regs = CoreRegs::new();
+        assert_eq!(regs.cs0.capacity(), 0);
+        assert!(regs.r8192.0.is_none());
+
+        assert!(!regs.set_n(RegR::R8192, Reg32::Reg1, MaybeNumber::none()));
+        assert!(regs.r8192.0.is_none());
+        assert!(regs.set_n(RegR::R8192, Reg32::Reg1, 7u8));
+        assert_eq!(regs.get_n(RegR::R8192, Reg32::Reg1).map(|n| n[0]), Some(7));
+        assert_eq!(regs.get_n(RegR::R8192, Reg32::Reg2), MaybeNumber::none());
+        assert!(regs.r4096.0.is_none());
+
+        let site = LibSite::with(5, zero!());
+        regs.set_limits(ExecLimits {
+            call_depth: 2,
+            ..default!()
+        });
+        assert!(regs.call(site).is_ok());
+        assert!(regs.call(site).is_ok());
+        assert!(regs.call(site).is_err());
+        assert_eq!(regs.call_depth(), 2);
+        assert_eq!(regs.call_stack(), &[site, site]);
+        assert_eq!(regs.ret(), Some(site));
+        assert_eq!(regs.ret(), Some(site));
+        assert_eq!(regs.ret(), None);
+        assert_eq!(regs.call_depth(), 0);
+    }
+
+    #[test]
+    fn typed_accessors() {
+        let mut regs = CoreRegs::new();
+        let val = u256::from(1u64) << 200;
+        assert_eq!(regs.set_a256(Reg32::Reg3, val), None);
+        assert_eq!(regs.a256(Reg32::Reg3), Some(val));
+        assert_eq!(regs.get_n(RegA::A256, Reg32::Reg3), Number::from(val).into());
+        assert_eq!(regs.clr_a256(Reg32::Reg3), Some(val));
+        assert_eq!(regs.a256(Reg32::Reg3), None);
+
+        let half = ieee::Single::from_bits(0x3F00_0000u32.into());
+        regs.set_f32(Reg32::Reg0, half);
+        assert_eq!(regs.f32(Reg32::Reg0), Some(half));
+        assert_eq!(regs.get_n(RegF::F32, Reg32::Reg0), MaybeNumber::from(half));
+
+        assert_eq!(regs.r2048(Reg32::Reg1), None);
+        assert_eq!(regs.set_r2048(Reg32::Reg1, [7u8; 256]), None);
+        assert_eq!(regs.get_n(RegR::R2048, Reg32::Reg1), Number::from([7u8; 256]).into());
+        assert_eq!(regs.set_r2048(Reg32::Reg1, [8u8; 256]), Some([7u8; 256]));
+
+        let mut bank = regs.r2048_bank();
+        assert_eq!(bank[1], Some([8u8; 256]));
+        bank[1] = None;
+        regs.set_r2048_bank(bank);
+        assert!(regs.r2048.0.is_none());
+
+        let mut bank = [None; 32];
+        bank[31] = Some(u1024::from(5u64));
+        regs.set_a1024_bank(bank);
+        assert_eq!(regs.a1024(Reg32::Reg31), Some(u1024::from(5u64)));
+        assert_eq!(regs.a1024_bank(), bank);
+        assert_eq!(regs.a8_bank(), [None; 32]);
+    }
+
+    #[test]
+    fn float_accessors() {
+        let mut regs = CoreRegs::new();
+        let neg_zero = -ieee::Single::ZERO;
+        assert_eq!(regs.set_f32(Reg32::Reg0, neg_zero), None);
+        assert!(neg_zero.is_negative());
+        assert_eq!(regs.f32(Reg32::Reg0).map(|val| val.is_negative()), Some(false));
+        assert_eq!(regs.set_f32(Reg32::Reg0, ieee::Single::NAN), Some(ieee::Single::ZERO));
+        assert_eq!(regs.f32(Reg32::Reg0), None);
+
+        let mut bank = [None; 32];
+        bank[0] = Some(bf16::NAN);
+        bank[1] = Some(bf16::NEG_ZERO);
+        regs.set_f16b_bank(bank);
+        assert_eq!(regs.f16b(Reg32::Reg0), None);
+        assert_eq!(regs.f16b(Reg32::Reg1).map(bf16::to_bits), Some(0));
+    }
 }
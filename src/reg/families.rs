@@ -23,12 +23,18 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#[cfg(feature = "alloc")]
+use alloc::borrow::ToOwned;
+#[cfg(feature = "alloc")]
+use alloc::string::ToString;
 use core::convert::TryFrom;
+use core::fmt::Display;
+use core::str::FromStr;
 
 use amplify::num::{u1, u3, u4};
 
 use crate::data as number;
-use crate::reg::Register;
+use crate::reg::{ParseRegError, Register};
 
 /// Common set of methods handled by different sets and families of VM registers
 pub trait NumericRegister: Register {
@@ -43,6 +49,15 @@ pub trait NumericRegister: Register {
     fn layout(&self) -> number::Layout;
 }
 
+/// Parses register from its name by matching it against the names of the provided registers
+fn parse_reg<R>(s: &str, all: &[R]) -> Result<R, ParseRegError>
+where R: Register + Display + Copy {
+    all.iter()
+        .find(|reg| reg.to_string() == s)
+        .copied()
+        .ok_or_else(|| ParseRegError::Unknown(R::description(), s.to_owned()))
+}
+
 /// Enumeration of integer arithmetic registers (`A`-registers)
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Display)]
 #[repr(u8)]
@@ -87,6 +102,13 @@ impl Register for RegA {
     fn description() -> &'static str { "A register" }
 }
 
+impl FromStr for RegA {
+    type Err = ParseRegError;
+
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> { parse_reg(s, &RegA::ALL) }
+}
+
 impl NumericRegister for RegA {
     #[inline]
     fn bytes(&self) -> u16 {
@@ -211,6 +233,13 @@ impl Register for RegA2 {
     fn description() -> &'static str { "A8 or A16 register" }
 }
 
+impl FromStr for RegA2 {
+    type Err = ParseRegError;
+
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> { parse_reg(s, &[RegA2::A8, RegA2::A16]) }
+}
+
 impl NumericRegister for RegA2 {
     #[inline]
     fn bytes(&self) -> u16 {
@@ -310,6 +339,13 @@ impl Register for RegF {
     fn description() -> &'static str { "F register" }
 }
 
+impl FromStr for RegF {
+    type Err = ParseRegError;
+
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> { parse_reg(s, &RegF::ALL) }
+}
+
 impl NumericRegister for RegF {
     #[inline]
     fn bytes(&self) -> u16 {
@@ -451,6 +487,13 @@ impl Register for RegR {
     fn description() -> &'static str { "R register" }
 }
 
+impl FromStr for RegR {
+    type Err = ParseRegError;
+
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> { parse_reg(s, &RegR::ALL) }
+}
+
 impl NumericRegister for RegR {
     #[inline]
     fn bytes(&self) -> u16 {
@@ -561,6 +604,19 @@ impl Register for RegAll {
     fn description() -> &'static str { "A, F, R or S register" }
 }
 
+impl FromStr for RegAll {
+    type Err = ParseRegError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "s16" {
+            return Ok(RegAll::S);
+        }
+        RegAFR::from_str(s)
+            .map(RegAll::from)
+            .map_err(|_| ParseRegError::Unknown(Self::description(), s.to_owned()))
+    }
+}
+
 impl RegAll {
     /// Returns inner A-register type, if any
     #[inline]
@@ -626,6 +682,17 @@ impl From<&RegA2> for RegAll {
     fn from(reg: &RegA2) -> Self { Self::A(reg.into()) }
 }
 
+impl From<RegAFR> for RegAll {
+    #[inline]
+    fn from(reg: RegAFR) -> Self {
+        match reg {
+            RegAFR::A(a) => Self::A(a),
+            RegAFR::F(f) => Self::F(f),
+            RegAFR::R(r) => Self::R(r),
+        }
+    }
+}
+
 impl From<RegAF> for RegAll {
     #[inline]
     fn from(reg: RegAF) -> Self {
@@ -694,6 +761,18 @@ impl Register for RegAFR {
     fn description() -> &'static str { "A, F or R register" }
 }
 
+impl FromStr for RegAFR {
+    type Err = ParseRegError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        RegA::from_str(s)
+            .map(RegAFR::A)
+            .or_else(|_| RegF::from_str(s).map(RegAFR::F))
+            .or_else(|_| RegR::from_str(s).map(RegAFR::R))
+            .map_err(|_| ParseRegError::Unknown(Self::description(), s.to_owned()))
+    }
+}
+
 impl NumericRegister for RegAFR {
     #[inline]
     fn bytes(&self) -> u16 {
@@ -844,6 +923,17 @@ impl Register for RegAF {
     fn description() -> &'static str { "A or F register" }
 }
 
+impl FromStr for RegAF {
+    type Err = ParseRegError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        RegA::from_str(s)
+            .map(RegAF::A)
+            .or_else(|_| RegF::from_str(s).map(RegAF::F))
+            .map_err(|_| ParseRegError::Unknown(Self::description(), s.to_owned()))
+    }
+}
+
 impl NumericRegister for RegAF {
     #[inline]
     fn bytes(&self) -> u16 {
@@ -949,6 +1039,17 @@ impl Register for RegAR {
     fn description() -> &'static str { "A or R register" }
 }
 
+impl FromStr for RegAR {
+    type Err = ParseRegError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        RegA::from_str(s)
+            .map(RegAR::A)
+            .or_else(|_| RegR::from_str(s).map(RegAR::R))
+            .map_err(|_| ParseRegError::Unknown(Self::description(), s.to_owned()))
+    }
+}
+
 impl NumericRegister for RegAR {
     #[inline]
     fn bytes(&self) -> u16 {
@@ -1061,6 +1162,15 @@ impl Register for RegBlockAR {
     fn description() -> &'static str { "A or R register block" }
 }
 
+impl FromStr for RegBlockAR {
+    type Err = ParseRegError;
+
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_reg(s, &[RegBlockAR::A, RegBlockAR::R])
+    }
+}
+
 impl RegBlockAR {
     /// Converts value into specific register matching the provided bit dimension. If the register
     /// with the given dimension does not exists, returns `None`.
@@ -1108,6 +1218,15 @@ impl Register for RegBlockAFR {
     fn description() -> &'static str { "A, F or R register block" }
 }
 
+impl FromStr for RegBlockAFR {
+    type Err = ParseRegError;
+
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_reg(s, &[RegBlockAFR::A, RegBlockAFR::F, RegBlockAFR::R])
+    }
+}
+
 impl RegBlockAFR {
     /// Converts value into specific register matching the provided bit dimension. If the register
     /// with the given dimension does not exists, returns `None`.
@@ -1160,6 +1279,15 @@ impl Register for RegBlock {
     fn description() -> &'static str { "A, F, R or S register block" }
 }
 
+impl FromStr for RegBlock {
+    type Err = ParseRegError;
+
+    #[inline]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_reg(s, &[RegBlock::A, RegBlock::F, RegBlock::R, RegBlock::S])
+    }
+}
+
 impl From<RegAll> for RegBlock {
     fn from(reg: RegAll) -> Self {
         match reg {
@@ -23,12 +23,24 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+#[cfg(feature = "alloc")]
+use alloc::borrow::ToOwned;
 use core::convert::TryFrom;
+use core::str::FromStr;
 
 use amplify::num::error::OverflowError;
 use amplify::num::{u3, u4, u5};
 
-use crate::reg::{RegAll, Register};
+use crate::reg::{ParseRegError, RegAll, Register};
+
+/// Parses register index in `[n]` form, checking that it is below `count`
+fn parse_index(s: &str, count: usize) -> Option<usize> {
+    let idx = s.strip_prefix('[')?.strip_suffix(']')?;
+    if !idx.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    usize::from_str(idx).ok().filter(|idx| *idx < count)
+}
 
 /// All possible register indexes for `a` and `r` register sets
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Display)]
@@ -212,6 +224,16 @@ impl Register for Reg32 {
     fn description() -> &'static str { "5-bit register index" }
 }
 
+impl FromStr for Reg32 {
+    type Err = ParseRegError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_index(s, Reg32::ALL.len())
+            .map(|idx| Reg32::ALL[idx])
+            .ok_or_else(|| ParseRegError::Unknown(Self::description(), s.to_owned()))
+    }
+}
+
 impl From<&Reg32> for u5 {
     #[inline]
     fn from(reg32: &Reg32) -> Self { u5::with(*reg32 as u8) }
@@ -376,6 +398,16 @@ impl Register for Reg16 {
     fn description() -> &'static str { "4-bit register index" }
 }
 
+impl FromStr for Reg16 {
+    type Err = ParseRegError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_index(s, Reg16::ALL.len())
+            .map(|idx| Reg16::ALL[idx])
+            .ok_or_else(|| ParseRegError::Unknown(Self::description(), s.to_owned()))
+    }
+}
+
 impl From<&Reg16> for u4 {
     #[inline]
     fn from(reg16: &Reg16) -> Self { u4::with(*reg16 as u8) }
@@ -487,6 +519,16 @@ impl Register for Reg8 {
     fn description() -> &'static str { "3-bit register index" }
 }
 
+impl FromStr for Reg8 {
+    type Err = ParseRegError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_index(s, Reg8::ALL.len())
+            .map(|idx| Reg8::ALL[idx])
+            .ok_or_else(|| ParseRegError::Unknown(Self::description(), s.to_owned()))
+    }
+}
+
 impl From<&Reg8> for u3 {
     #[inline]
     fn from(reg8: &Reg8) -> Self { u3::with(*reg8 as u8) }
@@ -555,6 +597,17 @@ impl Register for RegS {
     fn description() -> &'static str { "4-bit S register index" }
 }
 
+impl FromStr for RegS {
+    type Err = ParseRegError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.strip_prefix("s16")
+            .and_then(|idx| parse_index(idx, 16))
+            .map(|idx| RegS(u4::with(idx as u8)))
+            .ok_or_else(|| ParseRegError::Unknown(Self::description(), s.to_owned()))
+    }
+}
+
 impl Default for RegS {
     #[inline]
     fn default() -> Self { RegS(u4::MIN) }
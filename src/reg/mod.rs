@@ -25,17 +25,32 @@
 
 //! AluVM registers system
 
+#[cfg(feature = "alloc")]
+use alloc::borrow::ToOwned;
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+use core::str::FromStr;
+
 mod core_regs;
 mod families;
 mod indexes;
 
-pub use core_regs::{CoreRegs, CALL_STACK_SIZE};
+pub use core_regs::{CoreRegs, ExecLimits, CALL_STACK_SIZE};
 pub use families::{
     NumericRegister, RegA, RegA2, RegAF, RegAFR, RegAR, RegAll, RegBlock, RegBlockAFR, RegBlockAR,
     RegF, RegR,
 };
 pub use indexes::{Reg16, Reg32, Reg8, RegS};
 
+/// Errors parsing register names and indexes
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Display)]
+#[cfg_attr(feature = "std", derive(Error))]
+#[display(doc_comments)]
+pub enum ParseRegError {
+    /// `{1}` is not a valid {0}
+    Unknown(/** Register description */ &'static str, /** Unrecognized string */ String),
+}
+
 /// Trait marking all types representing register family, specific register or register index
 pub trait Register: Default {
     /// Text description of the register family
@@ -103,3 +118,47 @@ impl Reg {
         }
     }
 }
+
+impl FromStr for Reg {
+    type Err = ParseRegError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.starts_with("s16") {
+            return RegS::from_str(s).map(Reg::S);
+        }
+        let err = || ParseRegError::Unknown("register", s.to_owned());
+        let pos = s.find('[').ok_or_else(err)?;
+        let (reg, index) = s.split_at(pos);
+        let reg = RegAFR::from_str(reg).map_err(|_| err())?;
+        let index = Reg32::from_str(index).map_err(|_| err())?;
+        Ok(Reg::new(reg, index))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::string::ToString;
+
+    use super::*;
+
+    #[test]
+    fn parse() {
+        for reg in [
+            Reg::new(RegA::A8, Reg32::Reg0),
+            Reg::new(RegF::F16B, Reg32::Reg31),
+            Reg::new(RegR::R8192, Reg32::Reg17),
+            Reg::S(RegS::from(15)),
+        ] {
+            assert_eq!(Reg::from_str(&reg.to_string()), Ok(reg));
+        }
+        assert_eq!(RegAR::from_str("r160"), Ok(RegAR::R(RegR::R160)));
+        assert_eq!(RegBlockAR::from_str("a"), Ok(RegBlockAR::A));
+        assert_eq!(Reg16::from_str("[15]"), Ok(Reg16::Reg15));
+        assert!(Reg16::from_str("[16]").is_err());
+        assert!(RegS::from_str("s16[16]").is_err());
+        assert!(RegAF::from_str("r128").is_err());
+        assert!(Reg::from_str("a12[1]").is_err());
+        assert!(Reg::from_str("a16[+1]").is_err());
+        assert_eq!(RegA::from_str("a7").unwrap_err().to_string(), "`a7` is not a valid A register");
+    }
+}
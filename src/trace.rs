@@ -0,0 +1,243 @@
+// Reference rust implementation of AluVM (arithmetic logic unit virtual machine).
+// To find more on AluVM please check <https://aluvm.org>
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2021-2024 by
+//     Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2021-2022 LNP/BP Standards Association. All rights reserved.
+// Copyright (C) 2023-2024 UBIDECO Labs,
+//     Institute for Distributed and Cognitive Computing, Switzerland.
+//     All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Machine-readable execution traces
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Formatter, Write};
+use std::io;
+
+use amplify::hex::ToHex;
+
+use crate::data::RegValue;
+use crate::isa::{ExecStep, InstructionSet};
+use crate::library::{LibId, LibSite};
+use crate::observer::Observer;
+use crate::reg::{CoreRegs, Reg};
+
+/// Record on a single executed instruction.
+///
+/// Displays as a single-line JSON object:
+///
+/// ```json
+/// {"lib":"...","pos":0,"instr":"...","src":{"a8[0]":"1"},"dst":{},"st0":true,"cy0":0,"ca0":2}
+/// ```
+///
+/// Numeric register values are represented by strings in the same format as they are displayed,
+/// string register values by a hex-encoded string, and registers in the undefined state by `null`.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct TraceRecord {
+    /// Library containing the instruction
+    pub lib: LibId,
+
+    /// Offset of the instruction within the library code segment
+    pub pos: u16,
+
+    /// Disassembled instruction
+    pub instr: String,
+
+    /// Values of the instruction source registers before the instruction execution
+    pub src: Vec<(Reg, RegValue)>,
+
+    /// Values of the instruction destination registers after the instruction execution
+    pub dst: Vec<(Reg, RegValue)>,
+
+    /// Value of `st0` register after the instruction execution
+    pub st0: bool,
+
+    /// Value of `cy0` register after the instruction execution
+    pub cy0: u16,
+
+    /// Value of `ca0` register after the instruction execution
+    pub ca0: u64,
+}
+
+impl TraceRecord {
+    fn push_reg(regs: &mut Vec<(Reg, RegValue)>, reg: Reg, state: &CoreRegs) {
+        if regs.iter().all(|(r, _)| *r != reg) {
+            regs.push((reg, state.get(reg)));
+        }
+    }
+
+    fn fmt_str(f: &mut Formatter<'_>, s: &str) -> fmt::Result {
+        f.write_char('"')?;
+        for c in s.chars() {
+            match c {
+                '"' => f.write_str("\\\"")?,
+                '\\' => f.write_str("\\\\")?,
+                '\n' => f.write_str("\\n")?,
+                '\r' => f.write_str("\\r")?,
+                '\t' => f.write_str("\\t")?,
+                c if c.is_control() => write!(f, "\\u{:04x}", c as u32)?,
+                c => f.write_char(c)?,
+            }
+        }
+        f.write_char('"')
+    }
+
+    fn fmt_regs(f: &mut Formatter<'_>, regs: &[(Reg, RegValue)]) -> fmt::Result {
+        f.write_char('{')?;
+        for (no, (reg, val)) in regs.iter().enumerate() {
+            if no > 0 {
+                f.write_char(',')?;
+            }
+            Self::fmt_str(f, &reg.to_string())?;
+            f.write_char(':')?;
+            match val {
+                RegValue::Number(n) if n.is_some() => Self::fmt_str(f, &n.to_string())?,
+                RegValue::String(Some(s)) => Self::fmt_str(f, &s.as_ref().to_hex())?,
+                RegValue::Number(_) | RegValue::String(None) => f.write_str("null")?,
+            }
+        }
+        f.write_char('}')
+    }
+}
+
+impl Display for TraceRecord {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("{\"lib\":")?;
+        Self::fmt_str(f, &self.lib.to_string())?;
+        write!(f, ",\"pos\":{},\"instr\":", self.pos)?;
+        Self::fmt_str(f, &self.instr)?;
+        f.write_str(",\"src\":")?;
+        Self::fmt_regs(f, &self.src)?;
+        f.write_str(",\"dst\":")?;
+        Self::fmt_regs(f, &self.dst)?;
+        write!(f, ",\"st0\":{},\"cy0\":{},\"ca0\":{}}}", self.st0, self.cy0, self.ca0)
+    }
+}
+
+/// Observer writing a [`TraceRecord`] for each of the executed instructions to the provided
+/// writer in JSON Lines format.
+///
+/// Since observer hooks can't fail, the first I/O error stops the tracing and is reported by
+/// [`JsonTrace::into_inner`].
+#[derive(Debug)]
+pub struct JsonTrace<W: io::Write> {
+    writer: W,
+    record: Option<TraceRecord>,
+    error: Option<io::Error>,
+}
+
+impl<W: io::Write> JsonTrace<W> {
+    /// Constructs trace recorder writing to the provided writer.
+    pub fn new(writer: W) -> Self {
+        JsonTrace {
+            writer,
+            record: None,
+            error: None,
+        }
+    }
+
+    /// Returns the underlying writer, or the first I/O error which has happened during the
+    /// tracing.
+    pub fn into_inner(self) -> io::Result<W> {
+        match self.error {
+            Some(err) => Err(err),
+            None => Ok(self.writer),
+        }
+    }
+}
+
+impl<Isa, W> Observer<Isa> for JsonTrace<W>
+where
+    Isa: InstructionSet,
+    W: io::Write,
+{
+    fn before_instr(&mut self, site: LibSite, instr: &Isa, regs: &CoreRegs) {
+        let mut src = vec![];
+        instr.for_each_src_reg(|reg| TraceRecord::push_reg(&mut src, reg, regs));
+        self.record = Some(TraceRecord {
+            lib: site.lib,
+            pos: site.pos,
+            instr: instr.to_string(),
+            src,
+            dst: none!(),
+            st0: regs.status(),
+            cy0: regs.cycles(),
+            ca0: regs.complexity(),
+        });
+    }
+
+    fn after_instr(&mut self, _: LibSite, instr: &Isa, regs: &CoreRegs, _: bool, _: ExecStep) {
+        let Some(mut record) = self.record.take() else {
+            return;
+        };
+        if self.error.is_some() {
+            return;
+        }
+        instr.for_each_dst_reg(|reg| TraceRecord::push_reg(&mut record.dst, reg, regs));
+        record.st0 = regs.status();
+        record.cy0 = regs.cycles();
+        record.ca0 = regs.complexity();
+        if let Err(err) = writeln!(self.writer, "{record}") {
+            self.error = Some(err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::data::MaybeNumber;
+    use crate::isa::{ArithmeticOp, Instr, IntFlags, PutOp};
+    use crate::library::Lib;
+    use crate::reg::{Reg32, RegA};
+    use crate::Vm;
+
+    #[test]
+    fn json_lines() {
+        let code: Vec<Instr> = vec![
+            Instr::Put(PutOp::PutA(RegA::A8, Reg32::Reg0, Box::new(MaybeNumber::from(3u8)))),
+            Instr::Arithmetic(ArithmeticOp::AddA(
+                IntFlags::unsigned_checked(),
+                RegA::A8,
+                Reg32::Reg0,
+                Reg32::Reg1,
+            )),
+        ];
+        let lib = Lib::assemble(&code).unwrap();
+        let mut vm = Vm::<Instr>::new();
+        let mut trace = JsonTrace::new(Vec::new());
+        vm.run_observed(LibSite::with(0, lib.id()), |_: LibId| Some(&lib), &(), &mut trace);
+        let out = String::from_utf8(trace.into_inner().unwrap()).unwrap();
+        let lib = lib.id();
+        let expected = format!(
+            concat!(
+                r#"{{"lib":"{lib}","pos":0,"instr":"{}","src":{{}},"dst":{{"a8[0]":"3"}},"#,
+                r#""st0":true,"cy0":0,"ca0":2}}"#,
+                "\n",
+                r#"{{"lib":"{lib}","pos":4,"instr":"{}","src":{{"a8[0]":"3","a8[1]":null}},"#,
+                r#""dst":{{"a8[1]":null}},"st0":false,"cy0":0,"ca0":3}}"#,
+                "\n",
+            ),
+            code[0],
+            code[1],
+            lib = lib,
+        );
+        assert_eq!(out, expected);
+    }
+}
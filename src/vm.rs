@@ -27,20 +27,134 @@
 
 #[cfg(feature = "alloc")]
 use alloc::boxed::Box;
+#[cfg(feature = "alloc")]
+use alloc::collections::BTreeSet;
 use core::marker::PhantomData;
+#[cfg(feature = "std")]
+use std::io;
+
+#[cfg(feature = "std")]
+use crate::data::encoding::{Decode, DecodeError, Encode};
+use crate::debugger::Debugger;
+use crate::isa::{CostModel, DefaultCost, HaltReason, Instr, InstructionSet, ReservedOp};
+use crate::library::{ExecLib, Lib, LibExit, LibId, LibResolver, LibSite};
+use crate::observer::{DefaultObserver, Observer};
+use crate::reg::{CoreRegs, ExecLimits};
+
+/// Report on the program execution produced by [`Vm::run`]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct ExecReport {
+    /// Reason for which the execution has halted
+    pub halt: HaltReason,
+
+    /// Location in the code where the execution has halted. If the execution was suspended by
+    /// [`HaltReason::Yield`], location from which it has to be resumed.
+    pub site: LibSite,
+
+    /// Value of the `st0` register at the end of the program execution
+    pub status: bool,
+
+    /// Number of jumps and calls performed during the execution (`cy0` register value)
+    pub cycles: u16,
+
+    /// Accumulated complexity of the executed instructions (`ca0` register value)
+    pub complexity: u64,
+
+    /// Depth of the call stack at the moment of halting (`cp0` register value)
+    pub call_depth: u16,
+}
+
+impl ExecReport {
+    pub(crate) fn with(site: LibSite, halt: HaltReason, registers: &CoreRegs) -> Self {
+        ExecReport {
+            halt,
+            site,
+            status: registers.status(),
+            cycles: registers.cycles(),
+            complexity: registers.complexity(),
+            call_depth: registers.call_depth(),
+        }
+    }
+
+    /// Returns host request code if the execution was suspended by an instruction and may be
+    /// resumed with [`Vm::resume`].
+    pub fn yielded(&self) -> Option<u16> {
+        match self.halt {
+            HaltReason::Yield(request) => Some(request),
+            _ => None,
+        }
+    }
+}
+
+/// Snapshot of the virtual machine state taken when the program execution was paused, which can
+/// be used to resume the execution later, possibly by a different process.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(crate = "serde_crate"))]
+pub struct VmSnapshot {
+    /// Location of the next instruction to execute
+    pub site: LibSite,
+
+    /// State of all registers, including the call stack
+    pub registers: Box<CoreRegs>,
+}
+
+#[cfg(feature = "std")]
+impl Encode for VmSnapshot {
+    type Error = io::Error;
+
+    fn encode(&self, mut writer: impl io::Write) -> Result<usize, Self::Error> {
+        Ok(self.site.encode(&mut writer)? + self.registers.encode(&mut writer)?)
+    }
+}
 
-use crate::isa::{Instr, InstructionSet, ReservedOp};
-use crate::library::{Lib, LibId, LibSite};
-use crate::reg::CoreRegs;
+#[cfg(feature = "std")]
+impl Decode for VmSnapshot {
+    type Error = DecodeError;
+
+    fn decode(mut reader: impl io::Read) -> Result<Self, Self::Error>
+    where Self: Sized {
+        let site = LibSite::decode(&mut reader)?;
+        let registers = Box::new(CoreRegs::decode(&mut reader)?);
+        Ok(VmSnapshot { site, registers })
+    }
+}
+
+/// Policy for handling libraries which can't be resolved during the program execution.
+///
+/// In all cases the execution halts with [`HaltReason::LibNotFound`] reporting id of the missing
+/// library, setting `st0` register to `false`.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug, Default)]
+pub enum LibPolicy {
+    /// Halt the execution once the code of the missing library is called.
+    #[default]
+    Fail,
+
+    /// Check that all libraries the entry point library depends on, directly or transitively,
+    /// can be resolved before starting the execution; halt without executing any instruction
+    /// otherwise.
+    Preflight,
+}
 
 /// Alu virtual machine providing single-core execution environment
 #[derive(Clone, Debug, Default)]
-pub struct Vm<Isa = Instr<ReservedOp>>
-where Isa: InstructionSet
+pub struct Vm<Isa = Instr<ReservedOp>, Cost = DefaultCost>
+where
+    Isa: InstructionSet,
+    Cost: CostModel<Isa>,
 {
     /// A set of registers
     pub registers: Box<CoreRegs>,
 
+    /// Cost schedule used to account complexity of the executed instructions
+    pub cost: Cost,
+
+    /// Policy for libraries which can't be resolved
+    pub lib_policy: LibPolicy,
+
+    /// Refuse to execute libraries which do not pass [`Lib::verify`], halting with
+    /// [`HaltReason::Unverified`]
+    pub verify_libs: bool,
+
     phantom: PhantomData<Isa>,
 }
 
@@ -52,6 +166,37 @@ where Isa: InstructionSet
     pub fn new() -> Self {
         Self {
             registers: Box::default(),
+            cost: DefaultCost,
+            lib_policy: LibPolicy::default(),
+            verify_libs: false,
+            phantom: Default::default(),
+        }
+    }
+
+    /// Constructs new virtual machine instance with the provided resource limits.
+    pub fn with_limits(limits: ExecLimits) -> Self {
+        Self {
+            registers: Box::new(CoreRegs::with_limits(limits)),
+            cost: DefaultCost,
+            lib_policy: LibPolicy::default(),
+            verify_libs: false,
+            phantom: Default::default(),
+        }
+    }
+}
+
+impl<Isa, Cost> Vm<Isa, Cost>
+where
+    Isa: InstructionSet,
+    Cost: CostModel<Isa>,
+{
+    /// Constructs new virtual machine instance using a custom cost schedule.
+    pub fn with_cost(cost: Cost) -> Self {
+        Self {
+            registers: Box::default(),
+            cost,
+            lib_policy: LibPolicy::default(),
+            verify_libs: false,
             phantom: Default::default(),
         }
     }
@@ -60,23 +205,554 @@ where Isa: InstructionSet
     ///
     /// # Returns
     ///
-    /// Value of the `st0` register at the end of the program execution.
-    pub fn exec<'prog>(
+    /// Value of the `st0` register at the end of the program execution, or `false` if the
+    /// execution was suspended by an instruction yielding to the host (use [`Vm::run`] and
+    /// [`Vm::resume`] to handle such programs).
+    pub fn exec<Resolver>(
         &mut self,
         entry_point: LibSite,
-        lib_resolver: impl Fn(LibId) -> Option<&'prog Lib>,
+        lib_resolver: Resolver,
         context: &Isa::Context<'_>,
-    ) -> bool {
-        let mut call = Some(entry_point);
-        while let Some(ref mut site) = call {
-            if let Some(lib) = lib_resolver(site.lib) {
-                call = lib.exec::<Isa>(site.pos, &mut self.registers, context);
-            } else if let Some(pos) = site.pos.checked_add(1) {
-                site.pos = pos;
-            } else {
-                call = None;
+    ) -> bool
+    where
+        Resolver: LibResolver,
+        Resolver::Lib: ExecLib<Isa>,
+    {
+        let report = self.run(entry_point, lib_resolver, context);
+        report.status && report.yielded().is_none()
+    }
+
+    /// Takes snapshot of the virtual machine state, paused at the provided location.
+    pub fn snapshot(&self, site: LibSite) -> VmSnapshot {
+        VmSnapshot {
+            site,
+            registers: self.registers.clone(),
+        }
+    }
+
+    /// Restores virtual machine state from the snapshot, returning location from which the
+    /// execution should be resumed with [`Vm::run`] or [`Vm::debugger`].
+    pub fn restore(&mut self, snapshot: VmSnapshot) -> LibSite {
+        self.registers = snapshot.registers;
+        snapshot.site
+    }
+
+    /// Starts debugging session for the program starting from the provided entry point.
+    pub fn debugger<Resolver>(
+        &mut self,
+        entry_point: LibSite,
+        lib_resolver: Resolver,
+    ) -> Debugger<'_, Isa, Cost, Resolver>
+    where
+        Resolver: LibResolver,
+        Resolver::Lib: ExecLib<Isa>,
+    {
+        Debugger::new(self, entry_point, lib_resolver)
+    }
+
+    /// Executes the program starting from the provided entry point, reporting why and where the
+    /// execution has halted together with the final state of the control flow registers.
+    pub fn run<Resolver>(
+        &mut self,
+        entry_point: LibSite,
+        lib_resolver: Resolver,
+        context: &Isa::Context<'_>,
+    ) -> ExecReport
+    where
+        Resolver: LibResolver,
+        Resolver::Lib: ExecLib<Isa>,
+    {
+        self.run_observed(entry_point, lib_resolver, context, &mut DefaultObserver::default())
+    }
+
+    /// Resumes the program execution suspended by an instruction yielding to the host, keeping
+    /// the registers intact. If the execution has halted instead of being suspended, does nothing
+    /// and returns the same report.
+    pub fn resume<Resolver>(
+        &mut self,
+        report: ExecReport,
+        lib_resolver: Resolver,
+        context: &Isa::Context<'_>,
+    ) -> ExecReport
+    where
+        Resolver: LibResolver,
+        Resolver::Lib: ExecLib<Isa>,
+    {
+        if report.yielded().is_none() {
+            return report;
+        }
+        self.run(report.site, lib_resolver, context)
+    }
+
+    /// Executes the program starting from the provided entry point in the same way as
+    /// [`Vm::run`], reporting each of the execution steps to the provided observer.
+    pub fn run_observed<Resolver>(
+        &mut self,
+        entry_point: LibSite,
+        lib_resolver: Resolver,
+        context: &Isa::Context<'_>,
+        observer: &mut impl Observer<Isa>,
+    ) -> ExecReport
+    where
+        Resolver: LibResolver,
+        Resolver::Lib: ExecLib<Isa>,
+    {
+        if let Err(halt) = self.preflight(entry_point, &lib_resolver) {
+            return self.refuse(entry_point, halt, observer);
+        }
+
+        let mut verified = BTreeSet::new();
+        let mut call = entry_point;
+        let (site, halt) = loop {
+            let lib = match self.load_lib(call, &lib_resolver, &mut verified) {
+                Ok(lib) => lib,
+                Err(halt) => return self.refuse(call, halt, observer),
             };
+            let regs = &mut self.registers;
+            match lib.run_with_id(call.lib, call.pos, regs, context, &self.cost, observer) {
+                LibExit::Call(site) => call = site,
+                LibExit::Halt(site, halt) => break (site, halt),
+                LibExit::Yield(site, request) => break (site, HaltReason::Yield(request)),
+            }
+        };
+        ExecReport::with(site, halt, &self.registers)
+    }
+
+    /// Checks that the libraries required for the execution starting from the entry point are
+    /// available, if required by [`Vm::lib_policy`].
+    pub(crate) fn preflight(
+        &self,
+        entry_point: LibSite,
+        lib_resolver: &impl LibResolver,
+    ) -> Result<(), HaltReason> {
+        if self.lib_policy == LibPolicy::Preflight {
+            lib_resolver
+                .check_deps(entry_point.lib)
+                .map_err(|err| HaltReason::LibNotFound(err.lib_id()))?;
+        }
+        Ok(())
+    }
+
+    /// Resolves library containing the code at `site`, checking that it has the requested id, and,
+    /// if [`Vm::verify_libs`] is set, verifies it unless the library id is already present in
+    /// `verified` set.
+    pub(crate) fn load_lib<'r, Resolver>(
+        &self,
+        site: LibSite,
+        lib_resolver: &'r Resolver,
+        verified: &mut BTreeSet<LibId>,
+    ) -> Result<Resolver::Handle<'r>, HaltReason>
+    where
+        Resolver: LibResolver,
+        Resolver::Lib: ExecLib<Isa>,
+    {
+        let lib =
+            lib_resolver.resolve(site.lib).map_err(|err| HaltReason::LibNotFound(err.lib_id()))?;
+        if lib.lib_id() != site.lib {
+            return Err(HaltReason::LibNotFound(site.lib));
+        }
+        if self.verify_libs && !verified.contains(&site.lib) {
+            if AsRef::<Lib>::as_ref(&*lib).verify::<Isa>().is_err() {
+                return Err(HaltReason::Unverified(site.lib));
+            }
+            verified.insert(site.lib);
+        }
+        Ok(lib)
+    }
+
+    fn refuse(
+        &mut self,
+        site: LibSite,
+        halt: HaltReason,
+        observer: &mut impl Observer<Isa>,
+    ) -> ExecReport {
+        self.registers.set_failure();
+        observer.on_halt(site, halt, &self.registers);
+        ExecReport::with(site, halt, &self.registers)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::collections::BTreeSet;
+    use alloc::vec::Vec;
+    use core::ops::RangeInclusive;
+
+    #[cfg(feature = "std")]
+    use amplify::num::apfloat::{ieee, Float};
+
+    use super::*;
+    use crate::data::ByteStr;
+    #[cfg(feature = "std")]
+    use crate::data::MaybeNumber;
+    #[cfg(feature = "std")]
+    use crate::isa::PutOp;
+    use crate::isa::{
+        ArithmeticOp, Bytecode, BytecodeError, BytesOp, ControlFlowOp, DigestOp, ExecStep,
+        IntFlags, SizedCost,
+    };
+    use crate::library::{CodeEofError, IsaSeg, LibId, Read, Write};
+    #[cfg(feature = "std")]
+    use crate::reg::RegF;
+    use crate::reg::{Reg, Reg16, Reg32, RegA, RegS};
+
+    fn run(code: &[ControlFlowOp]) -> ExecReport { run_limited(code, ExecLimits::default()).0 }
+
+    fn run_limited(code: &[ControlFlowOp], limits: ExecLimits) -> (ExecReport, Vm) {
+        let code = code.iter().cloned().map(Instr::ControlFlow).collect::<Vec<Instr>>();
+        let lib = Lib::assemble(&code).unwrap();
+        let mut vm = Vm::<Instr>::with_limits(limits);
+        let report = vm.run(LibSite::with(0, lib.id()), |_: LibId| Some(&lib), &());
+        (report, vm)
+    }
+
+    #[test]
+    fn halt_reasons() {
+        let report = run(&[ControlFlowOp::Test]);
+        assert_eq!(report.halt, HaltReason::EndOfCode);
+        assert_eq!(report.site.pos, 1);
+        assert!(report.status);
+
+        let report = run(&[ControlFlowOp::Fail]);
+        assert_eq!(report.halt, HaltReason::Fail);
+        assert!(!report.status);
+
+        let report = run(&[ControlFlowOp::Jmp(0x10)]);
+        assert_eq!(report.halt, HaltReason::InvalidJump);
+        assert_eq!(report.site.pos, 0);
+        assert!(!report.status);
+
+        let report = run(&[ControlFlowOp::Jmp(0)]);
+        assert_eq!(report.halt, HaltReason::CycleLimit);
+        assert_eq!(report.cycles, u16::MAX);
+        assert!(!report.status);
+    }
+
+    #[test]
+    fn routine_return() {
+        let report = run(&[ControlFlowOp::Routine(4), ControlFlowOp::Ret, ControlFlowOp::Ret]);
+        assert_eq!(report.halt, HaltReason::Stop);
+        assert_eq!(report.site.pos, 3);
+        assert_eq!(report.cycles, 1);
+        assert_eq!(report.complexity, 6);
+        assert_eq!(report.call_depth, 0);
+        assert!(report.status);
+
+        let report = run(&[
+            ControlFlowOp::Routine(4),
+            ControlFlowOp::Ret,
+            ControlFlowOp::Routine(8),
+            ControlFlowOp::Ret,
+            ControlFlowOp::Fail,
+        ]);
+        assert_eq!(report.halt, HaltReason::Fail);
+        assert_eq!(report.site.pos, 8);
+        assert_eq!(report.cycles, 2);
+        assert_eq!(report.call_depth, 2);
+        assert!(!report.status);
+    }
+
+    #[test]
+    fn limits() {
+        let limits = ExecLimits {
+            complexity: Some(5),
+            ..ExecLimits::default()
+        };
+        let code = [ControlFlowOp::Test, ControlFlowOp::Test, ControlFlowOp::Test];
+        let (report, vm) = run_limited(&code, limits);
+        assert_eq!(report.halt, HaltReason::ComplexityLimit);
+        assert_eq!(report.site.pos, 2);
+        assert_eq!(report.complexity, 6);
+        assert_eq!(vm.registers.remaining_complexity(), Some(0));
+        assert!(!report.status);
+
+        let limits = ExecLimits {
+            cycles: 10,
+            ..ExecLimits::default()
+        };
+        let (report, vm) = run_limited(&[ControlFlowOp::Jmp(0)], limits);
+        assert_eq!(report.halt, HaltReason::CycleLimit);
+        assert_eq!(report.cycles, 10);
+        assert_eq!(vm.registers.remaining_cycles(), 0);
+        assert_eq!(vm.registers.remaining_complexity(), None);
+
+        let limits = ExecLimits {
+            call_depth: 3,
+            ..ExecLimits::default()
+        };
+        let (report, vm) = run_limited(&[ControlFlowOp::Routine(0)], limits);
+        assert_eq!(report.halt, HaltReason::CallStackOverflow);
+        assert_eq!(report.call_depth, 3);
+        assert_eq!(vm.registers.remaining_call_depth(), 0);
+        assert_eq!(vm.registers.remaining_cycles(), u16::MAX - 4);
+
+        let nested = [
+            ControlFlowOp::Routine(4),
+            ControlFlowOp::Ret,
+            ControlFlowOp::Routine(8),
+            ControlFlowOp::Ret,
+            ControlFlowOp::Routine(12),
+            ControlFlowOp::Ret,
+            ControlFlowOp::Ret,
+        ];
+        let limits = ExecLimits {
+            call_depth: 2,
+            ..ExecLimits::default()
+        };
+        let (report, vm) = run_limited(&nested, limits);
+        assert_eq!(report.halt, HaltReason::CallStackOverflow);
+        assert_eq!(report.site.pos, 8);
+        assert_eq!(report.call_depth, 2);
+        assert_eq!(vm.registers.remaining_call_depth(), 0);
+        assert!(!report.status);
+
+        let limits = ExecLimits {
+            call_depth: 3,
+            ..ExecLimits::default()
+        };
+        let (report, vm) = run_limited(&nested, limits);
+        assert_eq!(report.halt, HaltReason::Stop);
+        assert_eq!(report.call_depth, 0);
+        assert_eq!(vm.registers.remaining_call_depth(), 3);
+        assert!(report.status);
+    }
+
+    #[test]
+    fn cost_schedule() {
+        let code: Vec<Instr> = vec![
+            Instr::Bytes(BytesOp::Put(RegS::from(0), Box::new(ByteStr::with([0u8; 64])), false)),
+            Instr::Digest(DigestOp::Sha256(RegS::from(0), Reg16::Reg0)),
+        ];
+        let lib = Lib::assemble(&code).unwrap();
+        let site = LibSite::with(0, lib.id());
+
+        let mut vm = Vm::<Instr>::new();
+        let report = vm.run(site, |_: LibId| Some(&lib), &());
+        assert_eq!(report.complexity, 5 + 100);
+
+        let mut vm = Vm::<Instr, _>::with_cost(SizedCost { per_byte: 1 });
+        let report = vm.run(site, |_: LibId| Some(&lib), &());
+        assert_eq!(report.complexity, 5 + 100 + 64);
+
+        // Register used twice is charged once
+        let flags = IntFlags {
+            signed: false,
+            wrap: false,
+        };
+        let add: Instr =
+            Instr::Arithmetic(ArithmeticOp::AddA(flags, RegA::A64, Reg32::Reg1, Reg32::Reg1));
+        let cost = SizedCost { per_byte: 1 }.cost(&add, &CoreRegs::new());
+        assert_eq!(cost, add.complexity() + 8);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn snapshot_restore() {
+        let code: Vec<Instr> = vec![
+            Instr::ControlFlow(ControlFlowOp::Routine(5)),
+            Instr::ControlFlow(ControlFlowOp::Test),
+            Instr::ControlFlow(ControlFlowOp::Ret),
+            Instr::Put(PutOp::PutA(RegA::A16, Reg32::Reg1, Box::new(MaybeNumber::from(7u16)))),
+            Instr::Put(PutOp::PutF(
+                RegF::F32,
+                Reg32::Reg2,
+                Box::new(ieee::Single::SMALLEST.into()),
+            )),
+            Instr::Bytes(BytesOp::Put(RegS::from(3), Box::new(ByteStr::with(b"snapshot")), false)),
+            Instr::Digest(DigestOp::Sha256(RegS::from(3), Reg16::Reg1)),
+            Instr::ControlFlow(ControlFlowOp::Ret),
+        ];
+        let lib = Lib::assemble(&code).unwrap();
+        let entry = LibSite::with(0, lib.id());
+        let limits = ExecLimits {
+            complexity: Some(1000),
+            ..ExecLimits::default()
+        };
+
+        let mut vm = Vm::<Instr>::with_limits(limits);
+        let expected = vm.run(entry, |_: LibId| Some(&lib), &());
+
+        let mut vm = Vm::<Instr>::with_limits(limits);
+        let mut debugger = vm.debugger(entry, |_: LibId| Some(&lib));
+        for _ in 0..5 {
+            debugger.step(&());
         }
-        self.registers.st0
+        assert_eq!(debugger.call_stack(), &[LibSite::with(3, lib.id())]);
+        let data = debugger.snapshot().serialize();
+
+        let snapshot = VmSnapshot::deserialize(data).unwrap();
+        assert_eq!(snapshot.registers.call_depth(), 1);
+        assert_eq!(snapshot.registers.call_stack(), &[LibSite::with(3, lib.id())]);
+        assert_eq!(snapshot.registers.get_n(RegA::A16, Reg32::Reg1), MaybeNumber::from(7u16));
+        assert_eq!(snapshot.registers.call_stack(), vm.registers.call_stack());
+        assert_eq!(snapshot.registers.limits(), limits);
+        let mut vm = Vm::<Instr>::new();
+        let site = vm.restore(snapshot);
+        let report = vm.run(site, |_: LibId| Some(&lib), &());
+        assert_eq!(report, expected);
+        assert_eq!(vm.registers.serialize(), {
+            let mut vm = Vm::<Instr>::with_limits(limits);
+            vm.run(entry, |_: LibId| Some(&lib), &());
+            vm.registers.serialize()
+        });
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn snapshot_limits() {
+        let limits = ExecLimits {
+            cycles: 2,
+            call_depth: 1,
+            ..ExecLimits::default()
+        };
+        let mut regs = CoreRegs::with_limits(limits);
+        regs.cy0 = 2;
+        regs.cp0 = 1;
+        regs.cs0.push(LibSite::default());
+        let decoded = CoreRegs::deserialize(regs.serialize()).unwrap();
+        assert_eq!(decoded.serialize(), regs.serialize());
+
+        regs.cy0 = 3;
+        let err = CoreRegs::deserialize(regs.serialize()).unwrap_err();
+        assert_eq!(err, DecodeError::CycleLimit(3, 2));
+        regs.cy0 = 2;
+        regs.cp0 = 2;
+        regs.cs0.push(LibSite::default());
+        let err = CoreRegs::deserialize(regs.serialize()).unwrap_err();
+        assert_eq!(err, DecodeError::CallDepth(2, 1));
+    }
+
+    /// Extension instruction requesting the host to put a value into `a16[0]` register
+    #[derive(Copy, Clone, PartialEq, Eq, Debug, Display)]
+    #[display("pull    {0}")]
+    struct PullOp(u8);
+
+    impl Bytecode for PullOp {
+        fn instr_range() -> RangeInclusive<u8> { 0b11_000_000..=0b11_000_000 }
+
+        fn instr_byte(&self) -> u8 { 0b11_000_000 }
+
+        fn encode_args<W>(&self, writer: &mut W) -> Result<(), BytecodeError>
+        where W: Write {
+            writer.write_u8(self.0)?;
+            Ok(())
+        }
+
+        fn decode<R>(reader: &mut R) -> Result<Self, CodeEofError>
+        where R: Read {
+            reader.read_u8()?;
+            Ok(PullOp(reader.read_u8()?))
+        }
+    }
+
+    impl InstructionSet for PullOp {
+        type Context<'ctx> = ();
+
+        fn isa_ids() -> IsaSeg { IsaSeg::default() }
+
+        fn src_regs(&self) -> BTreeSet<Reg> { none!() }
+
+        fn dst_regs(&self) -> BTreeSet<Reg> { bset![Reg::A(RegA::A16, Reg32::Reg0)] }
+
+        fn complexity(&self) -> u64 { 1 }
+
+        fn exec(&self, _: &mut CoreRegs, _: LibSite, _: &()) -> ExecStep {
+            ExecStep::Yield(self.0 as u16)
+        }
+    }
+
+    #[test]
+    fn yield_resume() {
+        let add = Instr::Arithmetic(ArithmeticOp::AddA(
+            IntFlags::unsigned_checked(),
+            RegA::A16,
+            Reg32::Reg0,
+            Reg32::Reg1,
+        ));
+        let code =
+            [Instr::ExtensionCodes(PullOp(1)), add.clone(), Instr::ExtensionCodes(PullOp(2)), add];
+        let lib = Lib::assemble(&code).unwrap();
+        let mut vm = Vm::<Instr<PullOp>>::new();
+        vm.registers.set_n(RegA::A16, Reg32::Reg1, 0u16);
+
+        let mut report = vm.run(LibSite::with(0, lib.id()), |_: LibId| Some(&lib), &());
+        let mut requests = vec![];
+        while let Some(request) = report.yielded() {
+            requests.push(request);
+            vm.registers.set_n(RegA::A16, Reg32::Reg0, request * 10);
+            report = vm.resume(report, |_: LibId| Some(&lib), &());
+        }
+        assert_eq!(requests, [1, 2]);
+        assert_eq!(report.halt, HaltReason::EndOfCode);
+        assert!(report.status);
+        assert_eq!(vm.registers.a16(Reg32::Reg1), Some(30));
+        assert_eq!(vm.resume(report, |_: LibId| Some(&lib), &()), report);
+    }
+
+    #[test]
+    fn yield_exec() {
+        let lib = Lib::assemble(&[Instr::ExtensionCodes(PullOp(1))]).unwrap();
+        let mut vm = Vm::<Instr<PullOp>>::new();
+        assert!(!vm.exec(LibSite::with(0, lib.id()), |_: LibId| Some(&lib), &()));
+        assert!(vm.registers.status());
+    }
+
+    #[test]
+    fn missing_lib() {
+        let mut vm = Vm::<Instr>::new();
+        let site = LibSite::with(0, LibId::default());
+        let report = vm.run(site, |_: LibId| None, &());
+        assert_eq!(report.halt, HaltReason::LibNotFound(LibId::default()));
+        assert_eq!(report.site, site);
+        assert!(!report.status);
+
+        let callee = Lib::assemble::<Instr>(&[Instr::ControlFlow(ControlFlowOp::Ret)]).unwrap();
+        let code: [Instr; 2] = [
+            Instr::ControlFlow(ControlFlowOp::Test),
+            Instr::ControlFlow(ControlFlowOp::Call(LibSite::with(0, callee.id()))),
+        ];
+        let lib = Lib::assemble(&code).unwrap();
+        let site = LibSite::with(0, lib.id());
+        let resolver = |id: LibId| (id == lib.id()).then_some(&lib);
+
+        let mut vm = Vm::<Instr>::new();
+        let report = vm.run(site, resolver, &());
+        assert_eq!(report.halt, HaltReason::LibNotFound(callee.id()));
+        assert_eq!(report.site, LibSite::with(0, callee.id()));
+        assert_eq!(report.complexity, 2 + 2);
+        assert!(!report.status);
+
+        let mut vm = Vm::<Instr>::new();
+        vm.lib_policy = LibPolicy::Preflight;
+        let report = vm.run(site, resolver, &());
+        assert_eq!(report.halt, HaltReason::LibNotFound(callee.id()));
+        assert_eq!(report.site, site);
+        assert_eq!(report.complexity, 0);
+        assert!(!report.status);
+    }
+
+    #[test]
+    fn unverified_lib() {
+        let callee =
+            Lib::assemble::<Instr>(&[Instr::ControlFlow(ControlFlowOp::Jmp(100))]).unwrap();
+        let code: [Instr; 2] = [
+            Instr::ControlFlow(ControlFlowOp::Test),
+            Instr::ControlFlow(ControlFlowOp::Call(LibSite::with(0, callee.id()))),
+        ];
+        let lib = Lib::assemble(&code).unwrap();
+        let site = LibSite::with(0, lib.id());
+        let resolver = |id: LibId| [&lib, &callee].into_iter().find(|lib| lib.id() == id);
+
+        let mut vm = Vm::<Instr>::new();
+        let report = vm.run(site, resolver, &());
+        assert_eq!(report.halt, HaltReason::InvalidJump);
+
+        let mut vm = Vm::<Instr>::new();
+        vm.verify_libs = true;
+        let report = vm.run(site, resolver, &());
+        assert_eq!(report.halt, HaltReason::Unverified(callee.id()));
+        assert_eq!(report.site, LibSite::with(0, callee.id()));
+        assert_eq!(report.complexity, 2 + 2);
+        assert!(!report.status);
     }
 }
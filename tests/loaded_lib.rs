@@ -0,0 +1,89 @@
+// Reference rust implementation of AluVM (arithmetic logic unit virtual machine).
+// To find more on AluVM please check <https://aluvm.org>
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2021-2024 by
+//     Dr Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright (C) 2021-2022 LNP/BP Standards Association. All rights reserved.
+// Copyright (C) 2023-2024 UBIDECO Labs,
+//     Institute for Distributed and Cognitive Computing, Switzerland.
+//     All rights reserved.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Memory used by [`LoadedLib`] for untrusted libraries. Runs as a separate test binary, since it
+//! replaces the global allocator for measuring the peak heap usage.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use aluvm::isa::opcodes::INSTR_PUT;
+use aluvm::isa::{Instr, InstructionSet};
+use aluvm::library::constants::{
+    CODE_SEGMENT_MAX_LEN, DATA_SEGMENT_MAX_LEN, LOADED_CACHE_MAX_SIZE,
+};
+use aluvm::library::{Lib, LoadedLib};
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+/// System allocator tracking the peak size of the allocated memory.
+struct PeakAlloc;
+
+unsafe impl GlobalAlloc for PeakAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let current = CURRENT.fetch_add(layout.size(), Ordering::SeqCst) + layout.size();
+            PEAK.fetch_max(current, Ordering::SeqCst);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        CURRENT.fetch_sub(layout.size(), Ordering::SeqCst);
+    }
+}
+
+#[global_allocator]
+static ALLOC: PeakAlloc = PeakAlloc;
+
+/// Returns the peak memory allocated while loading the library.
+fn load_peak(code: Vec<u8>, data: Vec<u8>) -> usize {
+    let lib = Lib::with(&<Instr>::isa_ids().to_string(), code, data, Default::default()).unwrap();
+    let base = CURRENT.load(Ordering::SeqCst);
+    PEAK.store(base, Ordering::SeqCst);
+    let loaded = LoadedLib::<Instr>::new(lib);
+    let peak = PEAK.load(Ordering::SeqCst) - base;
+    drop(loaded);
+    peak
+}
+
+#[test]
+fn bounded_memory() {
+    let limit = 2 * LOADED_CACHE_MAX_SIZE;
+
+    // `put` instruction decodes at every offset, loading a part of the data segment
+    let peak =
+        load_peak(vec![INSTR_PUT; CODE_SEGMENT_MAX_LEN], vec![INSTR_PUT; DATA_SEGMENT_MAX_LEN]);
+    assert!(peak < limit, "{peak} bytes allocated");
+
+    // Each `put` instruction loads the whole data segment
+    let put = [INSTR_PUT, 0x00, 0x00, 0x00, 0xFF, 0xFF];
+    let code = put.repeat(CODE_SEGMENT_MAX_LEN / put.len());
+    let peak = load_peak(code, vec![0; DATA_SEGMENT_MAX_LEN]);
+    assert!(peak < limit, "{peak} bytes allocated");
+}